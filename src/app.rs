@@ -1,13 +1,27 @@
+use crate::app_state::{Action, Effect};
+use crate::components::bulk_rename_dialog::BulkRenameDialog;
+use crate::components::dual_pane_control::DualPaneControl;
 use crate::components::fav_dir_bar::FavoriteDirSidebar;
 use crate::components::header_control::HeaderControl;
 use crate::components::load_components;
+use crate::components::preview_pane::PreviewPane;
+use crate::components::search_help_dialog::SearchHelpDialog;
 use crate::components::search_result_control::SearchResultControl;
 use crate::components::status_bar_control::StatusBarControl;
+use crate::components::tab_bar_control::TabBarControl;
+use crate::dual_pane::{self, Side};
 use crate::event_handler;
+use crate::export::{self, ExportFormat, ExportOptions};
+use crate::file_operations::{Conflict, ConflictPolicy, OperationReport};
+use crate::journal::{self, JournalEntry};
+use crate::logging;
 use crate::memory::cache::Cache;
 use crate::resource_manager::Resources;
+use crate::search_engine::result::SearchEngineResult;
+use crate::tabs::{self, Tabs};
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::path::PathBuf;
 use std::rc::Rc;
 
 #[derive(Default)]
@@ -15,37 +29,427 @@ pub struct BasicApp {
     pub window: nwg::Window,
     pub body_layout: nwg::GridLayout,
     pub file_dialog: nwg::FileDialog,
+    pub export_dialog: nwg::FileDialog,
+    pub result_export_dialog: nwg::FileDialog,
+    pub target_dialog: nwg::FileDialog,
     pub header: HeaderControl,
+    pub tab_bar: TabBarControl,
     pub search_results: SearchResultControl,
+    pub dual_pane: DualPaneControl,
+    pub preview: PreviewPane,
     pub fav_dir_bar: FavoriteDirSidebar,
     pub resource_manager: Resources,
     pub status_bar: StatusBarControl,
+    pub bulk_rename: BulkRenameDialog,
+    pub search_help: SearchHelpDialog,
     pub cache: Cache,
+    /// Generation of the results the list shows
+    rendered_generation: Cell<u64>,
 }
 
 impl BasicApp {
     pub fn display_error(&self, e: String) {
+        crate::error!("{}", e);
         nwg::modal_error_message(&self.window, "Error occurred", &e);
     }
+
+    /// Runs the action on the state, carries out what it asks for and renders the result
+    pub fn dispatch(&self, action: Action) {
+        //Sorting would tear the duplicate sets apart
+        if matches!(action, Action::Sort(_)) && !self.cache.duplicates.borrow().is_empty() {
+            return;
+        }
+        let keeps_labels = matches!(action, Action::Sort(_) | Action::ShowResults(_));
+
+        let (effect, replaced) = {
+            let mut tabs = self.cache.tabs.borrow_mut();
+            let state = tabs.active_mut();
+            let generation = state.generation;
+            let effect = state.dispatch(action);
+            (effect, state.generation != generation)
+        };
+        if replaced && !keeps_labels {
+            self.cache.duplicates.borrow_mut().clear();
+            self.cache.usage_total.set(None);
+        }
+        //Sorting moves the rows as well
+        if replaced {
+            self.cache.dual_pane.borrow_mut().comparison = None;
+        }
+
+        match effect {
+            Some(Effect::Open(path)) => self.open_file(&path),
+            Some(Effect::ScanDiskUsage { path, skip }) => {
+                let use_index = self.status_bar.index_usage.check_state() == nwg::CheckBoxState::Checked;
+                self.cache.duplicates.borrow_mut().clear();
+                let results = self.search_results.show_disk_usage(&self.cache, &path, skip, use_index);
+                self.cache.tabs.borrow_mut().active_mut().dispatch(Action::ShowResults(results));
+            }
+            Some(Effect::Error(e)) => self.display_error(e),
+            None => {}
+        }
+
+        self.render();
+    }
+
+    /// Same as `dispatch` for the right pane, which has no disk usage or duplicates
+    pub fn dispatch_right(&self, action: Action) {
+        let effect = {
+            let mut dual = self.cache.dual_pane.borrow_mut();
+            let generation = dual.right.generation;
+            let effect = dual.right.dispatch(action);
+            if dual.right.generation != generation {
+                dual.comparison = None;
+            }
+            effect
+        };
+
+        match effect {
+            Some(Effect::Open(path)) => self.open_file(&path),
+            Some(Effect::Error(e)) => self.display_error(e),
+            //Disk usage is never turned on in the right pane
+            Some(Effect::ScanDiskUsage { .. }) | None => {}
+        }
+
+        self.render();
+    }
+
+    /// Dispatches to the pane that was used last
+    pub fn dispatch_active(&self, action: Action) {
+        let right = {
+            let dual = self.cache.dual_pane.borrow();
+            dual.enabled && dual.active == Side::Right
+        };
+        match right {
+            true => self.dispatch_right(action),
+            false => self.dispatch(action),
+        }
+    }
+
+    /// Splits the list into two panes, the right one starts in the directory of the left one
+    pub fn set_dual_pane(&self, enabled: bool) {
+        if enabled && self.preview.is_visible() {
+            self.preview.set_visible(false, &self.search_results.list);
+        }
+        self.preview.toggle_btn.set_enabled(!enabled);
+        self.dual_pane.set_visible(enabled, &self.search_results.list, &self.tab_bar);
+        self.dual_pane.set_active(Side::Left, &self.search_results.list);
+
+        let start = {
+            let mut dual = self.cache.dual_pane.borrow_mut();
+            dual.enabled = enabled;
+            dual.active = Side::Left;
+            dual.comparison = None;
+            dual.right.show_hidden = self.cache.settings.borrow().show_hidden_files;
+            match enabled && !dual.right.is_loaded() {
+                true => Some(self.cache.tabs.borrow().active().path.clone()),
+                false => None,
+            }
+        };
+        match start {
+            Some(path) => self.dispatch_right(Action::Navigate(path)),
+            None => self.render(),
+        }
+    }
+
+    pub fn activate_pane(&self, side: Side) {
+        {
+            let mut dual = self.cache.dual_pane.borrow_mut();
+            if !dual.enabled || dual.active == side {
+                return;
+            }
+            dual.active = side;
+        }
+        self.dual_pane.set_active(side, &self.search_results.list);
+    }
+
+    /// Directory of the pane, copies and moves to the other pane end up there
+    pub fn pane_dir(&self, side: Side) -> String {
+        match side {
+            Side::Left => self.cache.tabs.borrow().active().dir(),
+            Side::Right => self.cache.dual_pane.borrow().right.dir(),
+        }
+    }
+
+    /// Selects what differs on both sides, until either side lists something else
+    pub fn compare_panes(&self) {
+        let comparison = {
+            let tabs = self.cache.tabs.borrow();
+            let dual = self.cache.dual_pane.borrow();
+            dual_pane::compare(&tabs.active().results, &dual.right.results)
+        };
+        self.status_bar.task_progress.set_text(&comparison.summary());
+        self.search_results.select_rows(&comparison.rows(Side::Left));
+        self.cache.dual_pane.borrow_mut().comparison = Some(comparison);
+        self.render();
+    }
+
+    fn open_file(&self, path: &str) {
+        if let Err(e) = open::that(path) {
+            crate::warn!("Failed to open {}", path; error = e);
+        }
+    }
+
+    /// Brings the widgets up to date with the state
+    pub fn render(&self) {
+        let (path, query, titles, active) = {
+            let tabs = self.cache.tabs.borrow();
+            let state = tabs.active();
+            (state.path.clone(), state.query.clone(), tabs.titles(), tabs.active_index())
+        };
+        if self.header.path_bar.view.text().trim() != path {
+            self.header.path_bar.view.set_text(&path);
+        }
+        //Fires the text event, which ignores the query it already knows
+        if self.header.search_input.text() != query {
+            self.header.search_input.set_text(&query);
+        }
+        if let Err(e) = self.header.path_bar.render_breadcrumbs(&self.window) {
+            self.display_error(e.to_string());
+        }
+        if let Err(e) = self.tab_bar.render(&self.window, &titles, active) {
+            self.display_error(e.to_string());
+        }
+
+        let tabs = self.cache.tabs.borrow();
+        let state = tabs.active();
+        if state.generation != self.rendered_generation.get() {
+            self.rendered_generation.set(state.generation);
+            self.search_results.refresh(&state.results);
+        }
+        let count = match &state.status.error {
+            Some(e) => e.clone(),
+            None => format!(
+                "{} results, {}",
+                state.results.len(),
+                self.cache.formatter.borrow().size(state.total_size())
+            ),
+        };
+        self.status_bar.result_count.set_text(&count);
+        //Tabs restored from the session havent searched yet
+        let duration = match state.status.duration {
+            Some(d) => format!("{}ms", d.as_millis()),
+            None => String::new(),
+        };
+        self.status_bar.search_duration.set_text(&duration);
+        self.header.last_page_btn.set_enabled(state.can_go_back());
+        self.header.parent_page_btn.set_enabled(state.can_go_up());
+
+        let dual = self.cache.dual_pane.borrow();
+        if dual.enabled {
+            self.dual_pane.render(&dual.right, dual.comparison.as_ref());
+        }
+    }
+
+    /// Opens the directory in a tab next to the active one
+    pub fn open_tab(&self, path: String, activate: bool) {
+        let mut state = self.cache.tabs.borrow().active().sibling();
+        if let Some(Effect::Error(e)) = state.dispatch(Action::Navigate(path)) {
+            self.display_error(e);
+            return;
+        }
+        let index = self.cache.tabs.borrow_mut().open(state);
+        match activate {
+            true => self.switch_tab(index),
+            false => self.render(),
+        }
+    }
+
+    /// Another tab in the current directory
+    pub fn new_tab(&self) {
+        let path = self.cache.tabs.borrow().active().path.clone();
+        self.open_tab(path, true);
+    }
+
+    pub fn switch_tab(&self, index: usize) {
+        let switched = self.cache.tabs.borrow_mut().switch(index);
+        match switched {
+            true => self.show_active_tab(),
+            //Clicking the active tab unchecks it
+            false => self.render(),
+        }
+    }
+
+    pub fn close_tab(&self, index: usize) {
+        let was_active = self.cache.tabs.borrow().active_index() == index;
+        let closed = self.cache.tabs.borrow_mut().close(index);
+        match closed && was_active {
+            true => self.show_active_tab(),
+            false => self.render(),
+        }
+    }
+
+    pub fn save_session(&self) {
+        if let Err(e) = self.cache.tabs.borrow().save(tabs::session_path()) {
+            crate::error!("{}", e);
+        }
+    }
+
+    /// Shows the active tab after switching. Hidden files and disk usage follow the checkboxes,
+    /// so the tab is listed again if it was left with other ones or was never listed at all.
+    fn show_active_tab(&self) {
+        self.cache.duplicates.borrow_mut().clear();
+        self.cache.usage_total.set(None);
+        self.cache.dual_pane.borrow_mut().comparison = None;
+        //No tab reaches this generation, the list is drawn again in any case
+        self.rendered_generation.set(u64::MAX);
+
+        let show_hidden = self.cache.settings.borrow().show_hidden_files;
+        let disk_usage = self.status_bar.disk_usage.check_state() == nwg::CheckBoxState::Checked;
+        let (empty, reload) = {
+            let mut tabs = self.cache.tabs.borrow_mut();
+            let state = tabs.active_mut();
+            let reload = !state.is_loaded() || disk_usage || state.show_hidden != show_hidden;
+            //Set without an action, the reload below lists with them
+            state.show_hidden = show_hidden;
+            state.disk_usage = disk_usage;
+            (state.path.is_empty(), reload)
+        };
+
+        if empty {
+            self.dispatch(Action::Navigate(self.header.path_bar.view.text()));
+        } else if reload {
+            self.dispatch(Action::Refresh);
+        } else {
+            self.render();
+        }
+    }
+
+    /// Adds the finished part of the operation to the journal, so it can be undone even after a restart
+    pub fn record_operation(&self, report: &OperationReport) {
+        if let Some(entry) = JournalEntry::from_report(report) {
+            let mut journal = self.cache.journal.borrow_mut();
+            journal.record(entry);
+            if let Err(e) = journal.save(journal::journal_path()) {
+                self.status_bar.task_progress.set_text(&e);
+            }
+        }
+    }
+
+    /// Previews the selected file if the pane is open and a single row is selected
+    pub fn update_preview(&self) {
+        if !self.preview.is_visible() {
+            return;
+        }
+        if let Some(result) = self.search_results.single_selected(&self.cache) {
+            self.preview.show_path(&result.full_path, &self.cache.formatter.borrow());
+        }
+    }
+
+    /// Undoes the last operation, or redoes the last undone one. The listing is refreshed afterwards.
+    pub fn undo_operation(&self, redo: bool) {
+        let result = {
+            let mut journal = self.cache.journal.borrow_mut();
+            let result = match redo {
+                true => journal.redo().map(|d| format!("Redone: {}", d)),
+                false => journal.undo().map(|d| format!("Undone: {}", d)),
+            };
+            let _ = journal.save(journal::journal_path());
+            result
+        };
+
+        match result {
+            Ok(status) => {
+                self.status_bar.task_progress.set_text(&status);
+                self.dispatch(Action::Refresh);
+            }
+            Err(e) => self.display_error(e),
+        }
+    }
+
+    /// Yes overwrites the existing file, no keeps both and cancel skips it
+    pub fn ask_conflict(&self, conflict: &Conflict) -> ConflictPolicy {
+        let content = format!(
+            "{} already exists.\n\nYes: Replace it\nNo: Keep both\nCancel: Skip this one",
+            conflict.target
+        );
+        let choice = nwg::modal_message(&self.window, &nwg::MessageParams {
+            title: "File already exists",
+            content: &content,
+            buttons: nwg::MessageButtons::YesNoCancel,
+            icons: nwg::MessageIcons::Warning,
+        });
+
+        match choice {
+            nwg::MessageChoice::Yes => ConflictPolicy::Overwrite,
+            nwg::MessageChoice::No => ConflictPolicy::KeepBoth,
+            _ => ConflictPolicy::Skip,
+        }
+    }
+
+    /// Asks for the target file, the extension of the format is added if it has none
+    pub fn export_results(&self, results: &[SearchEngineResult], format: ExportFormat) {
+        if !self.result_export_dialog.run(Some(&self.window)) {
+            return;
+        }
+        let mut target = match self.result_export_dialog.get_selected_item() {
+            Ok(t) => PathBuf::from(t),
+            Err(_) => return,
+        };
+        if target.extension().is_none() {
+            target.set_extension(format.extension());
+        }
+
+        let options = ExportOptions {
+            format,
+            columns: self.cache.settings.borrow().export_columns.clone(),
+        };
+        match export::write_file(results, &options, &target) {
+            Ok(()) => self
+                .status_bar
+                .task_progress
+                .set_text(&format!("Exported {} results to {}", results.len(), target.display())),
+            Err(e) => self.display_error(e),
+        }
+    }
+
+    pub fn choose_directory(&self) -> Option<String> {
+        if !self.target_dialog.run(Some(&self.window)) {
+            return None;
+        }
+        self.target_dialog
+            .get_selected_item()
+            .ok()
+            .map(|p| p.to_string_lossy().to_string())
+    }
 }
 
 pub struct BasicAppUi {
     pub inner: Rc<BasicApp>,
     pub default_handler: RefCell<Option<nwg::EventHandler>>,
+    pub raw_handlers: RefCell<Vec<nwg::RawEventHandler>>,
+    /// Windows other than the main one need their own handler
+    pub dialog_handlers: RefCell<Vec<nwg::EventHandler>>,
 }
 
 impl nwg::NativeUi<BasicAppUi> for BasicApp {
     fn build_ui(mut data: BasicApp) -> Result<BasicAppUi, nwg::NwgError> {
+        //The explorer works without a log, there is no window yet to complain in
+        let level = data.cache.settings.borrow().log_level;
+        let _ = logging::init(&logging::default_log_dir(), level);
+        crate::info!("Starting"; version = env!("CARGO_PKG_VERSION"));
         load_components(&mut data)?;
 
         let mut ui = BasicAppUi {
             inner: Rc::new(data),
             default_handler: Default::default(),
+            raw_handlers: Default::default(),
+            dialog_handlers: Default::default(),
         };
 
         event_handler::handle_events(&mut ui);
+        event_handler::handle_list_notifications(&mut ui)?;
+        event_handler::handle_middle_clicks(&mut ui)?;
 
-        ui.inner.header.search_input.set_text("");
+        let app = &ui.inner;
+        //Without a session the explorer starts with one tab in the directory of the path bar
+        match Tabs::load(tabs::session_path()) {
+            Ok(tabs) => {
+                app.cache.tabs.replace(tabs);
+            }
+            Err(e) => crate::debug!("No session restored"; reason = e),
+        }
+        app.show_active_tab();
 
         Ok(ui)
     }
@@ -58,5 +462,11 @@ impl Drop for BasicAppUi {
         if handler.is_some() {
             nwg::unbind_event_handler(handler.as_ref().unwrap());
         }
+        for dialog in self.dialog_handlers.borrow().iter() {
+            nwg::unbind_event_handler(dialog);
+        }
+        for raw in self.raw_handlers.borrow().iter() {
+            let _ = nwg::unbind_raw_event_handler(raw);
+        }
     }
 }
@@ -0,0 +1,353 @@
+use std::{
+    fs, mem,
+    path::{Path, MAIN_SEPARATOR},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ring_buffer::RingBuffer,
+    search_engine::{
+        directory_listing::{DirectoryListing, ListingOptions},
+        parameter_parser::SearchEngineParameter,
+        result::SearchEngineResult,
+        search_help::SearchHelp,
+        sort::{self, SortKey, SortOrder},
+        SearchEngine,
+    },
+};
+
+/// Everything the user does that changes what the explorer shows
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Into a directory, the current one is remembered for `Back`
+    Navigate(String),
+    Back,
+    Up,
+    /// Searches below the current directory, an empty query lists it
+    Search(String),
+    /// Runs the current query again, e.g. after files changed
+    Refresh,
+    /// Reverses the order if the key already is the primary one
+    Sort(SortKey),
+    ShowHidden(bool),
+    /// Lists the directories by their recursive size instead
+    DiskUsage(bool),
+    /// Results that were made elsewhere, e.g. duplicate sets or the disk usage
+    ShowResults(Vec<SearchEngineResult>),
+    /// Double click on a row, directories are opened and files handed to the gui
+    Activate(usize),
+}
+
+/// What the gui has to do after an action, the state cant do it itself
+#[derive(Debug, Clone)]
+pub enum Effect {
+    /// Opens the file with its default program
+    Open(String),
+    /// The sizes are calculated by the gui, it answers with `ShowResults`
+    ScanDiskUsage { path: String, skip: SearchEngineParameter },
+    /// The action failed and nothing changed
+    Error(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Status {
+    /// Why the query isnt run, e.g. while it is typed. The last results stay.
+    pub error: Option<String>,
+    /// Time of the last listing or search
+    pub duration: Option<Duration>,
+}
+
+/// What of a tab survives a restart, the results are listed again
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedTab {
+    pub path: String,
+    #[serde(default)]
+    pub query: String,
+    #[serde(default)]
+    pub sort: SortOrder,
+    #[serde(default)]
+    pub history: RingBuffer<String>,
+}
+
+/// What the explorer shows in one tab, changed only through `dispatch`
+#[derive(Default)]
+pub struct AppState {
+    /// The current directory without a trailing separator, e.g. `C:\Users`
+    pub path: String,
+    pub query: String,
+    pub results: Vec<SearchEngineResult>,
+    pub sort: SortOrder,
+    pub show_hidden: bool,
+    pub disk_usage: bool,
+    pub status: Status,
+    /// Filters of the query
+    pub help: SearchHelp,
+    /// Counts up whenever `results` is replaced, so the gui only redraws the list then
+    pub generation: u64,
+    history: RingBuffer<String>,
+}
+
+impl AppState {
+    pub fn dispatch(&mut self, action: Action) -> Option<Effect> {
+        match action {
+            Action::Navigate(path) => {
+                let path = normalize(&path);
+                if path.is_empty() {
+                    return Some(Effect::Error(String::from("No path found")));
+                }
+                if let Err(e) = check_dir(&path) {
+                    return Some(Effect::Error(e));
+                }
+                let previous = self.path.clone();
+                match self.change_dir(path) {
+                    Ok(effect) => {
+                        if !previous.is_empty() && previous != self.path {
+                            self.history.push(previous);
+                        }
+                        effect
+                    }
+                    Err(e) => Some(Effect::Error(e)),
+                }
+            }
+            Action::Back => {
+                let path = self.history.peek()?.clone();
+                match self.change_dir(path) {
+                    Ok(effect) => {
+                        self.history.pop();
+                        effect
+                    }
+                    //The entry stays, so it can be tried again
+                    Err(e) => Some(Effect::Error(e)),
+                }
+            }
+            Action::Up => {
+                let parent = self.parent()?;
+                self.dispatch(Action::Navigate(parent))
+            }
+            Action::Search(query) => {
+                self.query = query;
+                self.reload()
+            }
+            Action::Refresh => self.reload(),
+            Action::Sort(key) => {
+                self.sort.toggle(key);
+                let results = sort::sort_results(mem::take(&mut self.results), &self.sort);
+                self.set_results(results, None);
+                None
+            }
+            Action::ShowHidden(show) => {
+                self.show_hidden = show;
+                self.reload()
+            }
+            Action::DiskUsage(enabled) => {
+                self.disk_usage = enabled;
+                self.reload()
+            }
+            Action::ShowResults(results) => {
+                self.set_results(results, None);
+                None
+            }
+            Action::Activate(row) => {
+                let result = self.results.get(row)?;
+                match result.file_type.is_dir() {
+                    true => {
+                        let path = result.full_path.clone();
+                        self.dispatch(Action::Navigate(path))
+                    }
+                    false => Some(Effect::Open(result.full_path.clone())),
+                }
+            }
+        }
+    }
+
+    /// The current directory with a trailing separator, like the listings expect it
+    pub fn dir(&self) -> String {
+        with_separator(&self.path)
+    }
+
+    /// Empty until the first action, e.g. for tabs restored from the session
+    pub fn is_loaded(&self) -> bool {
+        self.generation > 0
+    }
+
+    /// Name of the current directory, the whole path for roots like `C:`
+    pub fn title(&self) -> String {
+        match Path::new(&self.path).file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None if self.path.is_empty() => String::from("New tab"),
+            None => self.path.clone(),
+        }
+    }
+
+    /// Another tab that sorts the same way but starts without a path.
+    /// Disk usage stays off, tabs opened in the background shouldnt start scanning.
+    pub fn sibling(&self) -> AppState {
+        AppState {
+            sort: self.sort.clone(),
+            show_hidden: self.show_hidden,
+            ..Default::default()
+        }
+    }
+
+    pub fn saved(&self) -> SavedTab {
+        SavedTab {
+            path: self.path.clone(),
+            query: self.query.clone(),
+            sort: self.sort.clone(),
+            history: self.history.clone(),
+        }
+    }
+
+    /// Nothing is listed until the first `Refresh`
+    pub fn from_saved(saved: SavedTab) -> AppState {
+        AppState {
+            path: normalize(&saved.path),
+            query: saved.query,
+            sort: saved.sort,
+            history: saved.history,
+            ..Default::default()
+        }
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.history.all_read()
+    }
+
+    pub fn can_go_up(&self) -> bool {
+        self.parent().is_some()
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.results.iter().map(|r| r.size).sum()
+    }
+
+    fn parent(&self) -> Option<String> {
+        let parent = Path::new(&self.path).parent()?.to_string_lossy().to_string();
+        match parent.is_empty() {
+            true => None,
+            false => Some(normalize(&parent)),
+        }
+    }
+
+    fn set_results(&mut self, results: Vec<SearchEngineResult>, started: Option<Instant>) {
+        self.results = results;
+        self.generation += 1;
+        self.status.error = None;
+        if let Some(started) = started {
+            self.status.duration = Some(started.elapsed());
+        }
+    }
+
+    /// Lists `path` before anything is changed, so path, query and results stay as they were if that fails
+    fn change_dir(&mut self, path: String) -> Result<Option<Effect>, String> {
+        //The sizes are calculated by the gui, it knows the directory exists
+        if self.disk_usage {
+            self.path = path;
+            self.query.clear();
+            return Ok(self.reload());
+        }
+
+        let started = Instant::now();
+        let entries = self.list(&with_separator(&path))?;
+        self.path = path;
+        self.query.clear();
+        self.set_results(entries, Some(started));
+        Ok(None)
+    }
+
+    fn list(&self, dir: &str) -> Result<Vec<SearchEngineResult>, String> {
+        let _span = crate::span!("listing", dir = dir);
+        let options = ListingOptions {
+            show_hidden: self.show_hidden,
+            sort: self.sort.clone(),
+        };
+        match DirectoryListing::list(dir, &options) {
+            Ok(listing) => {
+                crate::debug!("Listed"; entries = listing.entries.len());
+                Ok(listing.entries)
+            }
+            Err(e) => {
+                crate::warn!("Failed to list the directory"; error = e);
+                Err(e.to_string())
+            }
+        }
+    }
+
+    /// Lists or searches the current directory again
+    fn reload(&mut self) -> Option<Effect> {
+        let started = Instant::now();
+        let dir = self.dir();
+        let query = self.query.trim();
+
+        if self.disk_usage {
+            //The query only carries skip flags like "!h!b" here
+            let skip = match query.is_empty() {
+                true => SearchEngineParameter::default(),
+                false => SearchEngineParameter::parse_with(query, &self.help).unwrap_or_default(),
+            };
+            return Some(Effect::ScanDiskUsage { path: dir, skip });
+        }
+
+        if query.is_empty() {
+            return match self.list(&dir) {
+                Ok(entries) => {
+                    self.set_results(entries, Some(started));
+                    None
+                }
+                Err(e) => Some(Effect::Error(e)),
+            };
+        }
+
+        let _span = crate::span!("search", dir = dir, query = query);
+        if let Err(e) = self.help.validate(query) {
+            crate::debug!("Invalid query"; error = e);
+            self.status.error = Some(e);
+            return None;
+        }
+        //Most likely the query isnt complete yet, the last results stay
+        let p = match SearchEngineParameter::parse_with(query, &self.help) {
+            Ok(p) => p,
+            Err(_) => {
+                crate::debug!("Failed to parse the query");
+                return None;
+            }
+        };
+        let results = match SearchEngine::search(&p, &dir, 0) {
+            Ok(r) => r,
+            Err(_) => {
+                crate::warn!("Failed to read {}", dir);
+                return None;
+            }
+        };
+        crate::debug!("Searched"; results = results.len());
+        self.set_results(sort::sort_results(results, &self.sort), Some(started));
+        None
+    }
+}
+
+/// Without surrounding whitespace and trailing separators, roots like `/` stay as they are
+fn normalize(path: &str) -> String {
+    let path = path.trim();
+    let trimmed = path.trim_end_matches(['\\', '/']);
+    match trimmed.is_empty() {
+        true => path.into(),
+        false => trimmed.into(),
+    }
+}
+
+fn with_separator(path: &str) -> String {
+    match path.ends_with(['\\', '/']) {
+        true => path.to_string(),
+        false => format!("{}{}", path, MAIN_SEPARATOR),
+    }
+}
+
+fn check_dir(path: &str) -> Result<(), String> {
+    match fs::metadata(with_separator(path)) {
+        Ok(m) if m.is_dir() => Ok(()),
+        Ok(_) => Err(format!("{} is not a directory", path)),
+        Err(_) => Err(format!("Directory {} does not exist", path)),
+    }
+}
@@ -0,0 +1,128 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{file_types::FileCategory, logging, preview::Thumbnail};
+
+/// Folder in the data directory of the app, pngs in it replace the embedded ones with the same name
+pub const THEME_DIR: &str = "theme";
+/// Size of the placeholder used when an asset cant be decoded
+const PLACEHOLDER_SIZE: u32 = 16;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ResourceType {
+    ArrowLeft,
+    ArrowUp,
+    Refresh,
+    Copy,
+    /// Icon of the category in the result list
+    FileType(FileCategory),
+}
+
+impl ResourceType {
+    /// Relative to the assets or theme folder
+    pub fn file_name(&self) -> String {
+        match self {
+            ResourceType::ArrowLeft => "arrow-left.png".into(),
+            ResourceType::ArrowUp => "arrow-up.png".into(),
+            ResourceType::Refresh => "refresh.png".into(),
+            ResourceType::Copy => "copy.png".into(),
+            ResourceType::FileType(c) => format!("types/{}.png", c.label().to_lowercase()),
+        }
+    }
+
+    /// Compiled into the binary so the icons dont depend on the working directory
+    pub fn embedded(&self) -> &'static [u8] {
+        match self {
+            ResourceType::ArrowLeft => include_bytes!("../assets/arrow-left.png"),
+            ResourceType::ArrowUp => include_bytes!("../assets/arrow-up.png"),
+            ResourceType::Refresh => include_bytes!("../assets/refresh.png"),
+            ResourceType::Copy => include_bytes!("../assets/copy.png"),
+            ResourceType::FileType(c) => match c {
+                FileCategory::Directory => include_bytes!("../assets/types/directory.png"),
+                FileCategory::Image => include_bytes!("../assets/types/image.png"),
+                FileCategory::Video => include_bytes!("../assets/types/video.png"),
+                FileCategory::Audio => include_bytes!("../assets/types/audio.png"),
+                FileCategory::Archive => include_bytes!("../assets/types/archive.png"),
+                FileCategory::Code => include_bytes!("../assets/types/code.png"),
+                FileCategory::Document => include_bytes!("../assets/types/document.png"),
+                FileCategory::Executable => include_bytes!("../assets/types/executable.png"),
+                FileCategory::Symlink => include_bytes!("../assets/types/symlink.png"),
+                FileCategory::Other => include_bytes!("../assets/types/file.png"),
+            },
+        }
+    }
+}
+
+/// Decodes every asset once, the theme folder is checked before the embedded data
+#[derive(Default)]
+pub struct AssetStore {
+    theme_dir: Option<PathBuf>,
+    cache: RefCell<HashMap<ResourceType, Rc<Thumbnail>>>,
+}
+
+impl AssetStore {
+    pub fn new(theme_dir: Option<PathBuf>) -> AssetStore {
+        AssetStore {
+            theme_dir,
+            cache: RefCell::default(),
+        }
+    }
+
+    /// Uses the theme folder in the data directory, the program folder usually isnt writable
+    pub fn with_default_theme() -> AssetStore {
+        AssetStore::new(Some(logging::data_dir().join(THEME_DIR)))
+    }
+
+    pub fn theme_dir(&self) -> Option<&Path> {
+        self.theme_dir.as_deref()
+    }
+
+    /// Never fails, a broken override falls back to the embedded asset and that to an empty placeholder
+    pub fn image(&self, t: ResourceType) -> Rc<Thumbnail> {
+        if let Some(img) = self.cache.borrow().get(&t) {
+            return Rc::clone(img);
+        }
+
+        let img = self
+            .theme_override(t)
+            .or_else(|| decode(t.embedded()).ok())
+            .unwrap_or_else(placeholder);
+        let img = Rc::new(img);
+        self.cache.borrow_mut().insert(t, Rc::clone(&img));
+        img
+    }
+
+    /// Forgets the decoded assets, e.g. after the theme folder changed
+    pub fn clear(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    pub fn cached(&self) -> usize {
+        self.cache.borrow().len()
+    }
+
+    fn theme_override(&self, t: ResourceType) -> Option<Thumbnail> {
+        let path = self.theme_dir.as_ref()?.join(t.file_name());
+        let data = fs::read(path).ok()?;
+        decode(&data).ok()
+    }
+}
+
+pub fn decode(data: &[u8]) -> Result<Thumbnail, String> {
+    let img = image::load_from_memory(data).map_err(|e| e.to_string())?;
+    Ok(Thumbnail::from_rgba(img.to_rgba8()))
+}
+
+/// Fully transparent, so a missing icon just leaves a gap
+fn placeholder() -> Thumbnail {
+    Thumbnail {
+        width: PLACEHOLDER_SIZE,
+        height: PLACEHOLDER_SIZE,
+        bgra: vec![0; (PLACEHOLDER_SIZE * PLACEHOLDER_SIZE * 4) as usize],
+    }
+}
@@ -0,0 +1,26 @@
+//Headless counterpart of the explorer, runs everywhere the library does
+use std::{
+    env,
+    io::{self, BufWriter},
+    process,
+};
+
+use win_expl::cli;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let invocation = match cli::parse_args(&args) {
+        Ok(i) => i,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    if let Err(e) = cli::run(&invocation, &mut out) {
+        win_expl::error!("{}", e);
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
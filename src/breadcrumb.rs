@@ -0,0 +1,202 @@
+use std::fs;
+
+use crate::memory::index::{Index, IndexType};
+
+const SEPARATORS: [char; 2] = ['\\', '/'];
+
+/// One clickable part of a path, e.g. `Users` in `C:\Users\me`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BreadcrumbSegment {
+    pub name: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Breadcrumb {
+    segments: Vec<BreadcrumbSegment>,
+}
+
+/// Anything that knows which directories live inside a given directory.
+pub trait DirectorySource {
+    /// Returns the names of the child directories or `None` if the source doesnt know the path.
+    fn child_directories(&self, path: &str) -> Option<Vec<String>>;
+}
+
+/// Reads the file system every time it is asked.
+pub struct LiveDirectorySource;
+
+impl Breadcrumb {
+    pub fn from_path(path: &str) -> Breadcrumb {
+        let sep = separator_of(path);
+        let mut segments = Vec::new();
+        let mut current = String::new();
+
+        if path.starts_with('/') {
+            current.push('/');
+            segments.push(BreadcrumbSegment {
+                name: "/".into(),
+                path: "/".into(),
+            });
+        }
+
+        for part in path.split(SEPARATORS).filter(|p| !p.is_empty()) {
+            if !current.is_empty() && !current.ends_with(SEPARATORS) {
+                current.push(sep);
+            }
+            current.push_str(part);
+            segments.push(BreadcrumbSegment {
+                name: part.into(),
+                path: current.clone(),
+            });
+        }
+
+        Breadcrumb { segments }
+    }
+
+    pub fn segments(&self) -> &[BreadcrumbSegment] {
+        &self.segments
+    }
+
+    pub fn path(&self) -> Option<&str> {
+        self.segments.last().map(|s| s.path.as_str())
+    }
+
+    pub fn path_of(&self, index: usize) -> Option<&str> {
+        self.segments.get(index).map(|s| s.path.as_str())
+    }
+
+    /// Directories next to the segment at `index`, the segment itself included.
+    /// The root has no siblings.
+    pub fn siblings(&self, index: usize, sources: &[&dyn DirectorySource]) -> Vec<BreadcrumbSegment> {
+        if index == 0 || index >= self.segments.len() {
+            return Vec::new();
+        }
+        let parent = &self.segments[index - 1].path;
+        let sep = separator_of(parent);
+
+        child_directories(parent, sources)
+            .into_iter()
+            .map(|name| BreadcrumbSegment {
+                path: join(parent, &name, sep),
+                name,
+            })
+            .collect()
+    }
+}
+
+/// Completes the last part of `input` with the child directories of the part before it.
+/// Returns full paths, sorted case insensitive.
+pub fn autocomplete(input: &str, sources: &[&dyn DirectorySource], limit: usize) -> Vec<String> {
+    let pos = match input.rfind(SEPARATORS) {
+        Some(p) => p,
+        None => return Vec::new(),
+    };
+    let sep = input[pos..].chars().next().unwrap();
+    let parent = match &input[..pos] {
+        "" => &input[..1],
+        p => p,
+    };
+    let prefix = input[pos + 1..].to_lowercase();
+
+    child_directories(parent, sources)
+        .into_iter()
+        .filter(|name| name.to_lowercase().starts_with(&prefix))
+        .take(limit)
+        .map(|name| join(parent, &name, sep))
+        .collect()
+}
+
+/// Asks every source in order and takes the first answer.
+fn child_directories(path: &str, sources: &[&dyn DirectorySource]) -> Vec<String> {
+    let mut dirs = sources
+        .iter()
+        .find_map(|s| s.child_directories(path))
+        .unwrap_or_default();
+    dirs.sort_by_key(|d| d.to_lowercase());
+    dirs
+}
+
+fn separator_of(path: &str) -> char {
+    path.chars()
+        .find(|c| SEPARATORS.contains(c))
+        .unwrap_or(std::path::MAIN_SEPARATOR)
+}
+
+fn join(parent: &str, name: &str, sep: char) -> String {
+    if parent.ends_with(SEPARATORS) {
+        format!("{}{}", parent, name)
+    } else {
+        format!("{}{}{}", parent, sep, name)
+    }
+}
+
+impl DirectorySource for LiveDirectorySource {
+    fn child_directories(&self, path: &str) -> Option<Vec<String>> {
+        //"C:" would be the current dir of drive C, so always read with a trailing separator
+        let dir = match path.ends_with(SEPARATORS) {
+            true => path.to_string(),
+            false => format!("{}{}", path, separator_of(path)),
+        };
+        let entries = fs::read_dir(dir).ok()?;
+
+        Some(
+            entries
+                .flatten()
+                .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .collect(),
+        )
+    }
+}
+
+impl DirectorySource for Index {
+    fn child_directories(&self, path: &str) -> Option<Vec<String>> {
+        let mut node = self.root.as_ref()?;
+        //The root is named after its full path, e.g. `C:\Users` or `/home`
+        let rest = match node {
+            IndexType::Dir(name, _) => strip_root(path, name)?,
+            IndexType::File(_) => return None,
+        };
+
+        for part in rest.split(SEPARATORS).filter(|p| !p.is_empty()) {
+            node = match node {
+                IndexType::Dir(_, children) => children
+                    .iter()
+                    .find(|c| matches!(c, IndexType::Dir(n, _) if n.eq_ignore_ascii_case(part)))?,
+                IndexType::File(_) => return None,
+            };
+        }
+
+        let dirs: Vec<String> = match node {
+            IndexType::Dir(_, children) => children
+                .iter()
+                .filter_map(|c| match c {
+                    IndexType::Dir(n, _) => Some(n.clone()),
+                    IndexType::File(_) => None,
+                })
+                .collect(),
+            IndexType::File(_) => return None,
+        };
+
+        //The index stops at a max depth, so its leaves look empty even if they arent
+        match dirs.is_empty() {
+            true => None,
+            false => Some(dirs),
+        }
+    }
+}
+
+/// Case insensitive, `C:\Users` is a prefix of `C:\Users\me` but not of `C:\Users2`
+fn strip_root<'a>(path: &'a str, root: &str) -> Option<&'a str> {
+    let path = path.trim_end_matches(SEPARATORS);
+    let root = root.trim_end_matches(SEPARATORS);
+    if path.len() < root.len() || !path.is_char_boundary(root.len()) {
+        return None;
+    }
+
+    let (head, rest) = path.split_at(root.len());
+    match head.eq_ignore_ascii_case(root) && (rest.is_empty() || rest.starts_with(SEPARATORS)) {
+        true => Some(rest),
+        false => None,
+    }
+}
@@ -0,0 +1,452 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::Path,
+};
+
+use regex::{NoExpand, Regex, RegexBuilder};
+
+use crate::{
+    file_operations::{self, OperationKind, OperationReport, Outcome},
+    formatting::{DateStyle, Formatter, SizeUnits},
+    search_engine::result::SearchEngineResult,
+};
+
+pub const DEFAULT_RENAME_DATE_PATTERN: &str = "[year]-[month]-[day]";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum CaseTransform {
+    #[default]
+    Keep,
+    Lower,
+    Upper,
+    /// First letter of every word upper case, the rest lower case
+    Title,
+}
+
+/// How the new names are built. Only the name without the extension is changed, the extension has its own option.
+///
+/// Placeholders in `replace`: `{name}` is the old name, `{n}` the counter and `{date}` the modified date.
+/// Regex groups are used with `$1` or `${name}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenameRules {
+    /// Empty replaces the whole name
+    pub find: String,
+    pub replace: String,
+    pub regex: bool,
+    pub ignore_case: bool,
+    pub case: CaseTransform,
+    pub counter_start: u64,
+    pub counter_step: u64,
+    /// Zeros in front of the counter up to this many digits
+    pub counter_padding: usize,
+    /// A `time` format description used for `{date}`
+    pub date_pattern: String,
+    /// New extension without the dot, empty removes it and `None` keeps it
+    pub extension: Option<String>,
+}
+
+impl Default for RenameRules {
+    fn default() -> Self {
+        RenameRules {
+            find: String::new(),
+            replace: String::from("{name}"),
+            regex: false,
+            ignore_case: false,
+            case: CaseTransform::Keep,
+            counter_start: 1,
+            counter_step: 1,
+            counter_padding: 3,
+            date_pattern: DEFAULT_RENAME_DATE_PATTERN.into(),
+            extension: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenameStatus {
+    Ready,
+    Unchanged,
+    /// Part of a chain that ends where it started, e.g. swapping two names. Works over a temporary name.
+    InCycle,
+    Invalid(String),
+    /// Another new name or an existing file that stays
+    Collision(String),
+}
+
+impl RenameStatus {
+    pub fn blocks(&self) -> bool {
+        matches!(self, RenameStatus::Invalid(_) | RenameStatus::Collision(_))
+    }
+}
+
+impl fmt::Display for RenameStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameStatus::Ready => Ok(()),
+            RenameStatus::Unchanged => write!(f, "Unchanged"),
+            RenameStatus::InCycle => write!(f, "Swapped over a temporary name"),
+            RenameStatus::Invalid(reason) | RenameStatus::Collision(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+/// One line of the preview
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedRename {
+    pub source: String,
+    pub new_name: String,
+    pub target: String,
+    pub status: RenameStatus,
+}
+
+/// Splits at the last dot, folders and names like `.gitignore` have no extension
+fn split_name(name: &str, is_dir: bool) -> (&str, Option<&str>) {
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !is_dir && !stem.is_empty() => (stem, Some(ext)),
+        _ => (name, None),
+    }
+}
+
+fn transform_case(name: &str, case: CaseTransform) -> String {
+    match case {
+        CaseTransform::Keep => name.into(),
+        CaseTransform::Lower => name.to_lowercase(),
+        CaseTransform::Upper => name.to_uppercase(),
+        CaseTransform::Title => {
+            let mut start_of_word = true;
+            name.chars()
+                .flat_map(|c| {
+                    let upper = start_of_word;
+                    start_of_word = !c.is_alphanumeric();
+                    let mapped: Vec<char> = match upper {
+                        true => c.to_uppercase().collect(),
+                        false => c.to_lowercase().collect(),
+                    };
+                    mapped
+                })
+                .collect()
+        }
+    }
+}
+
+struct Renamer<'a> {
+    rules: &'a RenameRules,
+    find: Option<Regex>,
+    dates: Formatter,
+}
+
+impl<'a> Renamer<'a> {
+    fn new(rules: &'a RenameRules) -> Result<Renamer<'a>, String> {
+        let find = match rules.find.is_empty() {
+            true => None,
+            false => {
+                let pattern = match rules.regex {
+                    true => rules.find.clone(),
+                    false => regex::escape(&rules.find),
+                };
+                let regex = RegexBuilder::new(&pattern)
+                    .case_insensitive(rules.ignore_case)
+                    .build()
+                    .map_err(|e| format!("Invalid pattern '{}': {}", rules.find, e))?;
+                Some(regex)
+            }
+        };
+        let dates = Formatter::new(SizeUnits::default(), &DateStyle::Pattern(rules.date_pattern.clone()))?;
+
+        Ok(Renamer { rules, find, dates })
+    }
+
+    fn new_name(&self, file: &SearchEngineResult, counter: u64) -> String {
+        let (stem, ext) = split_name(&file.name, file.file_type.is_dir());
+        //A regex expands `$` in the replacement, but in the old name or the date it is meant literally
+        let literal = |value: &str| match self.find.is_some() && self.rules.regex {
+            true => value.replace('$', "$$"),
+            false => value.to_string(),
+        };
+        let replacement = self
+            .rules
+            .replace
+            .replace("{name}", &literal(stem))
+            .replace("{n}", &format!("{:0width$}", counter, width = self.rules.counter_padding))
+            .replace("{date}", &literal(&self.dates.date(file.modified)));
+
+        let stem = match &self.find {
+            None => replacement,
+            //Plain text cant contain groups, a `$` in it is meant literally
+            Some(find) if !self.rules.regex => find.replace_all(stem, NoExpand(&replacement)).into_owned(),
+            Some(find) => find.replace_all(stem, replacement.as_str()).into_owned(),
+        };
+        let stem = transform_case(&stem, self.rules.case);
+
+        match self.rules.extension.as_deref().or(ext) {
+            Some("") | None => stem,
+            Some(ext) => format!("{}.{}", stem, ext),
+        }
+    }
+}
+
+/// Computes the new names without touching anything. Fails if the pattern or the date pattern is invalid.
+/// The counter follows the order of `files`.
+pub fn preview(files: &[SearchEngineResult], rules: &RenameRules) -> Result<Vec<PlannedRename>, String> {
+    let renamer = Renamer::new(rules)?;
+
+    let mut plan: Vec<PlannedRename> = files
+        .iter()
+        .enumerate()
+        .map(|(i, file)| {
+            let counter = rules.counter_start + i as u64 * rules.counter_step;
+            let new_name = renamer.new_name(file, counter);
+            let target = Path::new(&file.full_path)
+                .with_file_name(&new_name)
+                .to_string_lossy()
+                .to_string();
+            let status = match file_operations::invalid_name_reason(&new_name) {
+                Some(reason) => RenameStatus::Invalid(reason),
+                None if new_name == file.name => RenameStatus::Unchanged,
+                None => RenameStatus::Ready,
+            };
+            PlannedRename {
+                source: file.full_path.clone(),
+                new_name,
+                target,
+                status,
+            }
+        })
+        .collect();
+
+    mark_collisions(&mut plan);
+    mark_cycles(&mut plan);
+    Ok(plan)
+}
+
+/// Windows doesnt care about the case of names
+fn key(path: &str) -> String {
+    path.to_lowercase()
+}
+
+fn mark_collisions(plan: &mut [PlannedRename]) {
+    let mut targets: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, p) in plan.iter().enumerate() {
+        targets.entry(key(&p.target)).or_default().push(i);
+    }
+    //Paths that are free once the batch is done
+    let moving: HashSet<String> = plan
+        .iter()
+        .filter(|p| p.status == RenameStatus::Ready)
+        .map(|p| key(&p.source))
+        .collect();
+
+    for (i, p) in plan.iter_mut().enumerate() {
+        if p.status != RenameStatus::Ready {
+            continue;
+        }
+        let target = key(&p.target);
+        let others = targets[&target].iter().filter(|&&j| j != i).count();
+        if others > 0 {
+            p.status = RenameStatus::Collision(format!("{} more files would be named {}", others, p.new_name));
+            continue;
+        }
+        let is_self = target == key(&p.source);
+        if !is_self && !moving.contains(&target) && Path::new(&p.target).exists() {
+            p.status = RenameStatus::Collision(format!("{} already exists", p.new_name));
+        }
+    }
+
+    //A file whose target is blocked has to stay, so its name cant be used by others either
+    let mut changed = true;
+    while changed {
+        changed = false;
+        let staying: HashSet<String> = plan
+            .iter()
+            .filter(|p| p.status != RenameStatus::Ready)
+            .map(|p| key(&p.source))
+            .collect();
+        for p in plan.iter_mut().filter(|p| p.status == RenameStatus::Ready) {
+            let target = key(&p.target);
+            if target != key(&p.source) && staying.contains(&target) {
+                p.status = RenameStatus::Collision(format!("{} is kept by a file that cant be renamed", p.new_name));
+                changed = true;
+            }
+        }
+    }
+}
+
+fn mark_cycles(plan: &mut [PlannedRename]) {
+    let sources: HashMap<String, usize> = plan
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.status == RenameStatus::Ready)
+        .map(|(i, p)| (key(&p.source), i))
+        .collect();
+
+    for start in 0..plan.len() {
+        if plan[start].status != RenameStatus::Ready || key(&plan[start].target) == key(&plan[start].source) {
+            continue;
+        }
+        //Following the targets either leaves the batch or comes back, targets are unique after the collision check
+        let mut chain = vec![start];
+        let mut current = start;
+        while let Some(&next) = sources.get(&key(&plan[current].target)) {
+            if next == start {
+                for &i in &chain {
+                    plan[i].status = RenameStatus::InCycle;
+                }
+                break;
+            }
+            if chain.contains(&next) {
+                break;
+            }
+            chain.push(next);
+            current = next;
+        }
+    }
+}
+
+/// A cycle whose first item was moved to a temporary name
+struct BrokenCycle {
+    temp_item: usize,
+    members: Vec<usize>,
+    /// Renames of the other members since, old and new path and their index in the report
+    done: Vec<(String, String, usize)>,
+}
+
+/// Renames everything of the plan, refuses if anything blocks it. Chains are renamed back to front and cycles
+/// are broken up with a temporary name. If a rename of a cycle fails, the whole cycle is rolled back,
+/// so nothing is left behind under the temporary name.
+pub fn apply(plan: &[PlannedRename]) -> Result<OperationReport, String> {
+    if let Some(p) = plan.iter().find(|p| p.status.blocks()) {
+        return Err(format!("Cant rename {}: {}", p.source, p.status));
+    }
+
+    let mut report = OperationReport::new(OperationKind::Rename);
+    //Current location of every item that still has to be renamed
+    let mut pending: Vec<(usize, String)> = plan
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| matches!(p.status, RenameStatus::Ready | RenameStatus::InCycle))
+        .map(|(i, p)| (i, p.source.clone()))
+        .collect();
+    let mut cycle: Option<BrokenCycle> = None;
+
+    while !pending.is_empty() {
+        let free = pending.iter().position(|(i, current)| {
+            let target = key(&plan[*i].target);
+            target == key(current) || !pending.iter().any(|(_, other)| key(other) == target)
+        });
+
+        match free {
+            Some(pos) => {
+                let (i, current) = pending.remove(pos);
+                let p = &plan[i];
+                //Something of the batch failed before and is still in the way
+                let result = match key(&current) != key(&p.target) && Path::new(&p.target).exists() {
+                    true => Err(format!("{} already exists", p.new_name)),
+                    false => fs::rename(&current, &p.target).map_err(|e| e.to_string()),
+                };
+                match result {
+                    Ok(()) => {
+                        if let Some(c) = cycle.as_mut() {
+                            c.done.push((current, p.target.clone(), report.items.len()));
+                        }
+                        report.push(&p.source, Some(p.target.clone()), Outcome::Done);
+                        if cycle.as_ref().is_some_and(|c| c.temp_item == i) {
+                            cycle = None;
+                        }
+                    }
+                    Err(e) => {
+                        report.push(&p.source, None, Outcome::Failed(e));
+                        if let Some(c) = cycle.take() {
+                            roll_back(plan, c, (i, current), &mut pending, &mut report);
+                        }
+                    }
+                }
+            }
+            None => {
+                //Only cycles are left, moving one of them out of the way frees the next
+                let (i, current) = &mut pending[0];
+                let temp = file_operations::unique_path(Path::new(&format!("{}.renaming", plan[*i].source)));
+                if let Err(e) = fs::rename(&*current, &temp) {
+                    let (i, _) = pending.remove(0);
+                    report.push(&plan[i].source, None, Outcome::Failed(e.to_string()));
+                    continue;
+                }
+                *current = temp.to_string_lossy().to_string();
+                cycle = Some(BrokenCycle {
+                    temp_item: *i,
+                    members: cycle_members(plan, *i),
+                    done: Vec::new(),
+                });
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// The items of the cycle `start` is part of, following the targets
+fn cycle_members(plan: &[PlannedRename], start: usize) -> Vec<usize> {
+    let mut members = vec![start];
+    let mut current = start;
+    while let Some(next) = plan
+        .iter()
+        .position(|p| p.status == RenameStatus::InCycle && key(&p.source) == key(&plan[current].target))
+    {
+        if members.contains(&next) {
+            break;
+        }
+        members.push(next);
+        current = next;
+    }
+    members
+}
+
+/// Renames the members of the cycle that were done back in reverse order, then the item on the temporary name.
+/// The members that didnt get their turn yet are skipped.
+fn roll_back(
+    plan: &[PlannedRename],
+    cycle: BrokenCycle,
+    (failed, failed_at): (usize, String),
+    pending: &mut Vec<(usize, String)>,
+    report: &mut OperationReport,
+) {
+    for (from, to, index) in cycle.done.into_iter().rev() {
+        if fs::rename(&to, &from).is_ok() {
+            report.items[index].target = None;
+            report.items[index].outcome = Outcome::Skipped(String::from("Rolled back"));
+        }
+    }
+
+    let temp = match failed == cycle.temp_item {
+        true => Some(failed_at),
+        false => pending
+            .iter()
+            .position(|(i, _)| *i == cycle.temp_item)
+            .map(|pos| pending.remove(pos).1),
+    };
+    if let Some(temp) = temp {
+        let source = &plan[cycle.temp_item].source;
+        let outcome = match fs::rename(&temp, source) {
+            Ok(()) => Outcome::Skipped(String::from("Rolled back")),
+            Err(e) => Outcome::Failed(format!("Left at {}: {}", temp, e)),
+        };
+        match failed == cycle.temp_item {
+            //Keeps the reason it failed unless it is stuck on the temporary name
+            true if matches!(outcome, Outcome::Skipped(_)) => {}
+            true => {
+                if let Some(item) = report.items.last_mut() {
+                    item.outcome = outcome;
+                }
+            }
+            false => report.push(source, None, outcome),
+        }
+    }
+
+    pending.retain(|(i, _)| {
+        let member = cycle.members.contains(i);
+        if member {
+            report.push(&plan[*i].source, None, Outcome::Skipped(String::from("Rolled back")));
+        }
+        !member
+    });
+}
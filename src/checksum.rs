@@ -0,0 +1,275 @@
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::hashing::{self, HashError, HashFunction};
+
+/// Sidecar files bigger than this are no checksum lists and wont be read
+const MAX_SUMS_FILE_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpectedChecksum {
+    pub path: String,
+    pub hash_function: HashFunction,
+    pub expected: String,
+    /// Sums file the hash was taken from, `None` if it was pasted
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verification {
+    Match,
+    Mismatch { expected: String, actual: String },
+    Failed(String),
+}
+
+/// One line of a sums file
+#[derive(Debug, Clone, PartialEq)]
+pub struct SumsEntry {
+    /// Empty if the file only contains the hash, e.g. `file.iso.sha256`
+    pub name: String,
+    pub hash: String,
+    pub hash_function: Option<HashFunction>,
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verification::Match => write!(f, "OK"),
+            Verification::Mismatch { expected, actual } => {
+                write!(f, "MISMATCH, expected {} but got {}", expected, actual)
+            }
+            Verification::Failed(e) => write!(f, "FAILED, {}", e),
+        }
+    }
+}
+
+/// Derives the hash function from names like `file.sha256`, `SHA256SUMS` or `files.sfv`
+pub fn function_from_file_name(name: &str) -> Option<HashFunction> {
+    let lower = name.to_lowercase();
+    let ext = lower.rsplit_once('.').map(|(_, e)| e).unwrap_or_default();
+    let sums_name = lower.strip_suffix("sums").unwrap_or_default();
+
+    match (ext, sums_name) {
+        ("md5", _) | (_, "md5") => Some(HashFunction::MD5),
+        ("sha1", _) | (_, "sha1") => Some(HashFunction::SHA1),
+        ("sha256", _) | (_, "sha256") => Some(HashFunction::SHA256),
+        ("sha512", _) | (_, "sha512") => Some(HashFunction::SHA512),
+        ("b3", _) | ("blake3", _) | (_, "b3") | (_, "blake3") => Some(HashFunction::BLAKE3),
+        ("xxh3", _) | (_, "xxh3") => Some(HashFunction::XXH3),
+        ("sfv", _) => Some(HashFunction::CRC32),
+        _ => None,
+    }
+}
+
+/// Guesses the hash function of a pasted hash by its length. 64 chars could be BLAKE3 too, SHA256 is more common.
+pub fn guess_function(hash: &str) -> Option<HashFunction> {
+    if hash.is_empty() || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    match hash.len() {
+        8 => Some(HashFunction::CRC32),
+        16 => Some(HashFunction::XXH3),
+        32 => Some(HashFunction::MD5),
+        40 => Some(HashFunction::SHA1),
+        64 => Some(HashFunction::SHA256),
+        128 => Some(HashFunction::SHA512),
+        _ => None,
+    }
+}
+
+/// Reads the GNU (`hash  name`, `hash *name`), BSD (`SHA256 (name) = hash`) and SFV (`name crc`) formats.
+/// Comments and lines that arent understood are skipped.
+pub fn parse_sums(content: &str, hf: Option<HashFunction>) -> Vec<SumsEntry> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#') && !l.starts_with(';'))
+        .filter_map(|line| parse_line(line, hf))
+        .collect()
+}
+
+fn parse_line(line: &str, hf: Option<HashFunction>) -> Option<SumsEntry> {
+    if let Some((algo, rest)) = line.split_once(" (") {
+        if let Some((name, hash)) = rest.rsplit_once(") = ") {
+            return Some(SumsEntry {
+                name: name.into(),
+                hash: hash.trim().into(),
+                hash_function: HashFunction::from_name(algo.trim()).or(hf),
+            });
+        }
+    }
+
+    if hf == Some(HashFunction::CRC32) {
+        let (name, hash) = line.rsplit_once(char::is_whitespace)?;
+        return Some(SumsEntry {
+            name: name.trim().into(),
+            hash: hash.into(),
+            hash_function: hf,
+        });
+    }
+
+    let (hash, name) = match line.split_once(char::is_whitespace) {
+        Some((hash, name)) => (hash, name.trim_start().trim_start_matches('*')),
+        None => (line, ""),
+    };
+    if !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    Some(SumsEntry {
+        name: name.into(),
+        hash: hash.into(),
+        hash_function: hf.or_else(|| guess_function(hash)),
+    })
+}
+
+/// Looks next to the file for sidecars like `file.iso.sha256`, `SHA256SUMS` or `*.sfv` that contain its hash.
+/// Direct sidecars win over lists of several files.
+pub fn find_expected(path: &str) -> Option<ExpectedChecksum> {
+    let file = Path::new(path);
+    let name = file.file_name()?.to_string_lossy().to_string();
+    let dir = file.parent()?;
+
+    let mut candidates: Vec<(bool, String)> = fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|n| !n.eq(&name) && function_from_file_name(n).is_some())
+        .map(|n| {
+            let is_sidecar = n
+                .rsplit_once('.')
+                .map(|(stem, _)| stem.eq_ignore_ascii_case(&name))
+                .unwrap_or(false);
+            (!is_sidecar, n)
+        })
+        .collect();
+    candidates.sort();
+
+    for (is_list, candidate) in candidates {
+        let candidate_path = dir.join(&candidate);
+        let too_big = fs::metadata(&candidate_path)
+            .map(|m| m.len() > MAX_SUMS_FILE_SIZE)
+            .unwrap_or(true);
+        if too_big {
+            continue;
+        }
+        let content = match fs::read_to_string(&candidate_path) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+
+        let entries = parse_sums(&content, function_from_file_name(&candidate));
+        let entry = entries.into_iter().find(|e| match e.name.as_str() {
+            "" => !is_list,
+            n => same_file_name(n, &name),
+        });
+
+        if let Some(SumsEntry {
+            hash,
+            hash_function: Some(hf),
+            ..
+        }) = entry
+        {
+            return Some(ExpectedChecksum {
+                path: path.into(),
+                hash_function: hf,
+                expected: hash,
+                source: Some(candidate_path.to_string_lossy().to_string()),
+            });
+        }
+    }
+
+    None
+}
+
+/// Builds the expectation from a pasted hash, with an optional `hash  name` or BSD format around it
+pub fn expected_from_text(path: &str, text: &str) -> Option<ExpectedChecksum> {
+    let entry = parse_sums(text, None).into_iter().next()?;
+
+    Some(ExpectedChecksum {
+        path: path.into(),
+        hash_function: entry.hash_function?,
+        expected: entry.hash,
+        source: None,
+    })
+}
+
+pub fn compare(expected: &ExpectedChecksum, actual: &Result<String, HashError>) -> Verification {
+    match actual {
+        Ok(hash) if hash.eq_ignore_ascii_case(&expected.expected) => Verification::Match,
+        Ok(hash) => Verification::Mismatch {
+            expected: expected.expected.to_uppercase(),
+            actual: hash.clone(),
+        },
+        Err(e) => Verification::Failed(e.to_string()),
+    }
+}
+
+/// Hashes the file right away, blocks until done
+pub fn verify(expected: &ExpectedChecksum) -> Verification {
+    compare(
+        expected,
+        &hashing::hash_file(&expected.path, expected.hash_function, |_| true),
+    )
+}
+
+/// `SHA256SUMS`, `MD5SUMS`, ... and `checksums.sfv` for CRC32
+pub fn sums_file_name(hf: HashFunction) -> String {
+    match hf {
+        HashFunction::CRC32 => "checksums.sfv".into(),
+        HashFunction::BLAKE3 => "B3SUMS".into(),
+        hf => format!("{}SUMS", hf.name()),
+    }
+}
+
+/// Formats `(name, hash)` pairs the way `sha256sum` and co. or SFV tools expect them
+pub fn format_sums(entries: &[(String, String)], hf: HashFunction) -> String {
+    entries
+        .iter()
+        .map(|(name, hash)| match hf {
+            HashFunction::CRC32 => format!("{} {}\n", name, hash),
+            _ => format!("{} *{}\n", hash.to_lowercase(), name),
+        })
+        .collect()
+}
+
+/// Writes the sums file into `dir` and returns its path. Paths are stored relative to `dir` when possible.
+/// Fails if the sums file already exists, it may list files that arent hashed this time.
+pub fn write_sums(dir: &str, hf: HashFunction, hashes: &[(String, String)]) -> Result<String, String> {
+    let entries: Vec<(String, String)> = hashes
+        .iter()
+        .map(|(path, hash)| {
+            let name = Path::new(path)
+                .strip_prefix(dir)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|_| path.clone());
+            (name, hash.clone())
+        })
+        .collect();
+
+    let target = Path::new(dir).join(sums_file_name(hf));
+    let mut file = match OpenOptions::new().write(true).create_new(true).open(&target) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            return Err(format!("{} already exists", target.display()))
+        }
+        Err(e) => return Err(format!("Failed to write {}: {}", target.display(), e)),
+    };
+    file.write_all(format_sums(&entries, hf).as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+
+    Ok(target.to_string_lossy().to_string())
+}
+
+fn same_file_name(entry: &str, name: &str) -> bool {
+    let entry = entry
+        .trim_start_matches("./")
+        .trim_start_matches(".\\");
+    //Windows file names dont care about case
+    entry.eq_ignore_ascii_case(name)
+}
@@ -0,0 +1,373 @@
+use std::io::{self, Write};
+
+use serde_json::json;
+
+use crate::{
+    duplicates::{self, DuplicateGroup, DuplicateOptions},
+    export::{self, ExportColumn, ExportFormat, ExportOptions},
+    hashing::{self, HashFunction},
+    logging::{self, Level},
+    memory::index::{Index, DEFAULT_INDEX_DEPTH, INDEX_FILE},
+    search_engine::{
+        parameter_parser::SearchEngineParameter,
+        search_help::SearchHelp,
+        sort::{self, SortOrder},
+        SearchEngine,
+    },
+};
+
+pub const USAGE: &str = "Usage: win-expl <command> [options]
+
+Commands:
+    search <root> <query>     Searches like the search input of the explorer
+    index build <root>        Indexes <root> and writes the index file
+    index refresh             Indexes the root of the index file again
+    index stats               Counts the directories and files of the index
+    hash <file>...            Hashes the files
+    dupes <root>              Finds files with identical content below <root>
+
+Options:
+    -f, --format <format>     table (default), json or nul
+    --columns <list>          Columns of search results, e.g. path,size,modified
+    --index <file>            Index file, defaults to index.json
+    --depth <n>               Directory levels to index, defaults to 8
+    --algo <name>             MD5, SHA1, SHA256 (default), SHA512, BLAKE3, CRC32 or XXH3
+    --min-size <bytes>        Smaller files are ignored by dupes, defaults to 1
+    --use-index               Takes the files for dupes from the index instead of the disk
+    --log-level <level>       Writes a log to the user data directory: error, warn, info, debug or trace
+    -h, --help                Shows this text";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputFormat {
+    /// Aligned columns for people
+    #[default]
+    Table,
+    /// One json object per line
+    Json,
+    /// Paths terminated by NUL bytes, for `xargs -0`
+    Nul,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name.to_lowercase().as_str() {
+            "table" => Some(OutputFormat::Table),
+            "json" | "jsonl" => Some(OutputFormat::Json),
+            "nul" | "null" | "print0" => Some(OutputFormat::Nul),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Search {
+        root: String,
+        query: String,
+        columns: Vec<ExportColumn>,
+    },
+    IndexBuild {
+        root: String,
+        depth: usize,
+    },
+    IndexRefresh {
+        depth: usize,
+    },
+    IndexStats,
+    Hash {
+        files: Vec<String>,
+        algo: HashFunction,
+    },
+    Dupes {
+        root: String,
+        options: DuplicateOptions,
+        use_index: bool,
+    },
+    Help,
+}
+
+/// A command and the options every command shares
+#[derive(Debug, PartialEq)]
+pub struct Invocation {
+    pub command: Command,
+    pub format: OutputFormat,
+    pub index_file: String,
+    /// Nothing is logged without it
+    pub log_level: Option<Level>,
+}
+
+/// `args` without the program name
+pub fn parse_args(args: &[String]) -> Result<Invocation, String> {
+    let mut positional = Vec::new();
+    let mut format = OutputFormat::default();
+    let mut index_file = String::from(INDEX_FILE);
+    let mut columns = export::default_columns();
+    let mut depth = DEFAULT_INDEX_DEPTH;
+    let mut algo = HashFunction::SHA256;
+    let mut options = DuplicateOptions::default();
+    let mut use_index = false;
+    let mut log_level = None;
+    let mut help = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().cloned().ok_or_else(|| format!("{} needs a value", name));
+        match arg.as_str() {
+            "-h" | "--help" => help = true,
+            "-f" | "--format" => {
+                let v = value(arg)?;
+                format = OutputFormat::from_name(&v).ok_or_else(|| format!("Unknown format '{}'", v))?;
+            }
+            "--columns" => columns = ExportColumn::parse_list(&value(arg)?)?,
+            "--index" => index_file = value(arg)?,
+            "--depth" => depth = parse_number(arg, &value(arg)?)? as usize,
+            "--algo" => {
+                let v = value(arg)?;
+                algo = HashFunction::from_name(&v).ok_or_else(|| format!("Unknown hash function '{}'", v))?;
+            }
+            "--min-size" => options.min_size = parse_number(arg, &value(arg)?)?,
+            "--use-index" => use_index = true,
+            "--log-level" => {
+                let v = value(arg)?;
+                log_level = Some(Level::from_name(&v).ok_or_else(|| format!("Unknown log level '{}'", v))?);
+            }
+            a if a.starts_with('-') && a.len() > 1 => return Err(format!("Unknown option '{}'", a)),
+            a => positional.push(a.to_string()),
+        }
+    }
+    options.hash_function = algo;
+
+    let positional: Vec<&str> = positional.iter().map(String::as_str).collect();
+    let command = match positional.as_slice() {
+        _ if help => Command::Help,
+        [] => Command::Help,
+        ["search", root, query @ ..] => Command::Search {
+            root: root.to_string(),
+            //Unquoted queries arrive as several words
+            query: query.join(" "),
+            columns,
+        },
+        ["index", "build", root] => Command::IndexBuild {
+            root: root.to_string(),
+            depth,
+        },
+        ["index", "refresh"] => Command::IndexRefresh { depth },
+        ["index", "stats"] => Command::IndexStats,
+        ["hash", files @ ..] if !files.is_empty() => Command::Hash {
+            files: files.iter().map(|f| f.to_string()).collect(),
+            algo,
+        },
+        ["dupes", root] => Command::Dupes {
+            root: root.to_string(),
+            options,
+            use_index,
+        },
+        [command, ..] => return Err(format!("Invalid use of '{}'", command)),
+    };
+
+    Ok(Invocation {
+        command,
+        format,
+        index_file,
+        log_level,
+    })
+}
+
+fn parse_number(name: &str, value: &str) -> Result<u64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a number, got '{}'", name, value))
+}
+
+pub fn run<W: Write>(invocation: &Invocation, out: &mut W) -> Result<(), String> {
+    if let Some(level) = invocation.log_level {
+        logging::init(&logging::default_log_dir(), level)?;
+    }
+    let format = invocation.format;
+    let index_file = invocation.index_file.as_str();
+    let result = match &invocation.command {
+        Command::Help => writeln!(out, "{}", USAGE).map_err(|e| e.to_string()),
+        Command::Search { root, query, columns } => search(root, query, columns, format, out),
+        Command::IndexBuild { root, depth } => build_index(root, *depth, index_file, format, out),
+        Command::IndexRefresh { depth } => {
+            let root = Index::load(index_file)?
+                .root_path()
+                .map(String::from)
+                .ok_or_else(|| format!("{} is empty, build it first", index_file))?;
+            build_index(&root, *depth, index_file, format, out)
+        }
+        Command::IndexStats => {
+            let index = Index::load(index_file)?;
+            write_stats(&index, format, out).map_err(|e| e.to_string())
+        }
+        Command::Hash { files, algo } => hash(files, *algo, format, out),
+        Command::Dupes { root, options, use_index } => {
+            let files = match use_index {
+                true => duplicates::index_files(&Index::load(index_file)?, root),
+                false => duplicates::walk(root, || true).map_err(|e| e.to_string())?,
+            };
+            let groups = duplicates::find_duplicates(files, options, |_, _, _| true).map_err(|e| e.to_string())?;
+            write_dupes(&groups, format, out).map_err(|e| e.to_string())
+        }
+    };
+    result.and_then(|_| out.flush().map_err(|e| e.to_string()))
+}
+
+fn search<W: Write>(
+    root: &str,
+    query: &str,
+    columns: &[ExportColumn],
+    format: OutputFormat,
+    out: &mut W,
+) -> Result<(), String> {
+    let _span = crate::span!("search", root = root, query = query);
+    let help = SearchHelp::default();
+    help.validate(query)?;
+    let p = SearchEngineParameter::parse_with(query, &help).map_err(|_| format!("Invalid query '{}'", query))?;
+    let results = SearchEngine::search(&p, root, 0).map_err(|_| format!("Failed to read {}", root))?;
+    let results = sort::sort_results(results, &SortOrder::default());
+    crate::debug!("Searched"; results = results.len());
+
+    let export_format = match format {
+        OutputFormat::Table => {
+            let rows: Vec<Vec<String>> = results
+                .iter()
+                .map(|r| columns.iter().map(|c| c.cell(r)).collect())
+                .collect();
+            let header = columns.iter().map(|c| c.key().to_uppercase()).collect();
+            return write_table(header, &rows, out).map_err(|e| e.to_string());
+        }
+        OutputFormat::Json => ExportFormat::JsonLines,
+        OutputFormat::Nul => ExportFormat::NulPathList,
+    };
+    let options = ExportOptions {
+        format: export_format,
+        columns: columns.to_vec(),
+    };
+    export::write(&results, &options, out).map_err(|e| e.to_string())
+}
+
+fn build_index<W: Write>(root: &str, depth: usize, index_file: &str, format: OutputFormat, out: &mut W) -> Result<(), String> {
+    let index = Index::build(root, depth);
+    index.save(index_file)?;
+    write_stats(&index, format, out).map_err(|e| e.to_string())
+}
+
+fn write_stats<W: Write>(index: &Index, format: OutputFormat, out: &mut W) -> io::Result<()> {
+    let stats = index.stats();
+    let root = index.root_path().unwrap_or_default();
+    let modified = export::iso_8601(index.modified_date);
+    match format {
+        OutputFormat::Table => write_table(
+            vec!["ROOT".into(), "DIRECTORIES".into(), "FILES".into(), "DEPTH".into(), "MODIFIED".into()],
+            &[vec![
+                root.into(),
+                stats.dirs.to_string(),
+                stats.files.to_string(),
+                stats.depth.to_string(),
+                modified,
+            ]],
+            out,
+        ),
+        OutputFormat::Json => {
+            let stats = json!({
+                "root": root,
+                "directories": stats.dirs,
+                "files": stats.files,
+                "depth": stats.depth,
+                "modified": modified,
+            });
+            writeln!(out, "{}", stats)
+        }
+        OutputFormat::Nul => write!(out, "{}\0", root),
+    }
+}
+
+/// Keeps going after a file failed, the failures are returned at the end
+fn hash<W: Write>(files: &[String], algo: HashFunction, format: OutputFormat, out: &mut W) -> Result<(), String> {
+    let mut errors = Vec::new();
+    for file in files {
+        let hash = match hashing::hash_file(file, algo, |_| true) {
+            Ok(h) => h,
+            Err(e) => {
+                errors.push(e.to_string());
+                continue;
+            }
+        };
+        let written = match format {
+            //Same layout as the sums files
+            OutputFormat::Table => writeln!(out, "{}  {}", hash, file),
+            OutputFormat::Json => writeln!(out, "{}", json!({ "path": file, "algorithm": algo.name(), "hash": hash })),
+            OutputFormat::Nul => write!(out, "{}  {}\0", hash, file),
+        };
+        written.map_err(|e| e.to_string())?;
+    }
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(errors.join("\n")),
+    }
+}
+
+/// The nul format lists every file of every set
+fn write_dupes<W: Write>(groups: &[DuplicateGroup], format: OutputFormat, out: &mut W) -> io::Result<()> {
+    match format {
+        OutputFormat::Table => {
+            for group in groups {
+                writeln!(out, "{} bytes, {} wasted, {}", group.size, group.wasted(), group.hash)?;
+                for file in &group.files {
+                    writeln!(out, "    {}", file)?;
+                }
+                writeln!(out)?;
+            }
+            writeln!(
+                out,
+                "{} sets, {} bytes wasted",
+                groups.len(),
+                duplicates::total_wasted(groups)
+            )
+        }
+        OutputFormat::Json => {
+            for group in groups {
+                let group = json!({
+                    "size": group.size,
+                    "wasted": group.wasted(),
+                    "hash": group.hash,
+                    "files": group.files,
+                });
+                writeln!(out, "{}", group)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Nul => {
+            for file in groups.iter().flat_map(|g| &g.files) {
+                write!(out, "{}\0", file)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Pads every column but the last to its widest cell
+fn write_table<W: Write>(header: Vec<String>, rows: &[Vec<String>], out: &mut W) -> io::Result<()> {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(rows) {
+        let last = row.len().saturating_sub(1);
+        let line: String = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| match i == last {
+                true => cell.clone(),
+                false => format!("{:width$}  ", cell, width = widths[i]),
+            })
+            .collect();
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
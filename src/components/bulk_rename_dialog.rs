@@ -0,0 +1,121 @@
+use std::cell::RefCell;
+
+use crate::{
+    bulk_rename::{self, CaseTransform, PlannedRename, RenameRules},
+    file_operations::OperationReport,
+    search_engine::result::SearchEngineResult,
+};
+
+pub const CASE_OPTIONS: [&str; 4] = ["Keep case", "lower case", "UPPER CASE", "Title Case"];
+
+/// Window to rename the selection with patterns, the preview updates on every change
+#[derive(Default)]
+pub struct BulkRenameDialog {
+    pub window: nwg::Window,
+    pub find: nwg::TextInput,
+    pub replace: nwg::TextInput,
+    pub regex: nwg::CheckBox,
+    pub ignore_case: nwg::CheckBox,
+    pub case: nwg::ComboBox<&'static str>,
+    pub extension: nwg::TextInput,
+    pub counter_start: nwg::TextInput,
+    pub counter_padding: nwg::TextInput,
+    pub date_pattern: nwg::TextInput,
+    pub preview: nwg::ListView,
+    pub status: nwg::Label,
+    pub apply_btn: nwg::Button,
+    files: RefCell<Vec<SearchEngineResult>>,
+    plan: RefCell<Vec<PlannedRename>>,
+}
+
+impl BulkRenameDialog {
+    pub fn open(&self, files: Vec<SearchEngineResult>) {
+        self.files.replace(files);
+        self.update_preview();
+        self.window.set_visible(true);
+        self.find.set_focus();
+    }
+
+    pub fn is_input(&self, handle: &nwg::ControlHandle) -> bool {
+        [&self.find, &self.replace, &self.extension, &self.counter_start, &self.counter_padding, &self.date_pattern]
+            .iter()
+            .any(|input| input.handle == *handle)
+    }
+
+    pub fn close(&self) {
+        self.window.set_visible(false);
+        self.files.borrow_mut().clear();
+        self.plan.borrow_mut().clear();
+    }
+
+    fn rules(&self) -> Result<RenameRules, String> {
+        let number = |input: &nwg::TextInput, name: &str| {
+            input
+                .text()
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("The {} has to be a number", name))
+        };
+        let case = match self.case.selection() {
+            Some(1) => CaseTransform::Lower,
+            Some(2) => CaseTransform::Upper,
+            Some(3) => CaseTransform::Title,
+            _ => CaseTransform::Keep,
+        };
+        //Empty keeps the extension, a single dot removes it
+        let extension = match self.extension.text().trim() {
+            "" => None,
+            ext => Some(ext.trim_start_matches('.').to_string()),
+        };
+
+        Ok(RenameRules {
+            find: self.find.text(),
+            replace: self.replace.text(),
+            regex: self.regex.check_state() == nwg::CheckBoxState::Checked,
+            ignore_case: self.ignore_case.check_state() == nwg::CheckBoxState::Checked,
+            case,
+            counter_start: number(&self.counter_start, "counter start")?,
+            counter_step: 1,
+            counter_padding: number(&self.counter_padding, "counter padding")? as usize,
+            date_pattern: self.date_pattern.text(),
+            extension,
+        })
+    }
+
+    /// Dry run, nothing is renamed until `apply`
+    pub fn update_preview(&self) {
+        self.preview.clear();
+        let plan = self
+            .rules()
+            .and_then(|rules| bulk_rename::preview(&self.files.borrow(), &rules));
+
+        let plan = match plan {
+            Ok(p) => p,
+            Err(e) => {
+                self.status.set_text(&e);
+                self.apply_btn.set_enabled(false);
+                self.plan.borrow_mut().clear();
+                return;
+            }
+        };
+
+        for (i, p) in plan.iter().enumerate() {
+            let old_name = self.files.borrow()[i].name.clone();
+            self.preview
+                .insert_items_row(None, &[old_name, p.new_name.clone(), p.status.to_string()]);
+        }
+        let blocked = plan.iter().filter(|p| p.status.blocks()).count();
+        match blocked {
+            0 => self.status.set_text(&format!("{} files", plan.len())),
+            n => self.status.set_text(&format!("{} of {} files cant be renamed", n, plan.len())),
+        }
+        self.apply_btn.set_enabled(blocked == 0);
+        self.plan.replace(plan);
+    }
+
+    pub fn apply(&self) -> Result<OperationReport, String> {
+        let report = bulk_rename::apply(&self.plan.borrow())?;
+        self.close();
+        Ok(report)
+    }
+}
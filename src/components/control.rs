@@ -1,16 +1,35 @@
 use nwg::{
-    ButtonFlags, CheckBoxFlags, LabelFlags, ListViewExFlags, ListViewFlags, ListViewStyle,
+    ButtonFlags, CheckBoxFlags, CheckBoxState, LabelFlags, ListBoxFlags, ListViewExFlags, ListViewFlags, ListViewStyle,
     NwgError, TextBoxFlags,
 };
-use time::{OffsetDateTime, format_description};
-use winapi::um::winuser::{self, BS_AUTOCHECKBOX, SS_RIGHT};
+use winapi::um::{
+    commctrl::{LVS_EDITLABELS, LVS_OWNERDATA},
+    winuser::{self, BS_AUTOCHECKBOX, SS_RIGHT},
+};
 
-use crate::{app::BasicApp, resource_manager::ResourceType};
+use crate::{
+    app::BasicApp,
+    bulk_rename,
+    checksum,
+    context_actions,
+    export::ExportFormat,
+    file_types::FileCategory,
+    hashing::HashFunction,
+    journal::{self, Journal},
+    preview::THUMBNAIL_SIZE,
+    resource_manager::ResourceType,
+};
 
 use super::{
+    bulk_rename_dialog::{BulkRenameDialog, CASE_OPTIONS},
     fav_dir_bar::FavoriteDirSidebar, header_control::HeaderControl,
-    path_bar_control::PathBarControl, search_result_control::SearchResultControl,
+    path_bar_control::{PathBarControl, PATH_BAR_WIDTH, PATH_BAR_X},
+    preview_pane::{PreviewPane, PREVIEW_WIDTH},
+    search_help_dialog::SearchHelpDialog,
+    search_result_control::SearchResultControl,
     status_bar_control::StatusBarControl,
+    tab_bar_control::{TabBarControl, NEW_TAB_WIDTH, TAB_BAR_X, TAB_BAR_Y, TAB_HEIGHT},
+    dual_pane_control::{DualPaneControl, COLUMNS, PANE_WIDTH, RIGHT_PANE_X},
 };
 
 pub trait Control {
@@ -24,7 +43,7 @@ impl Control for StatusBarControl {
             .background_color(Some([0x32, 0x32, 0x32]))
             .flags(LabelFlags::ELIPSIS | LabelFlags::VISIBLE)
             .position((10, 600))
-            .size((100, 20))
+            .size((160, 20))
             .build(&mut app.status_bar.result_count)?;
 
         unsafe {
@@ -37,8 +56,8 @@ impl Control for StatusBarControl {
                         | LabelFlags::from_bits_unchecked(SS_RIGHT),
                 )
                 .text("200ms")
-                .position((120, 600))
-                .size((100, 20))
+                .position((170, 600))
+                .size((60, 20))
                 .build(&mut app.status_bar.search_duration)?;
 
             nwg::Label::builder()
@@ -60,7 +79,7 @@ impl Control for StatusBarControl {
             .parent(&app.window)
             .flags(ButtonFlags::ICON | ButtonFlags::VISIBLE)
             .bitmap(Some(
-                &app.resource_manager.get_bitmap(ResourceType::Refresh)?,
+                &*app.resource_manager.get_bitmap(ResourceType::Refresh)?,
             ))
             .build(&mut app.status_bar.index_refresh)?;
 
@@ -76,14 +95,59 @@ impl Control for StatusBarControl {
                 .build(&mut app.status_bar.index_usage)?;
         }
 
+        unsafe {
+            nwg::CheckBox::builder()
+                .size((100, 20))
+                .position((250, 600))
+                .parent(&app.window)
+                .text("Hidden files")
+                .background_color(Some([0x32, 0x32, 0x32]))
+                .flags(CheckBoxFlags::VISIBLE | CheckBoxFlags::from_bits_unchecked(BS_AUTOCHECKBOX))
+                .check_state(match app.cache.settings.get_mut().show_hidden_files {
+                    true => CheckBoxState::Checked,
+                    false => CheckBoxState::Unchecked,
+                })
+                .build(&mut app.status_bar.show_hidden)?;
+        }
+
+        nwg::Label::builder()
+            .parent(&app.window)
+            .background_color(Some([0x32, 0x32, 0x32]))
+            .flags(LabelFlags::ELIPSIS | LabelFlags::VISIBLE)
+            .position((360, 600))
+            .size((200, 20))
+            .build(&mut app.status_bar.task_progress)?;
+
+        nwg::Button::builder()
+            .size((90, 26))
+            .position((570, 597))
+            .parent(&app.window)
+            .text("Duplicates")
+            .build(&mut app.status_bar.find_duplicates)?;
+
+        nwg::Button::builder()
+            .size((60, 26))
+            .position((665, 597))
+            .parent(&app.window)
+            .text("Export")
+            .build(&mut app.status_bar.export_duplicates)?;
+
+        unsafe {
+            nwg::CheckBox::builder()
+                .size((75, 20))
+                .position((730, 600))
+                .parent(&app.window)
+                .text("Disk usage")
+                .background_color(Some([0x32, 0x32, 0x32]))
+                .flags(CheckBoxFlags::VISIBLE | CheckBoxFlags::from_bits_unchecked(BS_AUTOCHECKBOX))
+                .build(&mut app.status_bar.disk_usage)?;
+        }
+
+        *app.cache.journal.get_mut() = Journal::load(journal::journal_path());
+
         let index = app.cache.index.get_mut();
         index.check_for_updates();
-        let chrono_time: OffsetDateTime = index.modified_date.into();
-
-        let time = chrono_time
-            .format(&format_description::parse("[year]-[month]-[day] [hour]:[minute]").unwrap())
-            .unwrap()
-            .to_string();
+        let time = app.cache.formatter.get_mut().date(index.modified_date);
 
         app.status_bar.index_date.set_text(&time);
 
@@ -94,13 +158,200 @@ impl Control for StatusBarControl {
 impl Control for PathBarControl {
     fn load_components(app: &mut BasicApp) -> Result<(), NwgError> {
         nwg::TextBox::builder()
-            .position((120, 10))
-            .size((670, 30))
+            .position((PATH_BAR_X, 10))
+            .size((PATH_BAR_WIDTH, 30))
             .text("C:")
             .flags(TextBoxFlags::VISIBLE)
             .parent(&app.window)
             .build(&mut app.header.path_bar.view)?;
 
+        nwg::Button::builder()
+            .size((30, 30))
+            .position((PATH_BAR_X + PATH_BAR_WIDTH - 30, 10))
+            .text("...")
+            .parent(&app.window)
+            .build(&mut app.header.path_bar.edit_btn)?;
+
+        nwg::ListBox::builder()
+            .position((PATH_BAR_X, 40))
+            .size((PATH_BAR_WIDTH, 150))
+            .flags(ListBoxFlags::TAB_STOP)
+            .parent(&app.window)
+            .build(&mut app.header.path_bar.suggestions)?;
+
+        nwg::Menu::builder()
+            .popup(true)
+            .parent(&app.window)
+            .build(&mut app.header.path_bar.sibling_menu)?;
+
+        app.header.path_bar.set_editing(false);
+
+        Ok(())
+    }
+}
+
+impl Control for BulkRenameDialog {
+    fn load_components(app: &mut BasicApp) -> Result<(), NwgError> {
+        let dialog = &mut app.bulk_rename;
+        nwg::Window::builder()
+            .flags(nwg::WindowFlags::WINDOW)
+            .size((700, 500))
+            .center(true)
+            .title("Bulk rename")
+            .parent(Some(&app.window))
+            .build(&mut dialog.window)?;
+
+        for (input, placeholder, position, size, text) in [
+            (&mut dialog.find, "Find, empty replaces the whole name", (10, 10), (330, 25), ""),
+            (&mut dialog.replace, "Replace, {name} {n} {date} $1", (350, 10), (340, 25), "{name}"),
+            (&mut dialog.extension, "Extension, . removes it", (10, 45), (150, 25), ""),
+            (&mut dialog.counter_start, "Counter start", (170, 45), (80, 25), "1"),
+            (&mut dialog.counter_padding, "Digits", (260, 45), (60, 25), "3"),
+            (&mut dialog.date_pattern, "Date format", (330, 45), (160, 25), bulk_rename::DEFAULT_RENAME_DATE_PATTERN),
+        ] {
+            nwg::TextInput::builder()
+                .parent(&dialog.window)
+                .position(position)
+                .size(size)
+                .text(text)
+                .placeholder_text(Some(placeholder))
+                .build(input)?;
+        }
+
+        nwg::CheckBox::builder()
+            .parent(&dialog.window)
+            .position((10, 80))
+            .size((80, 25))
+            .text("Regex")
+            .build(&mut dialog.regex)?;
+
+        nwg::CheckBox::builder()
+            .parent(&dialog.window)
+            .position((100, 80))
+            .size((100, 25))
+            .text("Ignore case")
+            .build(&mut dialog.ignore_case)?;
+
+        nwg::ComboBox::builder()
+            .parent(&dialog.window)
+            .position((500, 45))
+            .size((190, 25))
+            .collection(CASE_OPTIONS.to_vec())
+            .selected_index(Some(0))
+            .build(&mut dialog.case)?;
+
+        nwg::ListView::builder()
+            .parent(&dialog.window)
+            .position((10, 115))
+            .size((680, 335))
+            .list_style(ListViewStyle::Detailed)
+            .ex_flags(ListViewExFlags::FULL_ROW_SELECT)
+            .build(&mut dialog.preview)?;
+
+        for (i, (text, width)) in [("Name", 230), ("New name", 230), ("Problem", 200)].into_iter().enumerate() {
+            dialog.preview.insert_column(nwg::InsertListViewColumn {
+                index: Some(i as i32),
+                fmt: None,
+                width: Some(width),
+                text: Some(text.into()),
+            });
+        }
+        dialog.preview.set_headers_enabled(true);
+
+        nwg::Label::builder()
+            .parent(&dialog.window)
+            .position((10, 462))
+            .size((560, 25))
+            .flags(LabelFlags::ELIPSIS | LabelFlags::VISIBLE)
+            .build(&mut dialog.status)?;
+
+        nwg::Button::builder()
+            .parent(&dialog.window)
+            .position((590, 458))
+            .size((100, 30))
+            .text("Rename")
+            .build(&mut dialog.apply_btn)?;
+
+        Ok(())
+    }
+}
+
+impl Control for PreviewPane {
+    fn load_components(app: &mut BasicApp) -> Result<(), NwgError> {
+        let (list_x, list_y) = app.search_results.list.position();
+        let (list_width, list_height) = app.search_results.list.size();
+        let x = list_x + list_width as i32 - PREVIEW_WIDTH + 5;
+
+        nwg::Button::builder()
+            .size((65, 30))
+            .position((830, 10))
+            .parent(&app.window)
+            .text("Preview")
+            .build(&mut app.preview.toggle_btn)?;
+
+        nwg::ImageFrame::builder()
+            .position((x, list_y))
+            .size((PREVIEW_WIDTH - 5, THUMBNAIL_SIZE as i32))
+            .background_color(Some([0x32, 0x32, 0x32]))
+            .parent(&app.window)
+            .build(&mut app.preview.image)?;
+        app.preview.image.set_visible(false);
+
+        nwg::Font::builder()
+            .family("Consolas")
+            .size(15)
+            .build(&mut app.preview.font)?;
+
+        nwg::TextBox::builder()
+            .position((x, list_y))
+            .size((PREVIEW_WIDTH - 5, list_height as i32))
+            .readonly(true)
+            .font(Some(&app.preview.font))
+            .flags(TextBoxFlags::VSCROLL | TextBoxFlags::AUTOVSCROLL | TextBoxFlags::HSCROLL)
+            .parent(&app.window)
+            .build(&mut app.preview.text)?;
+
+        nwg::Notice::builder()
+            .parent(&app.window)
+            .build(&mut app.preview.notice)?;
+
+        Ok(())
+    }
+}
+
+impl Control for SearchHelpDialog {
+    fn load_components(app: &mut BasicApp) -> Result<(), NwgError> {
+        let dialog = &mut app.search_help;
+        nwg::Window::builder()
+            .flags(nwg::WindowFlags::WINDOW)
+            .size((700, 320))
+            .center(true)
+            .title("Search help")
+            .parent(Some(&app.window))
+            .build(&mut dialog.window)?;
+
+        nwg::ListView::builder()
+            .parent(&dialog.window)
+            .position((10, 10))
+            .size((680, 300))
+            .list_style(ListViewStyle::Detailed)
+            .ex_flags(ListViewExFlags::FULL_ROW_SELECT)
+            .build(&mut dialog.list)?;
+
+        for (i, (text, width)) in [("Syntax", 120), ("Description", 380), ("Example", 160)].into_iter().enumerate() {
+            dialog.list.insert_column(nwg::InsertListViewColumn {
+                index: Some(i as i32),
+                fmt: None,
+                width: Some(width),
+                text: Some(text.into()),
+            });
+        }
+        dialog.list.set_headers_enabled(true);
+
+        for filter in app.cache.tabs.borrow().active().help.filters() {
+            dialog.list.insert_items_row(None, &[filter.syntax, filter.description, filter.example]);
+        }
+
         Ok(())
     }
 }
@@ -113,7 +364,7 @@ impl Control for HeaderControl {
             .parent(&app.window)
             .flags(ButtonFlags::ICON | ButtonFlags::VISIBLE)
             .bitmap(Some(
-                &app.resource_manager.get_bitmap(ResourceType::ArrowLeft)?,
+                &*app.resource_manager.get_bitmap(ResourceType::ArrowLeft)?,
             ))
             .build(&mut app.header.last_page_btn)?;
 
@@ -123,7 +374,7 @@ impl Control for HeaderControl {
             .parent(&app.window)
             .flags(ButtonFlags::ICON | ButtonFlags::VISIBLE)
             .bitmap(Some(
-                &app.resource_manager.get_bitmap(ResourceType::ArrowUp)?,
+                &*app.resource_manager.get_bitmap(ResourceType::ArrowUp)?,
             ))
             .build(&mut app.header.parent_page_btn)?;
         unsafe {
@@ -136,23 +387,37 @@ impl Control for HeaderControl {
             .parent(&app.window)
             .flags(ButtonFlags::ICON | ButtonFlags::VISIBLE)
             .bitmap(Some(
-                &app.resource_manager.get_bitmap(ResourceType::Refresh)?,
+                &*app.resource_manager.get_bitmap(ResourceType::Refresh)?,
             ))
             .build(&mut app.header.refresh_btn)?;
 
         nwg::TextInput::builder()
             .position((900, 10))
-            .size((290, 30))
+            .size((255, 30))
             .parent(&app.window)
-            .placeholder_text(Some("Search..."))
+            .placeholder_text(Some("Search... F1 for help"))
             .build(&mut app.header.search_input)?;
 
+        nwg::Button::builder()
+            .size((30, 30))
+            .position((1160, 10))
+            .parent(&app.window)
+            .text("?")
+            .build(&mut app.header.search_help_btn)?;
+
+        nwg::ListBox::builder()
+            .position((900, 40))
+            .size((290, 150))
+            .flags(ListBoxFlags::TAB_STOP)
+            .parent(&app.window)
+            .build(&mut app.header.search_suggestions)?;
+
         nwg::Button::builder()
             .size((30, 30))
             .position((795, 10))
             .parent(&app.window)
             .flags(ButtonFlags::ICON | ButtonFlags::VISIBLE)
-            .bitmap(Some(&app.resource_manager.get_bitmap(ResourceType::Copy)?))
+            .bitmap(Some(&*app.resource_manager.get_bitmap(ResourceType::Copy)?))
             .build(&mut app.header.copy_path_btn)?;
 
         //Call here because its part of the header
@@ -164,14 +429,31 @@ impl Control for HeaderControl {
 
 impl Control for SearchResultControl {
     fn load_components(app: &mut BasicApp) -> Result<(), NwgError> {
-        nwg::ListView::builder()
-            .parent(&app.window)
-            .position((120, 50))
-            .list_style(ListViewStyle::Detailed)
-            .ex_flags(ListViewExFlags::FULL_ROW_SELECT)
-            .size((1070, 540))
-            .background_color([0x32, 0x32, 0x32])
-            .build(&mut app.search_results.list)?;
+        unsafe {
+            //Owner data makes the list virtual, the rows are provided by the WM_NOTIFY handler
+            nwg::ListView::builder()
+                .parent(&app.window)
+                .position((120, TAB_BAR_Y + TAB_HEIGHT + 2))
+                .list_style(ListViewStyle::Detailed)
+                .flags(ListViewFlags::VISIBLE | ListViewFlags::from_bits_unchecked(LVS_OWNERDATA | LVS_EDITLABELS))
+                .ex_flags(ListViewExFlags::FULL_ROW_SELECT)
+                .size((1070, 540 - TAB_HEIGHT - 2))
+                .background_color([0x32, 0x32, 0x32])
+                .build(&mut app.search_results.list)?;
+        }
+
+        nwg::ImageList::builder()
+            .size((16, 16))
+            .initial(FileCategory::ALL.len() as i32)
+            .build(&mut app.search_results.icons)?;
+        //Same order as FileCategory::icon_index
+        for category in FileCategory::ALL {
+            let bitmap = app.resource_manager.get_bitmap(ResourceType::FileType(category))?;
+            app.search_results.icons.add_bitmap(&bitmap);
+        }
+        app.search_results
+            .list
+            .set_image_list(Some(&app.search_results.icons), nwg::ListViewImageListType::Small);
 
         app.search_results
             .list
@@ -236,6 +518,16 @@ impl Control for SearchResultControl {
             .text("Copy name")
             .build(&mut app.search_results.context_menu_items.copy_name)
             .unwrap();
+        nwg::MenuItem::builder()
+            .parent(&app.search_results.context_menu)
+            .text("Select all")
+            .build(&mut app.search_results.context_menu_items.select_all)
+            .unwrap();
+        nwg::MenuItem::builder()
+            .parent(&app.search_results.context_menu)
+            .text("Invert selection")
+            .build(&mut app.search_results.context_menu_items.invert_selection)
+            .unwrap();
 
         let mut seperator: nwg::MenuSeparator = Default::default();
         nwg::MenuSeparator::builder()
@@ -252,6 +544,21 @@ impl Control for SearchResultControl {
             .text("Add to favorite")
             .build(&mut app.search_results.context_menu_items.add_to_favorites)
             .unwrap();
+        nwg::MenuItem::builder()
+            .parent(&app.search_results.context_menu)
+            .text("Open in new tab")
+            .build(&mut app.search_results.context_menu_items.open_in_new_tab)
+            .unwrap();
+        nwg::MenuItem::builder()
+            .parent(&app.search_results.context_menu)
+            .text("Copy to other pane")
+            .build(&mut app.search_results.context_menu_items.copy_to_other_pane)
+            .unwrap();
+        nwg::MenuItem::builder()
+            .parent(&app.search_results.context_menu)
+            .text("Move to other pane")
+            .build(&mut app.search_results.context_menu_items.move_to_other_pane)
+            .unwrap();
 
         let mut seperator2: nwg::MenuSeparator = Default::default();
         nwg::MenuSeparator::builder()
@@ -263,27 +570,243 @@ impl Control for SearchResultControl {
             .seperator
             .push(seperator2);
 
-        nwg::MenuItem::builder()
+        for (text, item) in [
+            ("Copy to...", &mut app.search_results.context_menu_items.copy_to),
+            ("Move to...", &mut app.search_results.context_menu_items.move_to),
+            ("Rename", &mut app.search_results.context_menu_items.rename),
+            ("Bulk rename...", &mut app.search_results.context_menu_items.bulk_rename),
+            ("New folder", &mut app.search_results.context_menu_items.new_folder),
+            ("Move to recycle bin", &mut app.search_results.context_menu_items.trash),
+            ("Delete", &mut app.search_results.context_menu_items.delete),
+        ] {
+            nwg::MenuItem::builder()
+                .parent(&app.search_results.context_menu)
+                .text(text)
+                .build(item)?;
+        }
+
+        let mut seperator3: nwg::MenuSeparator = Default::default();
+        nwg::MenuSeparator::builder()
             .parent(&app.search_results.context_menu)
-            .text("MD5 hash")
-            .build(&mut app.search_results.context_menu_items.md5_hash)
-            .unwrap();
+            .build(&mut seperator3)?;
+        app.search_results
+            .context_menu_items
+            .seperator
+            .push(seperator3);
 
-        nwg::MenuItem::builder()
+        nwg::Menu::builder()
             .parent(&app.search_results.context_menu)
-            .text("SHA1 hash")
-            .build(&mut app.search_results.context_menu_items.sha1_hash)
-            .unwrap();
+            .text("Hash")
+            .build(&mut app.search_results.context_menu_items.hash_menu)?;
+
+        for hf in HashFunction::ALL {
+            let mut item: nwg::MenuItem = Default::default();
+            nwg::MenuItem::builder()
+                .parent(&app.search_results.context_menu_items.hash_menu)
+                .text(&format!("{} hash", hf.name()))
+                .build(&mut item)?;
+            app.search_results.context_menu_items.hashes.push((item, hf));
+        }
+
+        let mut seperator4: nwg::MenuSeparator = Default::default();
+        nwg::MenuSeparator::builder()
+            .parent(&app.search_results.context_menu_items.hash_menu)
+            .build(&mut seperator4)?;
+        app.search_results
+            .context_menu_items
+            .seperator
+            .push(seperator4);
+
         nwg::MenuItem::builder()
-            .parent(&app.search_results.context_menu)
-            .text("SHA256 hash")
-            .build(&mut app.search_results.context_menu_items.sha256_hash)
-            .unwrap();
+            .parent(&app.search_results.context_menu_items.hash_menu)
+            .text("Verify checksum")
+            .build(&mut app.search_results.context_menu_items.verify_checksum)?;
+
         nwg::MenuItem::builder()
+            .parent(&app.search_results.context_menu_items.hash_menu)
+            .text("Verify against clipboard hash")
+            .build(&mut app.search_results.context_menu_items.verify_clipboard)?;
+
+        nwg::Menu::builder()
+            .parent(&app.search_results.context_menu_items.hash_menu)
+            .text("Create sums file")
+            .build(&mut app.search_results.context_menu_items.sums_menu)?;
+
+        for hf in HashFunction::ALL.into_iter().filter(|hf| *hf != HashFunction::XXH3) {
+            let mut item: nwg::MenuItem = Default::default();
+            nwg::MenuItem::builder()
+                .parent(&app.search_results.context_menu_items.sums_menu)
+                .text(&checksum::sums_file_name(hf))
+                .build(&mut item)?;
+            app.search_results.context_menu_items.write_sums.push((item, hf));
+        }
+
+        nwg::Menu::builder()
             .parent(&app.search_results.context_menu)
-            .text("SHA512 hash")
-            .build(&mut app.search_results.context_menu_items.sha512_hash)
-            .unwrap();
+            .text("Export")
+            .build(&mut app.search_results.context_menu_items.export_menu)?;
+
+        for selection_only in [false, true] {
+            if selection_only {
+                let mut seperator: nwg::MenuSeparator = Default::default();
+                nwg::MenuSeparator::builder()
+                    .parent(&app.search_results.context_menu_items.export_menu)
+                    .build(&mut seperator)?;
+                app.search_results.context_menu_items.seperator.push(seperator);
+            }
+            for format in ExportFormat::ALL {
+                let text = match selection_only {
+                    true => format!("Selection as {}...", format),
+                    false => format!("All results as {}...", format),
+                };
+                let mut item: nwg::MenuItem = Default::default();
+                nwg::MenuItem::builder()
+                    .parent(&app.search_results.context_menu_items.export_menu)
+                    .text(&text)
+                    .build(&mut item)?;
+                app.search_results.context_menu_items.exports.push((item, format, selection_only));
+            }
+        }
+
+        nwg::Notice::builder()
+            .parent(&app.window)
+            .build(&mut app.search_results.hash_notice)?;
+
+        nwg::Notice::builder()
+            .parent(&app.window)
+            .build(&mut app.search_results.duplicate_notice)?;
+
+        nwg::Notice::builder()
+            .parent(&app.window)
+            .build(&mut app.search_results.usage_notice)?;
+
+        nwg::Notice::builder()
+            .parent(&app.window)
+            .build(&mut app.search_results.file_notice)?;
+
+        nwg::Notice::builder()
+            .parent(&app.window)
+            .build(&mut app.search_results.action_notice)?;
+
+        let actions = match context_actions::load(&context_actions::context_actions_path().to_string_lossy()) {
+            Ok(a) => a,
+            Err(e) => {
+                app.display_error(e);
+                Vec::new()
+            }
+        };
+        if !actions.is_empty() {
+            let mut seperator: nwg::MenuSeparator = Default::default();
+            nwg::MenuSeparator::builder()
+                .parent(&app.search_results.context_menu)
+                .build(&mut seperator)?;
+            app.search_results.context_menu_items.seperator.push(seperator);
+        }
+        for action in actions {
+            let mut item: nwg::MenuItem = Default::default();
+            nwg::MenuItem::builder()
+                .parent(&app.search_results.context_menu)
+                .text(&action.label)
+                .build(&mut item)?;
+            app.search_results.context_menu_items.custom_actions.push((item, action));
+        }
+
+        Ok(())
+    }
+}
+
+impl Control for TabBarControl {
+    fn load_components(app: &mut BasicApp) -> Result<(), NwgError> {
+        //Moved behind the last tab by every render
+        nwg::Button::builder()
+            .size((NEW_TAB_WIDTH, TAB_HEIGHT))
+            .position((TAB_BAR_X, TAB_BAR_Y))
+            .text("+")
+            .parent(&app.window)
+            .build(&mut app.tab_bar.new_tab_btn)?;
+
+        Ok(())
+    }
+}
+
+impl Control for DualPaneControl {
+    fn load_components(app: &mut BasicApp) -> Result<(), NwgError> {
+        let (_, list_y) = app.search_results.list.position();
+        let (_, list_height) = app.search_results.list.size();
+
+        nwg::CheckBox::builder()
+            .size((100, 24))
+            .position((10, 535))
+            .flags(CheckBoxFlags::VISIBLE | CheckBoxFlags::PUSHLIKE)
+            .text("Dual pane")
+            .parent(&app.window)
+            .build(&mut app.dual_pane.toggle_btn)?;
+
+        nwg::Button::builder()
+            .size((100, 24))
+            .position((10, 564))
+            .text("Compare")
+            .parent(&app.window)
+            .build(&mut app.dual_pane.compare_btn)?;
+        app.dual_pane.compare_btn.set_enabled(false);
+
+        nwg::TextInput::builder()
+            .position((RIGHT_PANE_X, TAB_BAR_Y))
+            .size((330, TAB_HEIGHT))
+            .parent(&app.window)
+            .build(&mut app.dual_pane.path_input)?;
+
+        nwg::TextInput::builder()
+            .position((RIGHT_PANE_X + 335, TAB_BAR_Y))
+            .size((PANE_WIDTH - 335, TAB_HEIGHT))
+            .placeholder_text(Some("Search..."))
+            .parent(&app.window)
+            .build(&mut app.dual_pane.search_input)?;
+
+        unsafe {
+            //Virtual like the left list
+            nwg::ListView::builder()
+                .parent(&app.window)
+                .position((RIGHT_PANE_X, list_y))
+                .size((PANE_WIDTH, list_height as i32))
+                .list_style(ListViewStyle::Detailed)
+                .flags(ListViewFlags::VISIBLE | ListViewFlags::from_bits_unchecked(LVS_OWNERDATA))
+                .ex_flags(ListViewExFlags::FULL_ROW_SELECT)
+                .background_color([0x32, 0x32, 0x32])
+                .build(&mut app.dual_pane.list)?;
+        }
+
+        for (i, (text, width)) in COLUMNS.into_iter().enumerate() {
+            app.dual_pane.list.insert_column(nwg::InsertListViewColumn {
+                index: Some(i as i32),
+                fmt: None,
+                width: Some(width),
+                text: Some(text.into()),
+            });
+        }
+        app.dual_pane.list.set_headers_enabled(true);
+
+        nwg::Menu::builder()
+            .popup(true)
+            .parent(&app.window)
+            .build(&mut app.dual_pane.context_menu)?;
+        nwg::MenuItem::builder()
+            .parent(&app.dual_pane.context_menu)
+            .text("Copy to other pane")
+            .build(&mut app.dual_pane.context_menu_items.copy_to_other_pane)?;
+        nwg::MenuItem::builder()
+            .parent(&app.dual_pane.context_menu)
+            .text("Move to other pane")
+            .build(&mut app.dual_pane.context_menu_items.move_to_other_pane)?;
+        nwg::MenuItem::builder()
+            .parent(&app.dual_pane.context_menu)
+            .text("Open in new tab")
+            .build(&mut app.dual_pane.context_menu_items.open_in_new_tab)?;
+
+        //Shown by the toggle
+        app.dual_pane.path_input.set_visible(false);
+        app.dual_pane.search_input.set_visible(false);
+        app.dual_pane.list.set_visible(false);
 
         Ok(())
     }
@@ -293,7 +816,7 @@ impl Control for FavoriteDirSidebar {
     fn load_components(app: &mut BasicApp) -> Result<(), NwgError> {
         nwg::ListView::builder()
             .parent(&app.window)
-            .size((100, 540))
+            .size((100, 480))
             .list_style(ListViewStyle::Detailed)
             .flags(ListViewFlags::NO_HEADER | ListViewFlags::VISIBLE)
             .ex_flags(ListViewExFlags::FULL_ROW_SELECT)
@@ -355,6 +878,12 @@ impl Control for FavoriteDirSidebar {
             .build(&mut app.fav_dir_bar.context_menu_items.copy_path)
             .unwrap();
 
+        nwg::MenuItem::builder()
+            .parent(&app.fav_dir_bar.context_menu)
+            .text("Open in new tab")
+            .build(&mut app.fav_dir_bar.context_menu_items.open_in_new_tab)
+            .unwrap();
+
         Ok(())
     }
 }
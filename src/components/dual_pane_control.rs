@@ -0,0 +1,151 @@
+use std::{
+    cell::{Cell, RefCell},
+    ops::RangeInclusive,
+};
+
+use crate::{
+    app_state::AppState,
+    dual_pane::{Comparison, Side},
+    formatting::Formatter,
+    memory::cache::Cache,
+    search_engine::result::SearchEngineResult,
+    selection::Selection,
+    virtual_list::VirtualList,
+};
+
+use super::{
+    search_result_control::{set_row_count, set_row_selected},
+    tab_bar_control::TabBarControl,
+};
+
+pub(super) const RIGHT_PANE_X: i32 = 660;
+/// Width of either list while both are shown
+pub(super) const PANE_WIDTH: i32 = 530;
+const FULL_WIDTH: i32 = 1070;
+pub(super) const COLUMNS: [(&str, i32); 4] = [("Name", 220), ("Date modified", 130), ("Size", 80), ("Difference", 90)];
+const ACTIVE_COLOR: [u8; 3] = [0x32, 0x32, 0x32];
+const INACTIVE_COLOR: [u8; 3] = [0x24, 0x24, 0x24];
+
+#[derive(Default)]
+pub struct DualPaneControl {
+    pub toggle_btn: nwg::CheckBox,
+    pub compare_btn: nwg::Button,
+    pub path_input: nwg::TextInput,
+    pub search_input: nwg::TextInput,
+    pub list: nwg::ListView,
+    pub context_menu: nwg::Menu,
+    pub context_menu_items: DualPaneMenuItems,
+    pub(super) context_menu_context_row: Cell<usize>,
+    /// Generation of the results the list shows and whether they were compared
+    rendered: Cell<Option<(u64, bool)>>,
+    selection: RefCell<Selection>,
+    /// Virtual like the left list, the rows are pulled through the same WM_NOTIFY handler
+    view: VirtualList<Vec<String>>,
+}
+
+#[derive(Default)]
+pub struct DualPaneMenuItems {
+    pub copy_to_other_pane: nwg::MenuItem,
+    pub move_to_other_pane: nwg::MenuItem,
+    pub open_in_new_tab: nwg::MenuItem,
+}
+
+impl DualPaneControl {
+    /// Halves the result list to make room for the right pane, or gives it the whole width back
+    pub fn set_visible(&self, visible: bool, left_list: &nwg::ListView, tab_bar: &TabBarControl) {
+        let (_, height) = left_list.size();
+        let width = match visible {
+            true => PANE_WIDTH,
+            false => FULL_WIDTH,
+        };
+        left_list.set_size(width as u32, height);
+        tab_bar.set_width(width);
+        self.path_input.set_visible(visible);
+        self.search_input.set_visible(visible);
+        self.list.set_visible(visible);
+        self.compare_btn.set_enabled(visible);
+        self.rendered.set(None);
+    }
+
+    /// The active pane is a bit lighter
+    pub fn set_active(&self, side: Side, left_list: &nwg::ListView) {
+        let (left, right) = match side {
+            Side::Left => (ACTIVE_COLOR, INACTIVE_COLOR),
+            Side::Right => (INACTIVE_COLOR, ACTIVE_COLOR),
+        };
+        left_list.set_background_color(left[0], left[1], left[2]);
+        self.list.set_background_color(right[0], right[1], right[2]);
+    }
+
+    /// Tells the list about new results or a new comparison since the last call.
+    /// The differing rows are selected, so they can be copied right away.
+    pub fn render(&self, state: &AppState, comparison: Option<&Comparison>) {
+        if self.path_input.text().trim() != state.path {
+            self.path_input.set_text(&state.path);
+        }
+        //Fires the text event, which ignores the query it already knows
+        if self.search_input.text() != state.query {
+            self.search_input.set_text(&state.query);
+        }
+
+        let key = Some((state.generation, comparison.is_some()));
+        if self.rendered.get() == key {
+            return;
+        }
+        self.rendered.set(key);
+
+        self.view.reset(state.results.len());
+        set_row_selected(&self.list, None, false);
+        self.selection.borrow_mut().reset(state.results.len());
+        set_row_count(&self.list, state.results.len());
+        if let Some(comparison) = comparison {
+            for row in comparison.rows(Side::Right) {
+                set_row_selected(&self.list, Some(row), true);
+            }
+        }
+    }
+
+    /// Called from the change notifications of the list, `None` means every row
+    pub fn selection_changed(&self, rows: Option<RangeInclusive<usize>>, selected: bool) {
+        if let Ok(mut selection) = self.selection.try_borrow_mut() {
+            selection.set_range(rows.unwrap_or(0..=usize::MAX), selected);
+        }
+    }
+
+    /// Text of a single cell, called by the list whenever a row becomes visible
+    pub fn cell_text(&self, cache: &Cache, row: usize, col: usize) -> Option<String> {
+        let dual = cache.dual_pane.try_borrow().ok()?;
+        //The comparison comes and goes without the rows changing
+        if col == 3 {
+            let difference = dual.comparison.as_ref()?.difference(Side::Right, row)?;
+            return Some(difference.label().to_string());
+        }
+        let formatter = cache.formatter.try_borrow().ok()?;
+        let formatted = self.view.row(&dual.right.results, row, |r| Self::format_row(r, &formatter))?;
+        formatted.get(col).cloned()
+    }
+
+    /// Formats the rows ahead of time, the list sends a hint before it asks for a range
+    pub fn prefetch(&self, cache: &Cache, from: usize, to: usize) {
+        if let (Ok(dual), Ok(formatter)) = (cache.dual_pane.try_borrow(), cache.formatter.try_borrow()) {
+            self.view.rows(&dual.right.results, from..to + 1, |r| Self::format_row(r, &formatter));
+        }
+    }
+
+    fn format_row(result: &SearchEngineResult, formatter: &Formatter) -> Vec<String> {
+        let size = match result.file_type.is_dir() {
+            true => String::new(),
+            false => formatter.size(result.size),
+        };
+        vec![result.name.clone(), formatter.date(result.modified), size]
+    }
+
+    /// Paths of the selected rows if the row is part of the selection, otherwise only the row itself
+    pub(super) fn paths_for_row(&self, state: &AppState, row: usize) -> Vec<String> {
+        let rows = self.selection.borrow().rows_for(row);
+        rows.into_iter()
+            .filter_map(|r| state.results.get(r))
+            .map(|r| r.full_path.clone())
+            .collect()
+    }
+}
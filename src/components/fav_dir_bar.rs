@@ -12,11 +12,16 @@ pub struct FavoriteDirSidebar {
 pub struct FavoriteDirSidebarMenuItems {
     pub remove: nwg::MenuItem,
     pub copy_path: nwg::MenuItem,
+    pub open_in_new_tab: nwg::MenuItem,
 }
 
 impl FavoriteDirSidebar {
     pub (super) fn execute_remove(&self) {}
 
+    pub (super) fn context_path(&self) -> Option<String> {
+        self.list.item(self.context_menu_context_row.get(), 1, 260).map(|i| i.text)
+    }
+
     pub (super) fn execute_copy_path(&self) {
         let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
         ctx.set_contents(
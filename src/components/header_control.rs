@@ -1,11 +1,47 @@
+use std::cell::RefCell;
+
+use crate::search_engine::search_help::{SearchHelp, Suggestion};
+
 use super::path_bar_control::PathBarControl;
 
+const MAX_SEARCH_SUGGESTIONS: usize = 10;
+
 #[derive(Default)]
 pub struct HeaderControl {
     pub last_page_btn: nwg::Button,
     pub parent_page_btn: nwg::Button,
     pub refresh_btn: nwg::Button,
     pub search_input: nwg::TextInput,
+    pub search_help_btn: nwg::Button,
+    pub search_suggestions: nwg::ListBox<String>,
     pub path_bar: PathBarControl,
     pub copy_path_btn: nwg::Button,
-}
\ No newline at end of file
+    suggestions: RefCell<Vec<Suggestion>>,
+}
+
+impl HeaderControl {
+    /// Shows the flags and keys that fit what is typed at the end of the search input
+    pub fn update_search_suggestions(&self, help: &SearchHelp) {
+        let suggestions = help.suggestions(&self.search_input.text(), MAX_SEARCH_SUGGESTIONS);
+        self.search_suggestions.set_visible(!suggestions.is_empty());
+        self.search_suggestions
+            .set_collection(suggestions.iter().map(|s| s.label.clone()).collect());
+        self.suggestions.replace(suggestions);
+    }
+
+    /// Writes the selected suggestion into the search input, which starts the search
+    pub fn accept_search_suggestion(&self) {
+        let text = match self.search_suggestions.selection() {
+            Some(i) => self.suggestions.borrow().get(i).map(|s| s.text.clone()),
+            None => None,
+        };
+        if let Some(text) = text {
+            self.search_suggestions.set_visible(false);
+            self.search_input.set_text(&text);
+            self.search_input.set_focus();
+            //Keeps typing at the end, e.g. the depth of !d
+            let end = text.encode_utf16().count() as u32;
+            self.search_input.set_selection(end..end);
+        }
+    }
+}
@@ -3,60 +3,166 @@ use std::rc::Rc;
 use nwg::EventData;
 use winapi::{shared::windef::POINT, um::winuser::GetCursorPos};
 
-use crate::app::BasicApp;
+use crate::{
+    app::BasicApp,
+    app_state::Action,
+    dual_pane::Side,
+    file_operations::{self, DeleteMode, Operation},
+};
 
-use super::{search_result_control::{SearchResultControl, HashFunction}, fav_dir_bar::FavoriteDirSidebar};
+use super::{dual_pane_control::DualPaneControl, search_result_control::SearchResultControl, fav_dir_bar::FavoriteDirSidebar};
 
 pub trait Menuable {
-    fn open_menu(&self, evt_data: &EventData);
+    fn open_menu(&self, evt_data: &EventData, app: &BasicApp);
     fn execute_menu_item_click(&self, item: &nwg::ControlHandle, app: Rc<BasicApp>);
 }
 
 impl Menuable for SearchResultControl {
-    fn open_menu(&self, evt_data: &nwg::EventData) {
+    fn open_menu(&self, evt_data: &nwg::EventData, app: &BasicApp) {
         let (row, _col) = evt_data.on_list_view_item_index();
-        if row >= self.list.len() {
-            //Clicked on empty field
+        let results = self.results_for_row(&app.cache, row);
+        //Clicked on empty field
+        if results.is_empty() {
             return;
         }
-        let is_folder = self
-            .list
-            .item(evt_data.on_list_view_item_index().0, 2, 260)
-            .expect("Invalid column clicked")
-            .text
-            .eq("Directory");
         //ignoring the fact that it might be already added because it doesnt cause any trouble
-        self.context_menu_items.add_to_favorites.set_enabled(is_folder);
+        self.context_menu_items
+            .add_to_favorites
+            .set_enabled(results.iter().any(|r| r.file_type.is_dir()));
+        self.context_menu_items
+            .open_in_new_tab
+            .set_enabled(results.iter().any(|r| r.file_type.is_dir()));
+        let dual_pane = app.cache.dual_pane.borrow().enabled;
+        self.context_menu_items.copy_to_other_pane.set_enabled(dual_pane);
+        self.context_menu_items.move_to_other_pane.set_enabled(dual_pane);
+        for (item, action) in &self.context_menu_items.custom_actions {
+            item.set_enabled(results.iter().any(|r| action.applies_to(r)));
+        }
+        self.context_menu_context_row.set(row);
         let mut cursor_pos: POINT = POINT { x: 0, y: 0 };
         unsafe {
             GetCursorPos(&mut cursor_pos);
         }
         self.context_menu.popup(cursor_pos.x, cursor_pos.y);
-        self.context_menu_context_row
-            .set(evt_data.on_list_view_item_index().0);
     }
 
     fn execute_menu_item_click(&self, item: &nwg::ControlHandle, app: Rc<BasicApp>) {
+        if item.eq(&self.context_menu_items.select_all) {
+            self.select_all();
+            return;
+        } else if item.eq(&self.context_menu_items.invert_selection) {
+            self.invert_selection();
+            return;
+        }
+
+        let results = self.results_for_row(&app.cache, self.context_menu_context_row.get());
+        if results.is_empty() {
+            return;
+        }
+
         if item.eq(&self.context_menu_items.copy_path) {
-            self.execute_copy_path();
+            self.execute_copy_path(&results);
         } else if item.eq(&self.context_menu_items.add_to_favorites) {
-            self.execute_add_to_favorites(app.clone());
+            self.execute_add_to_favorites(app.clone(), &results);
+        } else if item.eq(&self.context_menu_items.open_in_new_tab) {
+            for dir in results.into_iter().filter(|r| r.file_type.is_dir()) {
+                app.open_tab(dir.full_path, false);
+            }
         } else if item.eq(&self.context_menu_items.copy_name) {
-            self.execute_copy_name();
-        } else if item.eq(&self.context_menu_items.md5_hash) {
-            self.copy_file_hash(HashFunction::MD5);
-        } else if item.eq(&self.context_menu_items.sha1_hash) {
-            self.copy_file_hash(HashFunction::SHA1);
-        } else if item.eq(&self.context_menu_items.sha256_hash) {
-            self.copy_file_hash(HashFunction::SHA256);
-        } else if item.eq(&self.context_menu_items.sha512_hash) {
-            self.copy_file_hash(HashFunction::SHA512);
+            self.execute_copy_name(&results);
+        } else if let Some((_, hf)) = self.context_menu_items.hashes.iter().find(|(i, _)| item.eq(i)) {
+            let paths = self.paths_for_row(&app.cache, self.context_menu_context_row.get());
+            if let Err(e) = self.start_file_hash(*hf, paths) {
+                app.display_error(e);
+            }
+        } else if item.eq(&self.context_menu_items.verify_checksum) {
+            let paths = self.paths_for_row(&app.cache, self.context_menu_context_row.get());
+            if let Err(e) = self.start_verify_sidecar(paths) {
+                app.display_error(e);
+            }
+        } else if item.eq(&self.context_menu_items.verify_clipboard) {
+            let paths = self.paths_for_row(&app.cache, self.context_menu_context_row.get());
+            if let Err(e) = self.start_verify_clipboard(paths) {
+                app.display_error(e);
+            }
+        } else if let Some((_, hf)) = self.context_menu_items.write_sums.iter().find(|(i, _)| item.eq(i)) {
+            let paths = self.paths_for_row(&app.cache, self.context_menu_context_row.get());
+            if let Err(e) = self.start_write_sums(*hf, paths) {
+                app.display_error(e);
+            }
+        } else if item.eq(&self.context_menu_items.copy_to) || item.eq(&self.context_menu_items.move_to) {
+            let sources = self.paths_for_row(&app.cache, self.context_menu_context_row.get());
+            let target_dir = match app.choose_directory() {
+                Some(d) => d,
+                None => return,
+            };
+            let operation = match item.eq(&self.context_menu_items.copy_to) {
+                true => Operation::Copy { sources, target_dir },
+                false => Operation::Move { sources, target_dir },
+            };
+            if let Err(e) = self.start_file_operation(&app, operation) {
+                app.display_error(e);
+            }
+        } else if item.eq(&self.context_menu_items.copy_to_other_pane)
+            || item.eq(&self.context_menu_items.move_to_other_pane)
+        {
+            let sources = self.paths_for_row(&app.cache, self.context_menu_context_row.get());
+            let target_dir = app.pane_dir(Side::Right);
+            let operation = match item.eq(&self.context_menu_items.copy_to_other_pane) {
+                true => Operation::Copy { sources, target_dir },
+                false => Operation::Move { sources, target_dir },
+            };
+            if let Err(e) = self.start_file_operation(&app, operation) {
+                app.display_error(e);
+            }
+        } else if item.eq(&self.context_menu_items.rename) {
+            self.begin_rename(self.context_menu_context_row.get());
+        } else if item.eq(&self.context_menu_items.bulk_rename) {
+            let files = self.results_for_row(&app.cache, self.context_menu_context_row.get());
+            app.bulk_rename.open(files);
+        } else if item.eq(&self.context_menu_items.new_folder) {
+            let parent = match app.header.path_bar.get_path() {
+                Ok(p) => p,
+                Err(e) => {
+                    app.display_error(e);
+                    return;
+                }
+            };
+            let report = file_operations::new_folder(&parent, "New folder");
+            if let Some(e) = report.errors().first() {
+                app.display_error(e.clone());
+                return;
+            }
+            app.record_operation(&report);
+            app.dispatch(Action::Refresh);
+            let created = report.items.first().and_then(|i| i.target.clone()).unwrap_or_default();
+            if let Some(row) = self.row_of(&app.cache, &created) {
+                self.begin_rename(row);
+            }
+        } else if item.eq(&self.context_menu_items.trash) || item.eq(&self.context_menu_items.delete) {
+            let paths = self.paths_for_row(&app.cache, self.context_menu_context_row.get());
+            let mode = match item.eq(&self.context_menu_items.trash) {
+                true => DeleteMode::Trash,
+                false => DeleteMode::Permanent,
+            };
+            self.delete_paths(&app, paths, mode);
+        } else if let Some((_, format, selection_only)) = self.context_menu_items.exports.iter().find(|(i, ..)| item.eq(i)) {
+            //Cloned, the save dialog runs its own message loop which might touch the results
+            let results = match selection_only {
+                true => results,
+                false => app.cache.tabs.borrow().active().results.clone(),
+            };
+            app.export_results(&results, *format);
+        } else if let Some((_, action)) = self.context_menu_items.custom_actions.iter().find(|(i, _)| item.eq(i)) {
+            if let Err(e) = self.start_context_action(action, &results) {
+                app.display_error(e);
+            }
         } //else it doesnt belong here
     }
 }
 
 impl Menuable for FavoriteDirSidebar {
-    fn open_menu(&self, evt_data: &nwg::EventData) {
+    fn open_menu(&self, evt_data: &nwg::EventData, _: &BasicApp) {
         let (row, _col) = evt_data.on_list_view_item_index();
         if row >= self.list.len() {
             //Clicked on empty field
@@ -71,11 +177,68 @@ impl Menuable for FavoriteDirSidebar {
             .set(evt_data.on_list_view_item_index().0);
     }
 
-    fn execute_menu_item_click(&self, item: &nwg::ControlHandle, _: Rc<BasicApp>) {
+    fn execute_menu_item_click(&self, item: &nwg::ControlHandle, app: Rc<BasicApp>) {
         if item.eq(&self.context_menu_items.copy_path) {
             self.execute_copy_path();
+        } else if item.eq(&self.context_menu_items.open_in_new_tab) {
+            if let Some(path) = self.context_path() {
+                app.open_tab(path, false);
+            }
         } else if item.eq(&self.context_menu_items.remove) {
             self.execute_remove();
         } //else it doesnt belong here
     }
 }
+
+impl Menuable for DualPaneControl {
+    fn open_menu(&self, evt_data: &nwg::EventData, app: &BasicApp) {
+        let (row, _col) = evt_data.on_list_view_item_index();
+        let dual = app.cache.dual_pane.borrow();
+        let result = match dual.right.results.get(row) {
+            Some(r) => r,
+            //Clicked on empty field
+            None => return,
+        };
+        self.context_menu_items
+            .open_in_new_tab
+            .set_enabled(result.file_type.is_dir());
+        self.context_menu_context_row.set(row);
+        let mut cursor_pos: POINT = POINT { x: 0, y: 0 };
+        unsafe {
+            GetCursorPos(&mut cursor_pos);
+        }
+        self.context_menu.popup(cursor_pos.x, cursor_pos.y);
+    }
+
+    fn execute_menu_item_click(&self, item: &nwg::ControlHandle, app: Rc<BasicApp>) {
+        let row = self.context_menu_context_row.get();
+        let sources = self.paths_for_row(&app.cache.dual_pane.borrow().right, row);
+        if sources.is_empty() {
+            return;
+        }
+
+        if item.eq(&self.context_menu_items.copy_to_other_pane) || item.eq(&self.context_menu_items.move_to_other_pane) {
+            let target_dir = app.pane_dir(Side::Left);
+            let operation = match item.eq(&self.context_menu_items.copy_to_other_pane) {
+                true => Operation::Copy { sources, target_dir },
+                false => Operation::Move { sources, target_dir },
+            };
+            if let Err(e) = app.search_results.start_file_operation(&app, operation) {
+                app.display_error(e);
+            }
+        } else if item.eq(&self.context_menu_items.open_in_new_tab) {
+            let dir = app
+                .cache
+                .dual_pane
+                .borrow()
+                .right
+                .results
+                .get(row)
+                .filter(|r| r.file_type.is_dir())
+                .map(|r| r.full_path.clone());
+            if let Some(dir) = dir {
+                app.open_tab(dir, false);
+            }
+        } //else it doesnt belong here
+    }
+}
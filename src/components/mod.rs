@@ -1,27 +1,53 @@
 use nwg::NwgError;
 
-use crate::app::BasicApp;
+use crate::{app::BasicApp, formatting::Formatter, keybindings::Keymap};
 
 use self::{
-    control::Control, fav_dir_bar::FavoriteDirSidebar,
-    search_result_control::SearchResultControl, status_bar_control::StatusBarControl, header_control::HeaderControl,
+    bulk_rename_dialog::BulkRenameDialog, control::Control, fav_dir_bar::FavoriteDirSidebar,
+    preview_pane::PreviewPane, search_help_dialog::SearchHelpDialog, search_result_control::SearchResultControl, status_bar_control::StatusBarControl, header_control::HeaderControl,
+    tab_bar_control::TabBarControl, dual_pane_control::DualPaneControl,
 };
 
+pub mod bulk_rename_dialog;
 pub mod control;
+pub mod dual_pane_control;
 pub mod fav_dir_bar;
 pub mod header_control;
 pub mod menuable;
 pub mod path_bar_control;
+pub mod preview_pane;
+pub mod search_help_dialog;
 pub mod search_result_control;
 pub mod status_bar_control;
+pub mod tab_bar_control;
 mod window;
 
 pub fn load_components(data: &mut BasicApp) -> Result<(), NwgError> {
     window::load(data)?;
+
+    let settings = data.cache.settings.get_mut();
+    match Formatter::new(settings.size_units, &settings.date_format) {
+        Ok(f) => {
+            data.cache.formatter.replace(f);
+        }
+        Err(e) => data.display_error(e),
+    }
+
+    let (keymap, problems) = Keymap::with_overrides(&data.cache.settings.get_mut().keybindings);
+    data.cache.keymap.replace(keymap);
+    if !problems.is_empty() {
+        data.display_error(problems.join("\n"));
+    }
+
     HeaderControl::load_components(data)?;
+    TabBarControl::load_components(data)?;
     FavoriteDirSidebar::load_components(data)?;
     SearchResultControl::load_components(data)?;
+    DualPaneControl::load_components(data)?;
+    PreviewPane::load_components(data)?;
     StatusBarControl::load_components(data)?;
+    BulkRenameDialog::load_components(data)?;
+    SearchHelpDialog::load_components(data)?;
 
     Ok(())
 }
@@ -1,11 +1,28 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
-use crate::ring_buffer::RingBuffer;
+use winapi::{shared::windef::POINT, um::winuser::GetCursorPos};
+
+use crate::{
+    breadcrumb::{self, Breadcrumb, DirectorySource, LiveDirectorySource},
+    memory::index::Index,
+};
+
+pub(super) const PATH_BAR_X: i32 = 120;
+pub(super) const PATH_BAR_WIDTH: i32 = 670;
+const SEGMENT_ARROW_WIDTH: i32 = 16;
+const MAX_SUGGESTIONS: usize = 20;
 
 #[derive(Default)]
 pub struct PathBarControl {
     pub view: nwg::TextBox,
-    last_page: RefCell<RingBuffer<String>>,
+    pub edit_btn: nwg::Button,
+    pub suggestions: nwg::ListBox<String>,
+    pub sibling_menu: nwg::Menu,
+    breadcrumb: RefCell<Breadcrumb>,
+    /// Segment button and the arrow button next to it that opens the sibling dropdown
+    segment_buttons: RefCell<Vec<(nwg::Button, nwg::Button)>>,
+    sibling_items: RefCell<Vec<(nwg::MenuItem, String)>>,
+    editing: Cell<bool>,
 }
 
 impl PathBarControl {
@@ -18,45 +35,160 @@ impl PathBarControl {
         Ok(String::from(self.view.text().trim()) + "\\")
     }
 
-    pub fn move_into_directory(&self, dir_path: String) {
-        self.last_page.borrow_mut().push(self.view.text());
-        self.view.clear();
-        self.view.set_text(&dir_path);
+    pub fn is_editing(&self) -> bool {
+        self.editing.get()
+    }
+
+    /// Switches between the typed path and the clickable segments
+    pub fn set_editing(&self, editing: bool) {
+        self.editing.set(editing);
+        self.view.set_visible(editing);
+        self.edit_btn.set_visible(!editing);
+        for (segment, arrow) in self.segment_buttons.borrow().iter() {
+            segment.set_visible(!editing);
+            arrow.set_visible(!editing);
+        }
+        if editing {
+            self.view.set_focus();
+        } else {
+            self.suggestions.set_visible(false);
+        }
+    }
+
+    /// Rebuilds the segment buttons if the path changed since the last call
+    pub fn render_breadcrumbs(&self, parent: &nwg::Window) -> Result<(), nwg::NwgError> {
+        let crumbs = Breadcrumb::from_path(self.view.text().trim());
+        if crumbs.eq(&self.breadcrumb.borrow()) {
+            return Ok(());
+        }
+
+        let mut buttons = self.segment_buttons.borrow_mut();
+        buttons.clear();
+
+        let widths: Vec<i32> = crumbs
+            .segments()
+            .iter()
+            .map(|s| (s.name.chars().count() as i32 * 8 + 14).clamp(30, 200))
+            .collect();
+
+        //Leading segments are dropped until the rest fits next to the edit button
+        let available = PATH_BAR_WIDTH - 30;
+        let mut first_visible = 0;
+        while first_visible + 1 < widths.len()
+            && widths[first_visible..]
+                .iter()
+                .map(|w| w + SEGMENT_ARROW_WIDTH)
+                .sum::<i32>()
+                > available
+        {
+            first_visible += 1;
+        }
+
+        let mut x = PATH_BAR_X;
+        for (segment, width) in crumbs.segments().iter().zip(widths.iter()).skip(first_visible) {
+            let mut segment_btn = Default::default();
+            nwg::Button::builder()
+                .text(&segment.name)
+                .position((x, 10))
+                .size((*width, 30))
+                .parent(parent)
+                .build(&mut segment_btn)?;
+            x += width;
+
+            let mut arrow_btn = Default::default();
+            nwg::Button::builder()
+                .text(">")
+                .position((x, 10))
+                .size((SEGMENT_ARROW_WIDTH, 30))
+                .parent(parent)
+                .build(&mut arrow_btn)?;
+            x += SEGMENT_ARROW_WIDTH;
+
+            segment_btn.set_visible(!self.editing.get());
+            arrow_btn.set_visible(!self.editing.get());
+            buttons.push((segment_btn, arrow_btn));
+        }
+
+        self.breadcrumb.replace(crumbs);
+
+        Ok(())
+    }
+
+    /// The path of the segment if the handle was one
+    pub fn click_segment(&self, handle: &nwg::ControlHandle) -> Option<String> {
+        let target = self.segment_index(|(segment, _)| segment.handle.eq(handle))?;
+        self.breadcrumb.borrow().path_of(target).map(String::from)
     }
 
-    pub fn depth(&self) -> usize {
-        self.view.text().matches('\\').count()
+    /// Opens the sibling dropdown if the handle was one of the segment arrows
+    pub fn open_siblings(&self, handle: &nwg::ControlHandle, index: &Index) -> bool {
+        let target = match self.segment_index(|(_, arrow)| arrow.handle.eq(handle)) {
+            Some(t) => t,
+            None => return false,
+        };
+
+        let sources: [&dyn DirectorySource; 2] = [index, &LiveDirectorySource];
+        let siblings = self.breadcrumb.borrow().siblings(target, &sources);
+        if siblings.is_empty() {
+            return true;
+        }
+
+        let mut items = self.sibling_items.borrow_mut();
+        items.clear();
+        for sibling in siblings {
+            let mut item = Default::default();
+            let built = nwg::MenuItem::builder()
+                .parent(&self.sibling_menu)
+                .text(&sibling.name)
+                .build(&mut item);
+            if built.is_ok() {
+                items.push((item, sibling.path));
+            }
+        }
+
+        let mut cursor_pos: POINT = POINT { x: 0, y: 0 };
+        unsafe {
+            GetCursorPos(&mut cursor_pos);
+        }
+        self.sibling_menu.popup(cursor_pos.x, cursor_pos.y);
+
+        true
     }
 
-    pub fn any_last_page(&self) -> bool {
-        !self.last_page.borrow_mut().all_read()
+    /// The path of the sibling if the handle was one from the dropdown
+    pub fn select_sibling(&self, handle: &nwg::ControlHandle) -> Option<String> {
+        self.sibling_items
+            .borrow()
+            .iter()
+            .find(|(item, _)| item.handle.eq(handle))
+            .map(|(_, path)| path.clone())
     }
 
-    #[allow(unused_assignments)]
-    pub fn move_one_back(&self) {
-        let mut val = self.last_page.borrow_mut().pop();
-        if val.is_none() {
+    pub fn update_suggestions(&self, index: &Index) {
+        if !self.editing.get() {
             return;
         }
-        self.view.set_text(&val.unwrap());
-        val = Some(self.view.text());
+        let sources: [&dyn DirectorySource; 2] = [index, &LiveDirectorySource];
+        let suggestions = breadcrumb::autocomplete(&self.view.text(), &sources, MAX_SUGGESTIONS);
+        self.suggestions.set_visible(!suggestions.is_empty());
+        self.suggestions.set_collection(suggestions);
     }
 
-    pub fn move_one_up(&self) {
-        let path_parts: Vec<String> = self
-            .view
-            .text()
-            .split('\\')
-            .map(|s| s.to_string())
-            .collect();
-        if let Some(first) = path_parts.first() {
-            let result = path_parts
-                .iter()
-                .skip(1)
-                .take(path_parts.len() - 2)
-                .fold(first.clone(), |acc, part| acc + "\\" + part);
-            self.view.set_text(&result);
-            self.last_page.borrow_mut().push(self.view.text());
+    /// Writes the selected suggestion into the path bar so the user can keep typing
+    pub fn accept_suggestion(&self) {
+        if let Some(s) = self.suggestions.selection_string() {
+            self.view.set_text(&(s + "\\"));
+            self.view.set_focus();
         }
     }
+
+    fn segment_index<F>(&self, pred: F) -> Option<usize>
+    where
+        F: Fn(&(nwg::Button, nwg::Button)) -> bool,
+    {
+        let buttons = self.segment_buttons.borrow();
+        let pos = buttons.iter().position(pred)?;
+        //Collapsed leading segments have no buttons
+        Some(self.breadcrumb.borrow().segments().len() - buttons.len() + pos)
+    }
 }
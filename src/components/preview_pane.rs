@@ -0,0 +1,173 @@
+use std::{
+    cell::{Cell, RefCell},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use crate::{
+    formatting::Formatter,
+    preview::{Preview, PreviewCache, PreviewContent, PreviewJob, Previewers, THUMBNAIL_SIZE},
+};
+
+pub(super) const PREVIEW_WIDTH: i32 = 360;
+
+/// Shows the selected file next to the result list, previews are made off-thread
+#[derive(Default)]
+pub struct PreviewPane {
+    pub toggle_btn: nwg::Button,
+    pub image: nwg::ImageFrame,
+    pub text: nwg::TextBox,
+    pub notice: nwg::Notice,
+    pub font: nwg::Font,
+    previewers: Arc<Previewers>,
+    cache: RefCell<PreviewCache>,
+    job: RefCell<Option<PreviewJob>>,
+    /// Kept alive as long as the image frame shows it
+    bitmap: RefCell<Option<nwg::Bitmap>>,
+    visible: Cell<bool>,
+}
+
+impl PreviewPane {
+    pub fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
+    /// Makes room for the pane by shrinking the result list
+    pub fn set_visible(&self, visible: bool, list: &nwg::ListView) {
+        self.visible.set(visible);
+        let (width, height) = list.size();
+        let width = match visible {
+            true => width - PREVIEW_WIDTH as u32,
+            false => width + PREVIEW_WIDTH as u32,
+        };
+        list.set_size(width, height);
+        self.text.set_visible(visible);
+        if !visible {
+            self.image.set_visible(false);
+            self.job.replace(None);
+        }
+    }
+
+    /// Shows the cached preview right away, otherwise starts making it
+    pub fn show_path(&self, path: &str, formatter: &Formatter) {
+        let path = PathBuf::from(path);
+        if let Some(preview) = self.cache.borrow().get(&path) {
+            self.job.replace(None);
+            self.render(&preview, formatter);
+            return;
+        }
+        if self.job.borrow().as_ref().is_some_and(|j| j.path == path) {
+            return;
+        }
+
+        let sender = self.notice.sender();
+        let job = PreviewJob::spawn(Arc::clone(&self.previewers), path, move || sender.notice());
+        self.job.replace(Some(job));
+        self.text.set_text("Loading preview...");
+    }
+
+    pub fn poll_job(&self, formatter: &Formatter) {
+        let result = match self.job.borrow().as_ref().and_then(|j| j.poll()) {
+            Some(r) => r,
+            None => return,
+        };
+        self.job.replace(None);
+
+        match result {
+            Ok(preview) => {
+                let preview = Arc::new(preview);
+                self.cache.borrow_mut().insert(Arc::clone(&preview));
+                self.render(&preview, formatter);
+            }
+            Err(e) => {
+                self.image.set_visible(false);
+                self.text.set_text(&e);
+            }
+        }
+    }
+
+    fn render(&self, preview: &Preview, formatter: &Formatter) {
+        let mut lines = vec![
+            preview.path.to_string_lossy().to_string(),
+            format!(
+                "{}, modified {}{}",
+                formatter.size(preview.size),
+                formatter.date(preview.modified),
+                if preview.readonly { ", read only" } else { "" }
+            ),
+            String::new(),
+        ];
+
+        let mut thumbnail = None;
+        match &preview.content {
+            PreviewContent::Text { text, encoding, truncated } => {
+                lines.push(format!("{}{}", encoding, if *truncated { ", only the start" } else { "" }));
+                lines.push(String::new());
+                lines.push(text.clone());
+            }
+            PreviewContent::Image { width, height, thumbnail: t } => {
+                lines.push(format!("{} x {} pixels", width, height));
+                thumbnail = Some(t.to_bmp());
+            }
+            PreviewContent::Archive { entries, total } => {
+                lines.push(format!("{} entries", total));
+                lines.push(String::new());
+                for e in entries {
+                    match e.is_dir {
+                        true => lines.push(e.name.clone()),
+                        false => lines.push(format!("{}  ({})", e.name, formatter.size(e.size))),
+                    }
+                }
+                if entries.len() < *total {
+                    lines.push(format!("... and {} more", total - entries.len()));
+                }
+            }
+            PreviewContent::Hex { dump, truncated } => {
+                lines.push(format!("Binary{}", if *truncated { ", only the start" } else { "" }));
+                lines.push(String::new());
+                lines.push(dump.clone());
+            }
+        }
+
+        self.show_thumbnail(thumbnail);
+        //The edit control only breaks lines on \r\n
+        let text = lines.join("\n").replace("\r\n", "\n").replace('\n', "\r\n");
+        self.text.set_text(&text);
+    }
+
+    fn show_thumbnail(&self, bmp: Option<Vec<u8>>) {
+        let (x, y) = self.text.position();
+        let (width, height) = self.text.size();
+        let bitmap = bmp.and_then(|bmp| {
+            let mut bitmap = nwg::Bitmap::default();
+            nwg::Bitmap::builder()
+                .source_bin(Some(&bmp))
+                .build(&mut bitmap)
+                .ok()
+                .map(|_| bitmap)
+        });
+
+        //The text moves below the thumbnail and back
+        let image_height = THUMBNAIL_SIZE as i32 + 10;
+        let image_y = self.image.position().1;
+        match &bitmap {
+            Some(b) => {
+                self.image.set_bitmap(Some(b));
+                self.image.set_visible(true);
+                if y == image_y {
+                    self.text.set_position(x, y + image_height);
+                    self.text.set_size(width, height - image_height as u32);
+                }
+            }
+            None => {
+                self.image.set_bitmap(None);
+                self.image.set_visible(false);
+                if y != image_y {
+                    self.text.set_position(x, image_y);
+                    self.text.set_size(width, height + image_height as u32);
+                }
+            }
+        }
+        self.bitmap.replace(bitmap);
+    }
+}
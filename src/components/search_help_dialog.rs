@@ -0,0 +1,17 @@
+/// Lists every filter of the search input, generated from `SearchHelp`
+#[derive(Default)]
+pub struct SearchHelpDialog {
+    pub window: nwg::Window,
+    pub list: nwg::ListView,
+}
+
+impl SearchHelpDialog {
+    pub fn open(&self) {
+        self.window.set_visible(true);
+        self.list.set_focus();
+    }
+
+    pub fn close(&self) {
+        self.window.set_visible(false);
+    }
+}
@@ -1,244 +1,766 @@
-use crate::{app::BasicApp, memory::cache::Cache, search_engine::result::SearchEngineResult};
+use crate::{
+    app::BasicApp,
+    app_state::Action,
+    checksum::{self, ExpectedChecksum},
+    context_actions::{ActionJob, ActionOutput, ContextAction},
+    disk_usage::{self, UsageEvent, UsageJob, UsageNode, UsageSource},
+    duplicates::{self, DuplicateEvent, DuplicateJob, DuplicateOptions, DuplicateSets, FileSource},
+    export::ExportFormat,
+    file_operations::{self, AskFn, ConflictPolicy, DeleteMode, FileEvent, FileJob, Operation, OperationReport},
+    file_types::FileCategory,
+    formatting::Formatter,
+    hashing::{HashError, HashEvent, HashFunction, HashJob},
+    keybindings::KeyAction,
+    memory::cache::Cache,
+    search_engine::{parameter_parser::SearchEngineParameter, result::SearchEngineResult},
+    selection::Selection,
+    virtual_list::VirtualList,
+};
 use clipboard::{ClipboardContext, ClipboardProvider};
-use lhash::{Md5, Sha1, Sha256, Sha512};
-use std::{cell::Cell, fs, rc::Rc};
-use time::{format_description, OffsetDateTime};
-use winapi::um::winuser::{GetScrollPos, GetScrollRange, SB_VERT};
-use std::fmt::Write;
+use std::{cell::{Cell, RefCell}, collections::HashMap, mem, ops::RangeInclusive, path::Path, ptr, rc::Rc};
+use winapi::um::{
+    commctrl::{LVIS_SELECTED, LVITEMW, LVM_EDITLABELW, LVM_SETITEMCOUNT, LVM_SETITEMSTATE, LVSICF_NOINVALIDATEALL},
+    winuser::{InvalidateRect, SendMessageW, SetFocus},
+};
 
 #[derive(Default)]
 pub struct SearchResultControl {
     pub list: nwg::ListView,
+    /// Icon of every file category, see `FileCategory::icon_index`
+    pub icons: nwg::ImageList,
     pub context_menu: nwg::Menu,
     pub context_menu_items: SearchResultControlMenuItems,
     pub(super) context_menu_context_row: Cell<usize>,
+    /// Mirrors the selection of the list, every action on a selected row applies to all of them
+    selection: RefCell<Selection>,
+    /// The list is created with LVS_OWNERDATA, rows are pulled from here when they become visible
+    view: VirtualList<Vec<String>>,
+    /// Wakes up the ui thread whenever the hash job has news
+    pub hash_notice: nwg::Notice,
+    hash_job: RefCell<Option<HashJob>>,
+    hash_purpose: RefCell<HashPurpose>,
+    hash_results: RefCell<Vec<(String, Result<String, HashError>)>>,
+    pub duplicate_notice: nwg::Notice,
+    duplicate_job: RefCell<Option<DuplicateJob>>,
+    pub usage_notice: nwg::Notice,
+    usage_job: RefCell<Option<UsageJob>>,
+    pub file_notice: nwg::Notice,
+    file_job: RefCell<Option<FileJob>>,
+    pub action_notice: nwg::Notice,
+    action_job: RefCell<Option<ActionJob>>,
+}
+
+/// What happens with the hashes once the job is finished
+#[derive(Default)]
+enum HashPurpose {
+    #[default]
+    Copy,
+    Verify(Vec<ExpectedChecksum>),
+    /// Directory the sums file is written to
+    WriteSums(String, HashFunction),
+}
+
+pub enum HashReport {
+    Errors(Vec<String>),
+    /// One line per file, shown even if everything matched
+    Verification(Vec<String>),
 }
 #[derive(Default)]
 pub struct SearchResultControlMenuItems {
+    pub select_all: nwg::MenuItem,
+    pub invert_selection: nwg::MenuItem,
     pub add_to_favorites: nwg::MenuItem,
+    pub open_in_new_tab: nwg::MenuItem,
+    pub copy_to_other_pane: nwg::MenuItem,
+    pub move_to_other_pane: nwg::MenuItem,
     pub copy_path: nwg::MenuItem,
     pub copy_name: nwg::MenuItem,
-    pub md5_hash: nwg::MenuItem,
-    pub sha1_hash: nwg::MenuItem,
-    pub sha256_hash: nwg::MenuItem,
-    pub sha512_hash: nwg::MenuItem,
+    pub hash_menu: nwg::Menu,
+    pub hashes: Vec<(nwg::MenuItem, HashFunction)>,
+    pub verify_checksum: nwg::MenuItem,
+    pub verify_clipboard: nwg::MenuItem,
+    pub sums_menu: nwg::Menu,
+    pub write_sums: Vec<(nwg::MenuItem, HashFunction)>,
+    pub export_menu: nwg::Menu,
+    /// The flag is set for the items that only export the selection
+    pub exports: Vec<(nwg::MenuItem, ExportFormat, bool)>,
+    pub copy_to: nwg::MenuItem,
+    pub move_to: nwg::MenuItem,
+    pub rename: nwg::MenuItem,
+    pub bulk_rename: nwg::MenuItem,
+    pub new_folder: nwg::MenuItem,
+    pub trash: nwg::MenuItem,
+    pub delete: nwg::MenuItem,
+    /// Loaded from the json file of the user at startup
+    pub custom_actions: Vec<(nwg::MenuItem, ContextAction)>,
     pub seperator: Vec<nwg::MenuSeparator>
 }
 
-struct ListItemInsert {
-    ind: Option<i32>,
-    items: Vec<String>,
-}
-
-#[derive(Default, Clone, Debug, PartialEq)]
-pub enum SortDirection {
-    Asc,
-    Desc,
-    #[default]
-    None,
-}
+impl SearchResultControl {
+    /// Files in the selection are skipped
+    pub(super) fn execute_add_to_favorites(&self, app: Rc<BasicApp>, results: &[SearchEngineResult]) {
+        for result in results.iter().filter(|r| r.file_type.is_dir()) {
+            let ind = Some(app.fav_dir_bar.list.len() as i32);
+            let name = result.name.clone();
+            let path = result.full_path.clone();
+            nwg::ListView::insert_items_row(&app.fav_dir_bar.list, ind, &[name.clone(), path.clone()]);
+            app.cache
+                .settings
+                .borrow_mut()
+                .add_favorite_folder(name, path);
+        }
+    }
 
-pub enum HashFunction {
-    MD5,
-    SHA1,
-    SHA256,
-    SHA512,
-}
+    /// One path per line
+    pub(super) fn execute_copy_path(&self, results: &[SearchEngineResult]) {
+        let paths: Vec<&str> = results.iter().map(|r| r.full_path.as_str()).collect();
+        let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
+        ctx.set_contents(paths.join("\n")).unwrap();
+    }
 
-impl SearchResultControl {
-    pub(super) fn execute_add_to_favorites(&self, app: Rc<BasicApp>) {
-        let row = self.context_menu_context_row.get();
-        let path = self.list.item(row, 4, 260).expect("invalid menu row").text;
-        let ind = Some(app.fav_dir_bar.list.len() as i32);
-        let name = self.list.item(row, 0, 260).expect("invalid menu row").text;
-        nwg::ListView::insert_items_row(&app.fav_dir_bar.list, ind, &[name.clone(), path.clone()]);
-        app.cache
-            .settings
-            .borrow_mut()
-            .add_favorite_folder(name, path);
-    }
-
-    pub(super) fn execute_copy_path(&self) {
+    pub(super) fn execute_copy_name(&self, results: &[SearchEngineResult]) {
+        let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
         let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
-        ctx.set_contents(
-            self.list
-                .item(self.context_menu_context_row.get(), 1, 260)
-                .unwrap()
-                .text,
-        )
-        .unwrap();
+        ctx.set_contents(names.join("\n")).unwrap();
+    }
+
+    /// Hashes the files on a background thread, the result lands in the clipboard once all are done
+    pub(super) fn start_file_hash(&self, hf: HashFunction, paths: Vec<String>) -> Result<(), String> {
+        self.start_hash_job(paths.into_iter().map(|p| (p, hf)).collect(), HashPurpose::Copy)
+    }
+
+    /// Compares the files against the hashes of sums files next to them, e.g. `file.iso.sha256` or `SHA256SUMS`
+    pub(super) fn start_verify_sidecar(&self, paths: Vec<String>) -> Result<(), String> {
+        let (expected, missing): (Vec<_>, Vec<_>) = paths
+            .into_iter()
+            .map(|p| checksum::find_expected(&p).ok_or(p))
+            .partition(Result::is_ok);
+
+        if !missing.is_empty() {
+            let missing: Vec<String> = missing.into_iter().filter_map(Result::err).collect();
+            return Err(format!("No checksum file found for:\n{}", missing.join("\n")));
+        }
+        self.start_verify(expected.into_iter().filter_map(Result::ok).collect())
     }
 
-    pub(super) fn execute_copy_name(&self) {
+    /// Compares the files against the hash in the clipboard, the hash function is guessed by its length
+    pub(super) fn start_verify_clipboard(&self, paths: Vec<String>) -> Result<(), String> {
         let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
-        ctx.set_contents(
-            self.list
-                .item(self.context_menu_context_row.get(), 0, 260)
-                .unwrap()
-                .text,
+        let text = ctx.get_contents().unwrap_or_default();
+
+        let expected: Option<Vec<ExpectedChecksum>> = paths
+            .iter()
+            .map(|p| checksum::expected_from_text(p, &text))
+            .collect();
+        match expected {
+            Some(e) => self.start_verify(e),
+            None => Err(String::from("The clipboard does not contain a known hash")),
+        }
+    }
+
+    fn start_verify(&self, expected: Vec<ExpectedChecksum>) -> Result<(), String> {
+        let files = expected
+            .iter()
+            .map(|e| (e.path.clone(), e.hash_function))
+            .collect();
+        self.start_hash_job(files, HashPurpose::Verify(expected))
+    }
+
+    /// Writes a sums file like `SHA256SUMS` next to the files
+    pub(super) fn start_write_sums(&self, hf: HashFunction, paths: Vec<String>) -> Result<(), String> {
+        let dir = match paths.first().and_then(|p| Path::new(p).parent()) {
+            Some(d) => d.to_string_lossy().to_string(),
+            None => return Ok(()),
+        };
+        //Checked again when writing, this only saves the hashing
+        let target = Path::new(&dir).join(checksum::sums_file_name(hf));
+        if target.exists() {
+            return Err(format!("{} already exists", target.display()));
+        }
+        self.start_hash_job(
+            paths.into_iter().map(|p| (p, hf)).collect(),
+            HashPurpose::WriteSums(dir, hf),
         )
-        .unwrap();
-    }
-
-    pub(super) fn copy_file_hash(&self, hf: HashFunction) {
-        let path = self
-            .list
-            .item(self.context_menu_context_row.get(), 4, 260)
-            .unwrap()
-            .text;
-        let file = fs::read(path).unwrap();
-
-        let hash: Vec<u8> = match hf {
-            HashFunction::MD5 => {
-                let mut alg = Md5::new();
-                alg.update(&file);
-                alg.result().to_vec()
+    }
+
+    fn start_hash_job(&self, files: Vec<(String, HashFunction)>, purpose: HashPurpose) -> Result<(), String> {
+        if self.hash_job.borrow().is_some() {
+            return Err(String::from("Still busy hashing other files"));
+        }
+
+        let sender = self.hash_notice.sender();
+        self.hash_results.borrow_mut().clear();
+        self.hash_purpose.replace(purpose);
+        self.hash_job
+            .replace(Some(HashJob::spawn_each(files, move || sender.notice())));
+
+        Ok(())
+    }
+
+    /// Called on every notice of the hash job. Returns the progress text for the status bar
+    /// and once the job is finished the report to show.
+    pub fn poll_hash_job(&self) -> (String, Option<HashReport>) {
+        let events = match self.hash_job.borrow().as_ref() {
+            Some(job) => job.poll(),
+            None => return (String::new(), None),
+        };
+
+        let mut progress = String::new();
+        let mut finished = false;
+        for event in events {
+            match event {
+                HashEvent::Progress { done, total, .. } => {
+                    progress = format!("Hashing {}%", done * 100 / total.max(1));
+                }
+                HashEvent::File(path, result) => self.hash_results.borrow_mut().push((path, result)),
+                HashEvent::Finished => finished = true,
             }
-            HashFunction::SHA1 => {
-                let mut alg = Sha1::new();
-                alg.update(&file);
-                alg.result().to_vec()
+        }
+
+        if !finished {
+            return (progress, None);
+        }
+
+        self.hash_job.replace(None);
+        let results = self.hash_results.take();
+        let errors: Vec<String> = results
+            .iter()
+            .filter_map(|(_, r)| r.as_ref().err().map(|e| e.to_string()))
+            .collect();
+
+        match self.hash_purpose.take() {
+            HashPurpose::Copy => {
+                Self::copy_hashes(&results);
+                (String::from("Hash copied"), Some(HashReport::Errors(errors)))
             }
-            HashFunction::SHA256 => {
-                let mut alg = Sha256::new();
-                alg.update(&file);
-                alg.result().to_vec()
+            HashPurpose::Verify(expected) => {
+                let verified: Vec<(&String, checksum::Verification)> = expected
+                    .iter()
+                    .zip(results.iter())
+                    .map(|(e, (path, r))| (path, checksum::compare(e, r)))
+                    .collect();
+                let failed = verified
+                    .iter()
+                    .filter(|(_, v)| *v != checksum::Verification::Match)
+                    .count();
+                let lines = verified
+                    .iter()
+                    .map(|(path, v)| format!("{}: {}", path, v))
+                    .collect();
+                let status = match failed {
+                    0 => String::from("Checksums match"),
+                    n => format!("{} checksums dont match", n),
+                };
+                (status, Some(HashReport::Verification(lines)))
             }
-            HashFunction::SHA512 => {
-                let mut alg = Sha512::new();
-                alg.update(&file);
-                alg.result().to_vec()
+            HashPurpose::WriteSums(dir, hf) => {
+                let hashes: Vec<(String, String)> = results
+                    .into_iter()
+                    .filter_map(|(path, r)| r.ok().map(|h| (path, h)))
+                    .collect();
+                match checksum::write_sums(&dir, hf, &hashes) {
+                    Ok(target) => (format!("Written {}", target), Some(HashReport::Errors(errors))),
+                    Err(e) => (String::new(), Some(HashReport::Errors(vec![e]))),
+                }
             }
+        }
+    }
+
+    /// A single hash is copied as is, multiple ones in the format of sha256sum and co.
+    fn copy_hashes(results: &[(String, Result<String, HashError>)]) {
+        let text = match results {
+            [(_, Ok(hash))] => hash.clone(),
+            _ => results
+                .iter()
+                .filter_map(|(path, r)| r.as_ref().ok().map(|h| format!("{}  {}", h, path)))
+                .collect::<Vec<String>>()
+                .join("\r\n"),
         };
+        if !text.is_empty() {
+            let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
+            ctx.set_contents(text).unwrap();
+        }
+    }
 
-        let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
-        ctx.set_contents(hash.iter().fold(String::new(), |mut acc, &byte| {
-            write!(acc, "{:02X}", byte).expect("Failed to write to String");
-            acc
-        }))
-        .unwrap();
+    /// Searches for duplicate files on a background thread, the sets replace the results once done
+    pub fn start_duplicate_search(&self, source: FileSource) -> Result<(), String> {
+        if self.duplicate_job.borrow().is_some() {
+            return Err(String::from("Already searching for duplicates"));
+        }
+
+        let sender = self.duplicate_notice.sender();
+        self.duplicate_job.replace(Some(DuplicateJob::spawn(
+            source,
+            DuplicateOptions::default(),
+            move || sender.notice(),
+        )));
+
+        Ok(())
     }
 
-    //TODO: sorts and REFRESHES the column, maybe optimize later
-    pub fn sort_by_column(
+    /// Returns false if there was nothing to cancel
+    pub fn cancel_duplicate_search(&self) -> bool {
+        match self.duplicate_job.borrow().as_ref() {
+            Some(job) => {
+                job.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Called on every notice of the duplicate job, returns the text for the status bar
+    pub fn poll_duplicate_job(&self, cache: &Cache) -> String {
+        let events = match self.duplicate_job.borrow().as_ref() {
+            Some(job) => job.poll(),
+            None => return String::new(),
+        };
+
+        let mut status = String::new();
+        for event in events {
+            match event {
+                DuplicateEvent::Progress { stage, done, total } => {
+                    status = format!("{} {}/{}", stage, done, total);
+                }
+                DuplicateEvent::Finished(Ok(groups)) => {
+                    self.duplicate_job.replace(None);
+                    let results: Vec<SearchEngineResult> = groups
+                        .iter()
+                        .flat_map(|g| g.files.iter())
+                        .filter_map(|f| SearchEngineResult::from_path(f).ok())
+                        .collect();
+                    //The gui renders the state after the notice
+                    cache.tabs.borrow_mut().active_mut().dispatch(Action::ShowResults(results));
+
+                    status = format!(
+                        "{} duplicate sets, {} wasted",
+                        groups.len(),
+                        cache.formatter.borrow().size(duplicates::total_wasted(&groups))
+                    );
+                    cache.duplicates.replace(DuplicateSets::new(groups));
+                }
+                DuplicateEvent::Finished(Err(e)) => {
+                    self.duplicate_job.replace(None);
+                    status = e.to_string();
+                }
+            }
+        }
+
+        status
+    }
+
+    /// Shown in the type column while duplicates are listed
+    fn duplicate_label(&self, cache: &Cache, row: usize) -> Option<String> {
+        let sets = cache.duplicates.try_borrow().ok()?;
+        let tabs = cache.tabs.try_borrow().ok()?;
+        let state = tabs.active();
+        let path = &state.results.get(row)?.full_path;
+
+        let (i, group) = sets.find(path)?;
+        let wasted = cache.formatter.try_borrow().ok()?.size(group.wasted());
+        Some(format!("Set {}, {} wasted", i + 1, wasted))
+    }
+
+    /// Children of the directory with their recursive sizes, largest first. Without a cached tree the
+    /// sizes are calculated in the background, from the index if `use_index` is set, and nothing is returned for now.
+    pub fn show_disk_usage(
         &self,
-        sort_dir: &mut (usize, SortDirection),
-        col_index: usize,
-        mut results: Vec<SearchEngineResult>,
-    ) {
-        if sort_dir.0 != col_index && col_index != 4 {
-            //Not supported by type
-            sort_dir.0 = col_index;
-            sort_dir.1 = SortDirection::Asc;
-        }
-
-        match col_index {
-            1 => match sort_dir.1 {
-                SortDirection::Desc => results.sort_by(|a, b| b.modified.cmp(&a.modified)),
-                _ => results.sort_by(|a, b| a.modified.cmp(&b.modified)),
-            },
-            3 => match sort_dir.1 {
-                SortDirection::Desc => results.sort_by(|a, b| b.size.cmp(&a.size)),
-                _ => results.sort_by(|a, b| a.size.cmp(&b.size)),
-            },
-            0 => match sort_dir.1 {
-                SortDirection::Desc => {
-                    results.sort_by(|a, b| b.name.to_lowercase().cmp(&a.name.to_lowercase()))
+        cache: &Cache,
+        path: &str,
+        skip: SearchEngineParameter,
+        use_index: bool,
+    ) -> Vec<SearchEngineResult> {
+        if let Some(node) = cache.disk_usage.borrow().get(path) {
+            cache.usage_total.set(Some(node.size));
+            return Self::usage_results(node);
+        }
+
+        cache.usage_total.set(None);
+        //Only the part of the index is copied here, its sizes are looked up by the job
+        let subtree = match use_index {
+            true => disk_usage::index_subtree(&cache.index.borrow(), path),
+            false => None,
+        };
+        let source = match subtree {
+            Some((node, node_path)) => UsageSource::Index(node, node_path),
+            None => UsageSource::Disk(path.into()),
+        };
+        let sender = self.usage_notice.sender();
+        //Replacing the job drops and with that cancels the one of the previous directory
+        self.usage_job.replace(Some(UsageJob::spawn(source, skip, move || sender.notice())));
+        Vec::new()
+    }
+
+    fn usage_results(node: &UsageNode) -> Vec<SearchEngineResult> {
+        node.children
+            .iter()
+            .filter_map(|c| {
+                let mut result = SearchEngineResult::from_path(&c.path).ok()?;
+                result.size = c.size;
+                Some(result)
+            })
+            .collect()
+    }
+
+    /// Called on every notice of the disk usage job. Returns the text for the status bar and
+    /// true once the sizes are cached and the listing can be shown.
+    pub fn poll_usage_job(&self, cache: &Cache) -> (String, bool) {
+        let events = match self.usage_job.borrow().as_ref() {
+            Some(job) => job.poll(),
+            None => return (String::new(), false),
+        };
+
+        let mut status = String::new();
+        for event in events {
+            match event {
+                UsageEvent::Progress { scanned_dirs } => {
+                    status = format!("Calculating sizes, {} directories", scanned_dirs);
+                }
+                UsageEvent::Finished(result, failures) => {
+                    self.usage_job.replace(None);
+                    for failure in &failures {
+                        crate::warn!("{}", failure);
+                    }
+                    return match result {
+                        Ok(tree) => {
+                            let status = match failures.len() {
+                                0 => format!("{} files", tree.file_count),
+                                n => format!("{} files, {} could not be read", tree.file_count, n),
+                            };
+                            cache.disk_usage.borrow_mut().insert(tree);
+                            (status, true)
+                        }
+                        Err(e) => (e, false),
+                    };
+                }
+            }
+        }
+
+        (status, false)
+    }
+
+    /// Share of the parent directory, shown in the type column while the disk usage is listed
+    fn usage_label(&self, cache: &Cache, row: usize) -> Option<String> {
+        let total = cache.usage_total.get()?;
+        let tabs = cache.tabs.try_borrow().ok()?;
+        let state = tabs.active();
+        let result = state.results.get(row)?;
+
+        let percent = match total {
+            0 => 0.0,
+            t => result.size as f64 * 100.0 / t as f64,
+        };
+        match result.file_type.is_dir() {
+            true => {
+                let usage = cache.disk_usage.try_borrow().ok()?;
+                let files = usage.get(&result.full_path).map(|n| n.file_count).unwrap_or(0);
+                Some(format!("{:.1}%, {} files", percent, files))
+            }
+            false => Some(format!("{:.1}%", percent)),
+        }
+    }
+
+    /// Copies, moves or deletes in the background. Conflicts are asked about before anything starts,
+    /// unless the settings have a fixed policy.
+    pub(super) fn start_file_operation(&self, app: &BasicApp, operation: Operation) -> Result<(), String> {
+        if self.file_job.borrow().is_some() {
+            return Err(String::from("Still busy with another file operation"));
+        }
+
+        let policy = app.cache.settings.borrow().conflict_policy;
+        let mut decisions = HashMap::new();
+        if let Operation::Copy { sources, target_dir } | Operation::Move { sources, target_dir } = &operation {
+            if policy == ConflictPolicy::Ask {
+                for conflict in file_operations::conflicts(sources, target_dir) {
+                    decisions.insert(conflict.source.clone(), app.ask_conflict(&conflict));
+                }
+            }
+        }
+
+        let sender = self.file_notice.sender();
+        self.file_job.replace(Some(FileJob::spawn(operation, policy, decisions, move || {
+            sender.notice()
+        })));
+
+        Ok(())
+    }
+
+    /// Deleting for good asks first, trashing can be undone
+    pub(super) fn delete_paths(&self, app: &BasicApp, paths: Vec<String>, mode: DeleteMode) {
+        if paths.is_empty() {
+            return;
+        }
+        if mode == DeleteMode::Permanent {
+            let ans = nwg::modal_message(&app.window, &nwg::MessageParams {
+                title: "Delete",
+                content: &format!("Delete {} items for good? This cant be undone.", paths.len()),
+                buttons: nwg::MessageButtons::YesNo,
+                icons: nwg::MessageIcons::Warning,
+            });
+            if ans != nwg::MessageChoice::Yes {
+                return;
+            }
+        }
+        if let Err(e) = self.start_file_operation(app, Operation::Delete { paths, mode }) {
+            app.display_error(e);
+        }
+    }
+
+    /// Runs the shortcuts that work on the selection, false for the ones the list doesnt handle
+    pub fn execute_key_action(&self, action: KeyAction, app: &BasicApp) -> bool {
+        let rows = self.selection.borrow().rows();
+        let selected: Vec<SearchEngineResult> = rows.iter().filter_map(|r| self.result_at(&app.cache, *r)).collect();
+        match action {
+            KeyAction::SelectAll => self.select_all(),
+            KeyAction::InvertSelection => self.invert_selection(),
+            KeyAction::Rename => {
+                if let Some(row) = rows.first() {
+                    self.begin_rename(*row);
+                }
+            }
+            KeyAction::CopyPath => {
+                if !selected.is_empty() {
+                    self.execute_copy_path(&selected);
                 }
-                _ => results.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase())),
-            },
-            _ => return, //Sorting by type is not supported
+            }
+            KeyAction::Trash | KeyAction::Delete => {
+                let mode = match action {
+                    KeyAction::Trash => DeleteMode::Trash,
+                    _ => DeleteMode::Permanent,
+                };
+                self.delete_paths(app, selected.into_iter().map(|r| r.full_path).collect(), mode);
+            }
+            _ => return false,
         }
+        true
+    }
 
-        match sort_dir.1 {
-            SortDirection::Desc => sort_dir.1 = SortDirection::Asc,
-            _ => sort_dir.1 = SortDirection::Desc,
+    /// Called on every notice of the file job. Returns the progress text for the status bar
+    /// and the report once the job is finished.
+    pub fn poll_file_job(&self) -> (String, Option<OperationReport>) {
+        let events = match self.file_job.borrow().as_ref() {
+            Some(job) => job.poll(),
+            None => return (String::new(), None),
+        };
+
+        let mut progress = String::new();
+        for event in events {
+            match event {
+                FileEvent::Progress(p) => {
+                    progress = format!("{}/{} items, {}%", p.item + 1, p.items, p.done * 100 / p.total.max(1));
+                }
+                FileEvent::Finished(report) => {
+                    self.file_job.replace(None);
+                    return (report.to_string(), Some(report));
+                }
+            }
         }
 
-        //TODO: OPTIMIZE to no cloning
-        self.refresh(results.clone());
+        (progress, None)
     }
 
-    pub fn refresh(&self, results: Vec<SearchEngineResult>) {
-        self.list.clear();
-        //TODO: magic number
-        let items = Self::prep_data(results, 0, 50);
-        for item in items {
-            nwg::ListView::insert_items_row(&self.list, item.ind, item.items.as_slice());
+    /// Runs the programs of a custom action in the background, only one action at a time
+    pub(super) fn start_context_action(&self, action: &ContextAction, selection: &[SearchEngineResult]) -> Result<(), String> {
+        if self.action_job.borrow().is_some() {
+            return Err(String::from("Still waiting for the last action to finish"));
+        }
+        if action.prepare(selection).is_empty() {
+            return Err(format!("'{}' doesnt apply to the selection", action.label));
         }
+
+        let sender = self.action_notice.sender();
+        self.action_job.replace(Some(ActionJob::spawn(action, selection, move || sender.notice())));
+        Ok(())
+    }
+
+    /// Label of the action and what its commands printed, once they are all done
+    pub fn poll_action_job(&self) -> Option<(String, Vec<ActionOutput>)> {
+        let outputs = self.action_job.borrow().as_ref()?.poll()?;
+        let job = self.action_job.take()?;
+        Some((job.label, outputs))
     }
 
-    pub fn add_page(&self, cache: &Cache) {
-        let hnd = self.list.handle.hwnd().unwrap();
-        let mut max_pos = 0;
-        let mut min_pos = 0;
+    /// Lets the user edit the name in the list, the rename itself happens in `rename_row`
+    pub(super) fn begin_rename(&self, row: usize) {
+        let hwnd = match self.list.handle.hwnd() {
+            Some(h) => h,
+            None => return,
+        };
         unsafe {
-            GetScrollRange(hnd, SB_VERT as i32, &mut min_pos, &mut max_pos);
+            SetFocus(hwnd);
+            SendMessageW(hwnd, LVM_EDITLABELW, row, 0);
         }
-        let vertical_scroll_pos = unsafe { GetScrollPos(hnd, SB_VERT as i32) };
-        if (vertical_scroll_pos - max_pos).abs() != 25 {
-            //25 strangely appeared to be the offset to the bottom, maybe because of the style?
-            return;
+    }
+
+    pub fn rename_row(&self, cache: &Cache, row: usize, new_name: &str, ask: &mut AskFn) -> Option<OperationReport> {
+        let result = self.result_at(cache, row)?;
+        if result.name == new_name {
+            return None;
         }
-        let curr_res = cache.current_results.borrow_mut();
-        let len = self.list.len();
-        if len == curr_res.len() {
-            return;
+
+        let policy = cache.settings.borrow().conflict_policy;
+        Some(file_operations::rename(&result.full_path, new_name, policy, ask))
+    }
+
+    /// Row of the path in the current results
+    pub fn row_of(&self, cache: &Cache, path: &str) -> Option<usize> {
+        cache
+            .tabs
+            .borrow()
+            .active()
+            .results
+            .iter()
+            .position(|r| r.full_path.eq_ignore_ascii_case(path))
+    }
+
+    /// Paths of the selected rows if the row is part of the selection, otherwise only the row itself
+    pub(super) fn paths_for_row(&self, cache: &Cache, row: usize) -> Vec<String> {
+        self.results_for_row(cache, row)
+            .into_iter()
+            .map(|r| r.full_path)
+            .collect()
+    }
+
+    /// The selection if `row` is part of it, otherwise only `row`
+    pub(super) fn results_for_row(&self, cache: &Cache, row: usize) -> Vec<SearchEngineResult> {
+        let rows = self.selection.borrow().rows_for(row);
+        rows.into_iter()
+            .filter_map(|r| self.result_at(cache, r))
+            .collect()
+    }
+
+    pub fn selected_count(&self) -> usize {
+        self.selection.borrow().len()
+    }
+
+    /// Called from the change notifications of the list, `None` means every row
+    pub fn selection_changed(&self, rows: Option<RangeInclusive<usize>>, selected: bool) {
+        if let Ok(mut selection) = self.selection.try_borrow_mut() {
+            selection.set_range(rows.unwrap_or(0..=usize::MAX), selected);
         }
+    }
 
-        //TODO: remove/work around clone!
-        let e = curr_res.clone();
-        let prep = Self::prep_data(e, len, 50);
-
-        for (ind, res) in prep.iter().enumerate() {
-            nwg::ListView::insert_items_row(
-                &self.list,
-                Some((ind + len - 1) as i32),
-                res.items.as_slice(),
-            );
-        }
-    }
-
-    fn prep_data(
-        results: Vec<SearchEngineResult>,
-        skip: usize,
-        take: usize,
-    ) -> Vec<ListItemInsert> {
-        let mut items: Vec<ListItemInsert> = Vec::new();
-        for (ind, f) in results.iter().skip(skip).take(take).enumerate() {
-            let chrono_time: OffsetDateTime = f.modified.into();
-
-            let time = chrono_time
-                .format(&format_description::parse("[year]-[month]-[day] [hour]:[minute]").unwrap())
-                .unwrap()
-                .to_string();
-
-            let file_type_str = match f.file_type {
-                t if t.is_dir() => "Directory",
-                t if t.is_file() => "File",
-                t if t.is_symlink() => "Symlink",
-                _ => "Unknown",
-            };
-
-            let size = match f.file_type {
-                t if t.is_dir() => "".into(),
-                t if t.is_file() => format!("{} KiB", std::cmp::max(f.size / 1000, 1)),
-                t if t.is_symlink() => format!("{} KiB", std::cmp::max(f.size / 1000, 1)),
-                _ => "".into(),
-            };
-
-            items.push(ListItemInsert {
-                ind: Some(ind.try_into().unwrap()),
-                items: vec![
-                    f.name.clone(),
-                    time,
-                    file_type_str.into(),
-                    size,
-                    f.full_path.clone(),
-                ],
-            });
+    pub fn select_all(&self) {
+        self.set_row_selected(None, true);
+    }
+
+    pub fn invert_selection(&self) {
+        let rows = {
+            let mut selection = self.selection.borrow_mut();
+            selection.invert();
+            selection.rows()
+        };
+        //The list reports every change back, which leaves the model where it already is
+        self.set_row_selected(None, false);
+        for row in rows {
+            self.set_row_selected(Some(row), true);
+        }
+    }
+
+    /// Selects only the given rows, e.g. the ones that differ from the other pane
+    pub fn select_rows(&self, rows: &[usize]) {
+        self.set_row_selected(None, false);
+        for row in rows {
+            self.set_row_selected(Some(*row), true);
         }
+    }
+
+    fn set_row_selected(&self, row: Option<usize>, selected: bool) {
+        set_row_selected(&self.list, row, selected);
+    }
+
+    pub fn refresh(&self, results: &[SearchEngineResult]) {
+        self.view.reset(results.len());
+        //Old selected rows would point to other results now
+        self.set_row_selected(None, false);
+        self.selection.borrow_mut().reset(results.len());
+        set_row_count(&self.list, results.len());
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.view.len()
+    }
+
+    /// The result if exactly one row is selected
+    pub fn single_selected(&self, cache: &Cache) -> Option<SearchEngineResult> {
+        let rows = self.selection.try_borrow().ok()?.rows();
+        match rows.as_slice() {
+            [row] => self.result_at(cache, *row),
+            _ => None,
+        }
+    }
+
+    /// Decides the icon of the row
+    pub fn category_at(&self, cache: &Cache, row: usize) -> Option<FileCategory> {
+        cache.tabs.try_borrow().ok()?.active().results.get(row).map(|r| r.category)
+    }
+
+    /// The result shown in the given row, rows are in the same order as the cached results
+    pub fn result_at(&self, cache: &Cache, row: usize) -> Option<SearchEngineResult> {
+        cache.tabs.try_borrow().ok()?.active().results.get(row).cloned()
+    }
+
+    /// Text of a single cell, called by the list whenever a row becomes visible
+    pub fn cell_text(&self, cache: &Cache, row: usize, col: usize) -> Option<String> {
+        if col == 2 {
+            if let Some(label) = self.duplicate_label(cache, row).or_else(|| self.usage_label(cache, row)) {
+                return Some(label);
+            }
+        }
+        let tabs = cache.tabs.try_borrow().ok()?;
+        let state = tabs.active();
+        let formatter = cache.formatter.try_borrow().ok()?;
+        let formatted = self.view.row(&state.results, row, |r| Self::format_row(r, &formatter))?;
+        formatted.get(col).cloned()
+    }
+
+    /// Formats the rows ahead of time, the list sends a hint before it asks for a range
+    pub fn prefetch(&self, cache: &Cache, from: usize, to: usize) {
+        if let (Ok(tabs), Ok(formatter)) = (cache.tabs.try_borrow(), cache.formatter.try_borrow()) {
+            self.view.rows(&tabs.active().results, from..to + 1, |r| Self::format_row(r, &formatter));
+        }
+    }
+
+    fn format_row(f: &SearchEngineResult, formatter: &Formatter) -> Vec<String> {
+        //Directories only have a size while the disk usage is shown
+        let size = match f.file_type {
+            t if t.is_dir() && f.size == 0 => "".into(),
+            _ => formatter.size(f.size),
+        };
+
+        vec![
+            f.name.clone(),
+            formatter.date(f.modified),
+            f.category.label().into(),
+            size,
+            f.full_path.clone(),
+        ]
+    }
+}
+
+/// Selection of a virtual list, `None` changes every row
+pub(super) fn set_row_selected(list: &nwg::ListView, row: Option<usize>, selected: bool) {
+    let hwnd = match list.handle.hwnd() {
+        Some(h) => h,
+        None => return,
+    };
+    unsafe {
+        let mut item: LVITEMW = mem::zeroed();
+        item.stateMask = LVIS_SELECTED;
+        item.state = if selected { LVIS_SELECTED } else { 0 };
+        //-1 is every row
+        SendMessageW(hwnd, LVM_SETITEMSTATE, row.unwrap_or(usize::MAX), &item as *const _ as isize);
+    }
+}
 
-        items
+/// Tells a virtual list how many rows it has and makes it ask for the visible ones again
+pub(super) fn set_row_count(list: &nwg::ListView, len: usize) {
+    let hwnd = match list.handle.hwnd() {
+        Some(h) => h,
+        None => return,
+    };
+    unsafe {
+        SendMessageW(hwnd, LVM_SETITEMCOUNT, len, LVSICF_NOINVALIDATEALL as isize);
+        InvalidateRect(hwnd, ptr::null(), 1);
     }
 }
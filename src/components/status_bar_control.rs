@@ -4,5 +4,10 @@ pub struct StatusBarControl {
     pub search_duration: nwg::Label,
     pub index_date: nwg::Label,
     pub index_usage: nwg::CheckBox,
-    pub index_refresh: nwg::Button
+    pub index_refresh: nwg::Button,
+    pub show_hidden: nwg::CheckBox,
+    pub task_progress: nwg::Label,
+    pub find_duplicates: nwg::Button,
+    pub export_duplicates: nwg::Button,
+    pub disk_usage: nwg::CheckBox,
 }
\ No newline at end of file
@@ -0,0 +1,82 @@
+use std::cell::{Cell, RefCell};
+
+use nwg::{CheckBoxFlags, CheckBoxState};
+
+pub(super) const TAB_BAR_X: i32 = 120;
+pub(super) const TAB_BAR_Y: i32 = 46;
+const TAB_BAR_WIDTH: i32 = 1070;
+pub(super) const TAB_HEIGHT: i32 = 22;
+const MAX_TAB_WIDTH: i32 = 180;
+const CLOSE_WIDTH: i32 = 20;
+pub(super) const NEW_TAB_WIDTH: i32 = 24;
+
+#[derive(Default)]
+pub struct TabBarControl {
+    pub new_tab_btn: nwg::Button,
+    /// A push like check box per tab, only the active one is checked, and its close button
+    tabs: RefCell<Vec<(nwg::CheckBox, nwg::Button)>>,
+    titles: RefCell<Vec<String>>,
+    /// Narrower while the right pane is shown
+    width: Cell<Option<i32>>,
+}
+
+impl TabBarControl {
+    /// The tabs are rebuilt to fit with the next render
+    pub fn set_width(&self, width: i32) {
+        self.width.set(Some(width));
+        self.titles.borrow_mut().clear();
+    }
+
+    /// Rebuilds the tabs if the titles changed since the last call
+    pub fn render(&self, parent: &nwg::Window, titles: &[String], active: usize) -> Result<(), nwg::NwgError> {
+        if titles != self.titles.borrow().as_slice() {
+            let mut tabs = self.tabs.borrow_mut();
+            tabs.clear();
+
+            //Tabs get narrower once they dont fit anymore
+            let bar_width = self.width.get().unwrap_or(TAB_BAR_WIDTH);
+            let width = ((bar_width - NEW_TAB_WIDTH) / titles.len().max(1) as i32).min(MAX_TAB_WIDTH);
+            let mut x = TAB_BAR_X;
+            for title in titles {
+                let mut tab = nwg::CheckBox::default();
+                nwg::CheckBox::builder()
+                    .parent(parent)
+                    .text(title)
+                    .flags(CheckBoxFlags::VISIBLE | CheckBoxFlags::PUSHLIKE)
+                    .position((x, TAB_BAR_Y))
+                    .size((width - CLOSE_WIDTH, TAB_HEIGHT))
+                    .build(&mut tab)?;
+                let mut close = nwg::Button::default();
+                nwg::Button::builder()
+                    .parent(parent)
+                    .text("x")
+                    .position((x + width - CLOSE_WIDTH, TAB_BAR_Y))
+                    .size((CLOSE_WIDTH, TAB_HEIGHT))
+                    .build(&mut close)?;
+                tabs.push((tab, close));
+                x += width;
+            }
+            self.new_tab_btn.set_position(x, TAB_BAR_Y);
+            self.titles.replace(titles.to_vec());
+        }
+
+        //Clicks toggle the check boxes themselves, so the states are set every time
+        for (i, (tab, _)) in self.tabs.borrow().iter().enumerate() {
+            tab.set_check_state(match i == active {
+                true => CheckBoxState::Checked,
+                false => CheckBoxState::Unchecked,
+            });
+        }
+        Ok(())
+    }
+
+    /// Index of the tab if the handle was one
+    pub fn tab_of(&self, handle: &nwg::ControlHandle) -> Option<usize> {
+        self.tabs.borrow().iter().position(|(tab, _)| tab.handle.eq(handle))
+    }
+
+    /// Index of the tab if the handle was its close button
+    pub fn close_of(&self, handle: &nwg::ControlHandle) -> Option<usize> {
+        self.tabs.borrow().iter().position(|(_, close)| close.handle.eq(handle))
+    }
+}
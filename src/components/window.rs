@@ -48,5 +48,23 @@ pub fn load(data: &mut BasicApp) -> Result<(), NwgError> {
         .title("Search for a directory to add")
         .build(&mut data.file_dialog)?;
 
+    nwg::FileDialog::builder()
+        .action(nwg::FileDialogAction::Save)
+        .filters("CSV(*.csv)|Any (*.*)")
+        .title("Export duplicates")
+        .build(&mut data.export_dialog)?;
+
+    nwg::FileDialog::builder()
+        .action(nwg::FileDialogAction::Save)
+        .filters("CSV(*.csv)|JSON Lines(*.jsonl)|Text(*.txt)|Any (*.*)")
+        .title("Export results")
+        .build(&mut data.result_export_dialog)?;
+
+    nwg::FileDialog::builder()
+        .action(nwg::FileDialogAction::OpenDirectory)
+        .multiselect(false)
+        .title("Choose the target folder")
+        .build(&mut data.target_dialog)?;
+
     Ok(())
 }
@@ -0,0 +1,248 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{job::Job, logging, search_engine::result::SearchEngineResult};
+
+pub const CONTEXT_ACTIONS_FILE: &str = "context_actions.json";
+
+/// Where the gui looks for the actions, in the data directory next to the settings
+pub fn context_actions_path() -> PathBuf {
+    logging::data_dir().join(CONTEXT_ACTIONS_FILE)
+}
+/// Placeholders that make the action run once for every file
+const PER_FILE_PLACEHOLDERS: [&str; 3] = ["{path}", "{name}", "{dir}"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AppliesTo {
+    #[default]
+    All,
+    Files,
+    Directories,
+}
+
+/// Entry of the context menu that runs a program, e.g.
+/// `{ "label": "Git log", "applies_to": "directories", "program": "git", "args": ["log", "-5"], "working_dir": "{path}", "capture_output": true }`
+///
+/// `{path}`, `{name}` and `{dir}` run the program once for every selected item, `{dir}` is the parent folder.
+/// `{selection}` stands for all selected paths, as an argument of its own every path becomes a separate argument.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextAction {
+    pub label: String,
+    #[serde(default)]
+    pub applies_to: AppliesTo,
+    /// e.g. `jpg` or `*.tar.gz`, empty allows every extension. Directories dont have one.
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub working_dir: Option<String>,
+    /// Shows what the program printed once it is done, otherwise it runs on its own
+    #[serde(default)]
+    pub capture_output: bool,
+}
+
+/// A single program start with every placeholder filled in
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    pub working_dir: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionOutput {
+    pub command: PreparedCommand,
+    /// Exit code, `None` if it couldnt be started or wasnt waited for
+    pub status: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ContextAction {
+    pub fn applies_to(&self, result: &SearchEngineResult) -> bool {
+        let is_dir = result.file_type.is_dir();
+        let kind_matches = match self.applies_to {
+            AppliesTo::All => true,
+            AppliesTo::Files => !is_dir,
+            AppliesTo::Directories => is_dir,
+        };
+        if !kind_matches || self.extensions.is_empty() {
+            return kind_matches;
+        }
+
+        let name = result.name.to_lowercase();
+        !is_dir
+            && self.extensions.iter().any(|e| {
+                let e = e.trim_start_matches('*').trim_start_matches('.').to_lowercase();
+                name.ends_with(&format!(".{}", e))
+            })
+    }
+
+    fn runs_per_file(&self) -> bool {
+        self.args
+            .iter()
+            .chain(self.working_dir.iter())
+            .chain(std::iter::once(&self.program))
+            .any(|a| PER_FILE_PLACEHOLDERS.iter().any(|p| a.contains(p)))
+    }
+
+    /// The programs to start for `selection`, items the action doesnt apply to are left out
+    pub fn prepare(&self, selection: &[SearchEngineResult]) -> Vec<PreparedCommand> {
+        let targets: Vec<&SearchEngineResult> = selection.iter().filter(|r| self.applies_to(r)).collect();
+        if targets.is_empty() {
+            return Vec::new();
+        }
+        let paths: Vec<&str> = targets.iter().map(|r| r.full_path.as_str()).collect();
+
+        match self.runs_per_file() {
+            true => targets.iter().map(|t| self.fill(Some(t), &paths)).collect(),
+            false => vec![self.fill(None, &paths)],
+        }
+    }
+
+    fn fill(&self, target: Option<&SearchEngineResult>, selection: &[&str]) -> PreparedCommand {
+        let (path, name, dir) = match target {
+            Some(t) => (
+                t.full_path.clone(),
+                t.name.clone(),
+                Path::new(&t.full_path)
+                    .parent()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            ),
+            None => Default::default(),
+        };
+        let replace = |s: &str| {
+            s.replace("{path}", &path)
+                .replace("{name}", &name)
+                .replace("{dir}", &dir)
+                .replace("{selection}", &selection.join(" "))
+        };
+
+        let args = self
+            .args
+            .iter()
+            .flat_map(|a| match a.as_str() {
+                "{selection}" => selection.iter().map(|s| s.to_string()).collect(),
+                _ => vec![replace(a)],
+            })
+            .collect();
+
+        PreparedCommand {
+            program: replace(&self.program),
+            args,
+            working_dir: self.working_dir.as_deref().map(replace),
+        }
+    }
+}
+
+/// Reads the actions of the user, a missing file means there are none
+pub fn load(path: &str) -> Result<Vec<ContextAction>, String> {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(_) => return Ok(Vec::new()),
+    };
+    serde_json::from_reader(BufReader::new(file)).map_err(|e| format!("Invalid context actions in {}: {}", path, e))
+}
+
+/// Starts the command, only waits for it if the output is wanted
+pub fn run(command: PreparedCommand, capture_output: bool) -> ActionOutput {
+    let mut process = Command::new(&command.program);
+    process.args(&command.args);
+    if let Some(dir) = &command.working_dir {
+        process.current_dir(dir);
+    }
+
+    let result = match capture_output {
+        true => process.stdin(Stdio::null()).output().map(|o| {
+            (
+                o.status.code(),
+                String::from_utf8_lossy(&o.stdout).to_string(),
+                String::from_utf8_lossy(&o.stderr).to_string(),
+            )
+        }),
+        false => process.spawn().map(|_| (None, String::new(), String::new())),
+    };
+
+    match result {
+        Ok((status, stdout, stderr)) => ActionOutput {
+            command,
+            status,
+            stdout,
+            stderr,
+        },
+        Err(e) => ActionOutput {
+            stderr: format!("Failed to start {}: {}", command.program, e),
+            command,
+            status: None,
+            stdout: String::new(),
+        },
+    }
+}
+
+/// Runs the commands of an action one after another on a background thread. Cancelling does nothing,
+/// started programs cant be taken back.
+pub struct ActionJob {
+    pub label: String,
+    job: Job<Vec<ActionOutput>>,
+}
+
+impl ActionJob {
+    pub fn spawn<F>(action: &ContextAction, selection: &[SearchEngineResult], notify: F) -> ActionJob
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let commands = action.prepare(selection);
+        let capture_output = action.capture_output;
+
+        let job = Job::start(
+            move |reporter| reporter.send(commands.into_iter().map(|c| run(c, capture_output)).collect()),
+            notify,
+        );
+
+        ActionJob {
+            label: action.label.clone(),
+            job,
+        }
+    }
+
+    /// The outputs once everything ran, never blocks
+    pub fn poll(&self) -> Option<Vec<ActionOutput>> {
+        self.job.poll().pop()
+    }
+
+    /// Waits for all commands, mainly for headless use
+    pub fn wait(self) -> Vec<ActionOutput> {
+        self.job.wait().pop().unwrap_or_default()
+    }
+}
+
+/// Text to show after an action ran, the output of every command below its command line
+pub fn summary(outputs: &[ActionOutput]) -> String {
+    outputs
+        .iter()
+        .map(|o| {
+            let mut text = format!("> {} {}", o.command.program, o.command.args.join(" "));
+            for part in [&o.stdout, &o.stderr] {
+                if !part.trim().is_empty() {
+                    text.push('\n');
+                    text.push_str(part.trim_end());
+                }
+            }
+            if let Some(code) = o.status.filter(|c| *c != 0) {
+                text.push_str(&format!("\nExited with {}", code));
+            }
+            text
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
@@ -0,0 +1,388 @@
+use std::{
+    fs::{self, DirEntry},
+    path::{Path, MAIN_SEPARATOR},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    job::Job,
+    memory::index::{Index, IndexType},
+    search_engine::parameter_parser::SearchEngineParameter,
+    win::file_attributes,
+};
+
+const SEPARATORS: [char; 2] = ['\\', '/'];
+
+/// Size of a file or the recursive size of a directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageNode {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    /// Files below this directory, 1 for files
+    pub file_count: u64,
+    /// Largest first
+    pub children: Vec<UsageNode>,
+}
+
+impl UsageNode {
+    fn file(name: String, path: String, size: u64) -> UsageNode {
+        UsageNode {
+            name,
+            path,
+            is_dir: false,
+            size,
+            file_count: 1,
+            children: Vec::new(),
+        }
+    }
+
+    fn dir(name: String, path: String, mut children: Vec<UsageNode>) -> UsageNode {
+        children.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+        UsageNode {
+            name,
+            path,
+            is_dir: true,
+            size: children.iter().map(|c| c.size).sum(),
+            file_count: children.iter().map(|c| c.file_count).sum(),
+            children,
+        }
+    }
+
+    /// The node of `path` if it is this one or below it
+    pub fn find(&self, path: &str) -> Option<&UsageNode> {
+        let rest = strip_path_prefix(path, &self.path)?;
+
+        let mut node = self;
+        for part in rest.split(SEPARATORS).filter(|p| !p.is_empty()) {
+            node = node
+                .children
+                .iter()
+                .find(|c| c.is_dir && c.name.eq_ignore_ascii_case(part))?;
+        }
+        Some(node)
+    }
+
+    /// Share of this node in `total` in percent, 0 if the total is empty
+    pub fn percent_of(&self, total: u64) -> f64 {
+        match total {
+            0 => 0.0,
+            t => self.size as f64 * 100.0 / t as f64,
+        }
+    }
+}
+
+/// Case insensitive, `C:\Users` is a prefix of `C:\Users\me` but not of `C:\Users2`
+fn strip_path_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    let path = path.trim_end_matches(SEPARATORS);
+    let prefix = prefix.trim_end_matches(SEPARATORS);
+    if path.len() < prefix.len() || !path.is_char_boundary(prefix.len()) {
+        return None;
+    }
+
+    let (head, rest) = path.split_at(prefix.len());
+    match head.eq_ignore_ascii_case(prefix) && (rest.is_empty() || rest.starts_with(SEPARATORS)) {
+        true => Some(rest),
+        false => None,
+    }
+}
+
+/// Shared between the workers of a scan
+#[derive(Default)]
+pub struct ScanState {
+    pub cancelled: Arc<AtomicBool>,
+    pub scanned_dirs: AtomicUsize,
+    /// What could not be read, left out of the sizes
+    pub failures: Mutex<Vec<String>>,
+}
+
+impl ScanState {
+    fn fail(&self, message: String) {
+        if let Ok(mut failures) = self.failures.lock() {
+            failures.push(message);
+        }
+    }
+
+    pub fn take_failures(&self) -> Vec<String> {
+        self.failures.lock().map(|mut f| std::mem::take(&mut *f)).unwrap_or_default()
+    }
+}
+
+/// Computes the sizes below `path`, the subdirectories are scanned in parallel.
+/// Directories the skip flags of `skip` exclude and symlinks are left out. Whatever cant be read counts as empty
+/// and ends up in the failures of `state`.
+pub fn analyze(path: &str, skip: &SearchEngineParameter, state: &ScanState) -> Result<UsageNode, String> {
+    let (mut children, subdirs) = read_entries(path, skip, state).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+    let next = AtomicUsize::new(0);
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .min(subdirs.len());
+    let mut crashed = false;
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut done = Vec::new();
+                    while let Some((name, dir)) = subdirs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        done.push(scan_dir(name.clone(), dir.clone(), skip, state));
+                    }
+                    done
+                })
+            })
+            .collect();
+        for handle in handles {
+            match handle.join() {
+                Ok(done) => children.extend(done),
+                Err(_) => crashed = true,
+            }
+        }
+    });
+
+    if state.cancelled.load(Ordering::Relaxed) {
+        return Err(String::from("Disk usage calculation was cancelled"));
+    }
+    //The directories of the worker are missing, the sizes would be wrong
+    if crashed {
+        return Err(format!("Disk usage calculation of {} failed", path));
+    }
+
+    Ok(UsageNode::dir(name_of(path), path.into(), children))
+}
+
+fn scan_dir(name: String, path: String, skip: &SearchEngineParameter, state: &ScanState) -> UsageNode {
+    let mut children = Vec::new();
+    if state.cancelled.load(Ordering::Relaxed) {
+        return UsageNode::dir(name, path, children);
+    }
+
+    match read_entries(&path, skip, state) {
+        Ok((files, subdirs)) => {
+            children = files;
+            for (child_name, child_path) in subdirs {
+                children.push(scan_dir(child_name, child_path, skip, state));
+            }
+        }
+        Err(e) => state.fail(format!("Failed to read {}: {}", path, e)),
+    }
+    state.scanned_dirs.fetch_add(1, Ordering::Relaxed);
+
+    UsageNode::dir(name, path, children)
+}
+
+/// The files of `path` and the names and paths of the subdirectories that should be scanned
+#[allow(clippy::type_complexity)]
+fn read_entries(
+    path: &str,
+    skip: &SearchEngineParameter,
+    state: &ScanState,
+) -> std::io::Result<(Vec<UsageNode>, Vec<(String, String)>)> {
+    let mut files = Vec::new();
+    let mut subdirs = Vec::new();
+    for entry in fs::read_dir(path)?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let entry_path = entry.path().to_string_lossy().to_string();
+        match entry.file_type() {
+            Ok(t) if t.is_dir() && can_be_scanned(&entry, &name, skip, state) => subdirs.push((name, entry_path)),
+            Ok(t) if t.is_file() => {
+                let size = match entry.metadata() {
+                    Ok(m) => m.len(),
+                    Err(e) => {
+                        state.fail(format!("Failed to read {}: {}", entry_path, e));
+                        0
+                    }
+                };
+                files.push(UsageNode::file(name, entry_path, size));
+            }
+            _ => {}
+        }
+    }
+    Ok((files, subdirs))
+}
+
+/// Same as `SearchEngineParameter::dir_can_be_searched`, but doesnt panic on the worker threads
+/// if the metadata is gone or the name isnt valid unicode
+fn can_be_scanned(entry: &DirEntry, name: &str, skip: &SearchEngineParameter, state: &ScanState) -> bool {
+    match entry.metadata() {
+        Ok(m) => SearchEngineParameter::name_can_be_searched(name, file_attributes::attributes_of(&m), skip),
+        Err(e) => {
+            state.fail(format!("Failed to read {}: {}", entry.path().to_string_lossy(), e));
+            false
+        }
+    }
+}
+
+/// The part of the index below `path` with its full path, the disk isnt touched
+pub fn index_subtree(index: &Index, path: &str) -> Option<(IndexType, String)> {
+    let mut node = index.root.as_ref()?;
+    let mut node_path = match node {
+        IndexType::Dir(name, _) => name.clone(),
+        IndexType::File(_) => return None,
+    };
+    let rest = strip_path_prefix(path, &node_path)?;
+
+    for part in rest.split(SEPARATORS).filter(|p| !p.is_empty()) {
+        let (name, child) = match node {
+            IndexType::Dir(_, children) => children.iter().find_map(|c| match c {
+                IndexType::Dir(n, _) if n.eq_ignore_ascii_case(part) => Some((n, c)),
+                _ => None,
+            })?,
+            IndexType::File(_) => return None,
+        };
+        node = child;
+        node_path = format!("{}{}{}", node_path, MAIN_SEPARATOR, name);
+    }
+
+    Some((node.clone(), node_path))
+}
+
+/// Builds the tree from a part of the index instead of reading every directory, only the sizes and attributes
+/// are looked up. The index stops at a max depth, so deeper files are missing.
+pub fn from_index(
+    node: &IndexType,
+    path: String,
+    skip: &SearchEngineParameter,
+    state: &ScanState,
+) -> Result<UsageNode, String> {
+    let tree = index_node(node, path, skip, state);
+    match state.cancelled.load(Ordering::Relaxed) {
+        true => Err(String::from("Disk usage calculation was cancelled")),
+        false => Ok(tree),
+    }
+}
+
+fn index_node(node: &IndexType, path: String, skip: &SearchEngineParameter, state: &ScanState) -> UsageNode {
+    match node {
+        IndexType::Dir(name, children) => {
+            if state.cancelled.load(Ordering::Relaxed) {
+                return UsageNode::dir(name.clone(), path, Vec::new());
+            }
+            let children = children
+                .iter()
+                .filter_map(|c| {
+                    let child_path = match c {
+                        IndexType::Dir(n, _) | IndexType::File(n) => format!("{}{}{}", path, MAIN_SEPARATOR, n),
+                    };
+                    match c {
+                        IndexType::Dir(n, _) => {
+                            let attributes = match fs::metadata(&child_path) {
+                                Ok(m) => file_attributes::attributes_of(&m),
+                                Err(e) => {
+                                    state.fail(format!("Failed to read {}: {}", child_path, e));
+                                    return None;
+                                }
+                            };
+                            SearchEngineParameter::name_can_be_searched(n, attributes, skip)
+                                .then(|| index_node(c, child_path, skip, state))
+                        }
+                        IndexType::File(_) => Some(index_node(c, child_path, skip, state)),
+                    }
+                })
+                .collect();
+            state.scanned_dirs.fetch_add(1, Ordering::Relaxed);
+            UsageNode::dir(name.clone(), path, children)
+        }
+        IndexType::File(name) => {
+            let size = match fs::metadata(&path) {
+                Ok(m) => m.len(),
+                Err(e) => {
+                    state.fail(format!("Failed to read {}: {}", path, e));
+                    0
+                }
+            };
+            UsageNode::file(name.clone(), path, size)
+        }
+    }
+}
+
+fn name_of(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.trim_end_matches(SEPARATORS).to_string())
+}
+
+/// Trees that were already computed. Drilling down into a directory reuses the tree of its parent.
+#[derive(Default)]
+pub struct UsageCache {
+    trees: Vec<UsageNode>,
+}
+
+impl UsageCache {
+    pub fn get(&self, path: &str) -> Option<&UsageNode> {
+        self.trees.iter().find_map(|t| t.find(path))
+    }
+
+    /// Trees inside the new one are outdated by it
+    pub fn insert(&mut self, tree: UsageNode) {
+        self.trees
+            .retain(|t| strip_path_prefix(&t.path, &tree.path).is_none());
+        self.trees.push(tree);
+    }
+
+    pub fn clear(&mut self) {
+        self.trees.clear();
+    }
+}
+
+#[derive(Debug)]
+pub enum UsageEvent {
+    Progress { scanned_dirs: usize },
+    /// With the paths that couldnt be read
+    Finished(Result<UsageNode, String>, Vec<String>),
+}
+
+/// Where the sizes come from
+pub enum UsageSource {
+    /// Reads every directory below the path
+    Disk(String),
+    /// A part of the index with its full path, see `index_subtree`
+    Index(IndexType, String),
+}
+
+/// Runs `analyze` or `from_index` on a background thread
+pub type UsageJob = Job<UsageEvent>;
+
+impl UsageJob {
+    pub fn spawn<F>(source: UsageSource, skip: SearchEngineParameter, notify: F) -> UsageJob
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        Job::start(
+            move |reporter| {
+                let state = ScanState {
+                    cancelled: reporter.cancel_flag(),
+                    ..Default::default()
+                };
+                let finished = AtomicBool::new(false);
+                let result = thread::scope(|s| {
+                    //Reports the progress every now and then while the workers are busy
+                    s.spawn(|| {
+                        while !finished.load(Ordering::Relaxed) {
+                            thread::sleep(Duration::from_millis(250));
+                            let scanned_dirs = state.scanned_dirs.load(Ordering::Relaxed);
+                            reporter.send(UsageEvent::Progress { scanned_dirs });
+                        }
+                    });
+                    let result = match source {
+                        UsageSource::Disk(path) => analyze(&path, &skip, &state),
+                        UsageSource::Index(node, path) => from_index(&node, path, &skip, &state),
+                    };
+                    finished.store(true, Ordering::Relaxed);
+                    result
+                });
+
+                reporter.send(UsageEvent::Finished(result, state.take_failures()));
+            },
+            notify,
+        )
+    }
+}
@@ -0,0 +1,150 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::{app_state::AppState, search_engine::result::SearchEngineResult};
+
+/// FAT keeps modification times in steps of two seconds, copies there dont match the original exactly
+const MODIFIED_TOLERANCE: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Side {
+    #[default]
+    Left,
+    Right,
+}
+
+impl Side {
+    pub fn other(self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+}
+
+/// The left pane is the active tab, the right one browses on its own
+#[derive(Default)]
+pub struct DualPane {
+    pub enabled: bool,
+    pub active: Side,
+    pub right: AppState,
+    /// Differences of the last comparison, cleared once either side lists something else
+    pub comparison: Option<Comparison>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difference {
+    /// The other side has nothing with this name
+    Missing,
+    /// A file on one side and a folder on the other
+    Type,
+    Size,
+    Modified,
+}
+
+impl Difference {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difference::Missing => "Only here",
+            Difference::Type => "Type",
+            Difference::Size => "Size",
+            Difference::Modified => "Modified",
+        }
+    }
+}
+
+/// Rows of both sides that differ, by row so every cell finds its own at once
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Comparison {
+    pub left: HashMap<usize, Difference>,
+    pub right: HashMap<usize, Difference>,
+}
+
+impl Comparison {
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty() && self.right.is_empty()
+    }
+
+    fn side(&self, side: Side) -> &HashMap<usize, Difference> {
+        match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        }
+    }
+
+    pub fn difference(&self, side: Side, row: usize) -> Option<Difference> {
+        self.side(side).get(&row).copied()
+    }
+
+    /// In the order of the results
+    pub fn rows(&self, side: Side) -> Vec<usize> {
+        let mut rows: Vec<usize> = self.side(side).keys().copied().collect();
+        rows.sort_unstable();
+        rows
+    }
+
+    /// A file and a folder of the same name count as differing, not as missing on either side
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return String::from("Both sides are the same");
+        }
+        let missing = |rows: &HashMap<usize, Difference>| rows.values().filter(|d| **d == Difference::Missing).count();
+        let changed = self.left.len() - missing(&self.left);
+        format!(
+            "{} only left, {} only right, {} differ",
+            missing(&self.left),
+            missing(&self.right),
+            changed
+        )
+    }
+}
+
+/// Matches the results by name, case insensitive on Windows. Folders are only compared by name,
+/// their size and date say nothing about the content.
+pub fn compare(left: &[SearchEngineResult], right: &[SearchEngineResult]) -> Comparison {
+    let key = |r: &SearchEngineResult| match cfg!(windows) {
+        true => r.name.to_lowercase(),
+        false => r.name.clone(),
+    };
+    let right_rows: HashMap<String, usize> = right.iter().enumerate().map(|(i, r)| (key(r), i)).collect();
+
+    let mut comparison = Comparison::default();
+    let mut matched = vec![false; right.len()];
+    for (row, l) in left.iter().enumerate() {
+        let other = match right_rows.get(&key(l)) {
+            Some(&other) => other,
+            None => {
+                comparison.left.insert(row, Difference::Missing);
+                continue;
+            }
+        };
+        matched[other] = true;
+        let r = &right[other];
+        let difference = if l.file_type.is_dir() != r.file_type.is_dir() {
+            Difference::Type
+        } else if l.file_type.is_dir() {
+            continue;
+        } else if l.size != r.size {
+            Difference::Size
+        } else if modified_apart(l, r) {
+            Difference::Modified
+        } else {
+            continue;
+        };
+        comparison.left.insert(row, difference);
+        comparison.right.insert(other, difference);
+    }
+    for (row, matched) in matched.into_iter().enumerate() {
+        if !matched {
+            comparison.right.insert(row, Difference::Missing);
+        }
+    }
+    comparison
+}
+
+fn modified_apart(a: &SearchEngineResult, b: &SearchEngineResult) -> bool {
+    let apart = match a.modified.duration_since(b.modified) {
+        Ok(d) => d,
+        Err(e) => e.duration(),
+    };
+    apart > MODIFIED_TOLERANCE
+}
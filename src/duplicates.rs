@@ -0,0 +1,383 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom},
+    path::MAIN_SEPARATOR,
+};
+
+use crate::{
+    hashing::{self, HashError, HashFunction},
+    job::Job,
+    memory::index::{Index, IndexType},
+};
+
+/// Size of the blocks at the start and end of a file that are hashed before the whole file
+pub const BLOCK_SIZE: u64 = 4096;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateOptions {
+    /// Smaller files are ignored, empty files are all equal but dont waste anything
+    pub min_size: u64,
+    pub hash_function: HashFunction,
+}
+
+impl Default for DuplicateOptions {
+    fn default() -> Self {
+        DuplicateOptions {
+            min_size: 1,
+            hash_function: HashFunction::BLAKE3,
+        }
+    }
+}
+
+/// Files with identical content
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateGroup {
+    pub size: u64,
+    pub hash: String,
+    pub files: Vec<String>,
+}
+
+impl DuplicateGroup {
+    /// Space that could be freed by keeping a single copy
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.files.len() as u64).saturating_sub(1)
+    }
+}
+
+/// The groups of a finished search with the set of every file, so a path is found without scanning
+#[derive(Default)]
+pub struct DuplicateSets {
+    groups: Vec<DuplicateGroup>,
+    by_path: HashMap<String, usize>,
+}
+
+impl DuplicateSets {
+    pub fn new(groups: Vec<DuplicateGroup>) -> Self {
+        let by_path = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(i, g)| g.files.iter().map(move |f| (f.clone(), i)))
+            .collect();
+        DuplicateSets { groups, by_path }
+    }
+
+    pub fn groups(&self) -> &[DuplicateGroup] {
+        &self.groups
+    }
+
+    /// Index and group of the set the file belongs to
+    pub fn find(&self, path: &str) -> Option<(usize, &DuplicateGroup)> {
+        let i = *self.by_path.get(path)?;
+        Some((i, &self.groups[i]))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = DuplicateSets::default();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    Collecting,
+    PartialHash,
+    FullHash,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Collecting => write!(f, "Collecting files"),
+            Stage::PartialHash => write!(f, "Hashing file starts"),
+            Stage::FullHash => write!(f, "Hashing whole files"),
+        }
+    }
+}
+
+/// Where the files to compare come from
+pub enum FileSource {
+    /// Walks the directory tree on the worker thread
+    Tree(String),
+    Files(Vec<String>),
+}
+
+/// All files below `root`, symlinks arent followed and unreadable directories are skipped.
+/// `keep_going` is asked before every directory and stops the walk by returning false.
+pub fn walk<F>(root: &str, mut keep_going: F) -> Result<Vec<String>, HashError>
+where
+    F: FnMut() -> bool,
+{
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_string()];
+
+    while let Some(dir) = dirs.pop() {
+        if !keep_going() {
+            return Err(HashError::Cancelled);
+        }
+        let entries = match fs::read_dir(&dir) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path().to_string_lossy().to_string();
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => dirs.push(path),
+                Ok(t) if t.is_file() => files.push(path),
+                _ => {}
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Files of the index below `root`, without touching the disk
+pub fn index_files(index: &Index, root: &str) -> Vec<String> {
+    let mut files = Vec::new();
+    if let Some(node) = &index.root {
+        collect_index_files(node, "", &mut files);
+    }
+
+    let prefix = root.trim_end_matches(['\\', '/']).to_lowercase();
+    files
+        .into_iter()
+        .filter(|f| {
+            let lower = f.to_lowercase();
+            lower.starts_with(&prefix) && lower[prefix.len()..].starts_with(['\\', '/'])
+        })
+        .collect()
+}
+
+fn collect_index_files(node: &IndexType, parent: &str, files: &mut Vec<String>) {
+    match node {
+        IndexType::Dir(name, children) => {
+            let path = match parent {
+                "" => name.clone(),
+                p => format!("{}{}{}", p, MAIN_SEPARATOR, name),
+            };
+            for child in children {
+                collect_index_files(child, &path, files);
+            }
+        }
+        IndexType::File(name) => files.push(format!("{}{}{}", parent, MAIN_SEPARATOR, name)),
+    }
+}
+
+/// Groups identical files, first by size, then by a hash of the first and last block and at last by a full hash.
+/// Hardlinks to the same file count as one. `progress` gets the stage with the done and total amount of files
+/// and stops the search by returning false.
+pub fn find_duplicates<F>(
+    paths: Vec<String>,
+    options: &DuplicateOptions,
+    mut progress: F,
+) -> Result<Vec<DuplicateGroup>, HashError>
+where
+    F: FnMut(Stage, usize, usize) -> bool,
+{
+    let total = paths.len();
+    let mut by_size: HashMap<u64, Vec<String>> = HashMap::new();
+    for (i, path) in paths.into_iter().enumerate() {
+        if !progress(Stage::Collecting, i, total) {
+            return Err(HashError::Cancelled);
+        }
+        match fs::metadata(&path) {
+            Ok(m) if m.is_file() && m.len() >= options.min_size => by_size.entry(m.len()).or_default().push(path),
+            _ => {}
+        }
+    }
+
+    let candidates: Vec<(u64, Vec<String>)> = by_size
+        .into_iter()
+        .map(|(size, files)| (size, without_hardlinks(files)))
+        .filter(|(_, files)| files.len() > 1)
+        .collect();
+
+    let partial = split_by_hash(candidates, Stage::PartialHash, &mut progress, |path, size, _| {
+        partial_hash(path, size, options.hash_function)
+    })?;
+
+    //The partial hash already covered the whole content of small files
+    let (small, big): (Vec<_>, Vec<_>) = partial
+        .into_iter()
+        .partition(|(size, _, _)| *size <= 2 * BLOCK_SIZE);
+    let big = big.into_iter().map(|(size, _, files)| (size, files)).collect();
+    //Big files take a while, so the hashing itself can be stopped as well
+    let full = split_by_hash(big, Stage::FullHash, &mut progress, |path, _, keep_going| {
+        hashing::hash_file(path, options.hash_function, keep_going)
+    })?;
+
+    let mut groups: Vec<DuplicateGroup> = small
+        .into_iter()
+        .chain(full)
+        .map(|(size, hash, mut files)| {
+            files.sort();
+            DuplicateGroup { size, hash, files }
+        })
+        .collect();
+    groups.sort_by(|a, b| b.wasted().cmp(&a.wasted()).then_with(|| a.files.cmp(&b.files)));
+
+    Ok(groups)
+}
+
+/// Splits every group by the hash of its files, files that cant be read are dropped.
+/// `hash` gets a check that asks `progress` whether to go on.
+fn split_by_hash<P, H>(
+    groups: Vec<(u64, Vec<String>)>,
+    stage: Stage,
+    progress: &mut P,
+    mut hash: H,
+) -> Result<Vec<(u64, String, Vec<String>)>, HashError>
+where
+    P: FnMut(Stage, usize, usize) -> bool,
+    H: FnMut(&str, u64, &mut dyn FnMut(u64) -> bool) -> Result<String, HashError>,
+{
+    let total = groups.iter().map(|(_, files)| files.len()).sum();
+    let mut done = 0;
+    let mut result = Vec::new();
+
+    for (size, files) in groups {
+        let mut by_hash: HashMap<String, Vec<String>> = HashMap::new();
+        for path in files {
+            if !progress(stage, done, total) {
+                return Err(HashError::Cancelled);
+            }
+            let hashed = hash(&path, size, &mut |_| progress(stage, done, total));
+            done += 1;
+            match hashed {
+                Ok(h) => by_hash.entry(h).or_default().push(path),
+                Err(HashError::Cancelled) => return Err(HashError::Cancelled),
+                Err(_) => {}
+            }
+        }
+        result.extend(
+            by_hash
+                .into_iter()
+                .filter(|(_, files)| files.len() > 1)
+                .map(|(h, files)| (size, h, files)),
+        );
+    }
+
+    Ok(result)
+}
+
+/// Hash of the first and last `BLOCK_SIZE` bytes, of the whole file if it is smaller than two blocks
+fn partial_hash(path: &str, size: u64, hf: HashFunction) -> Result<String, HashError> {
+    let to_err = |e: io::Error| HashError::Io(path.into(), e.to_string());
+    let mut file = File::open(path).map_err(to_err)?;
+    let mut data = Vec::new();
+
+    if size <= 2 * BLOCK_SIZE {
+        file.read_to_end(&mut data).map_err(to_err)?;
+    } else {
+        data.resize(2 * BLOCK_SIZE as usize, 0);
+        let (head, tail) = data.split_at_mut(BLOCK_SIZE as usize);
+        file.read_exact(head).map_err(to_err)?;
+        file.seek(SeekFrom::End(-(BLOCK_SIZE as i64))).map_err(to_err)?;
+        file.read_exact(tail).map_err(to_err)?;
+    }
+
+    let hash = hashing::hash_reader(data.as_slice(), hf, |_| true).map_err(to_err)?;
+    Ok(hashing::to_hex(&hash))
+}
+
+/// Keeps only the first path of every physical file
+fn without_hardlinks(files: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    files
+        .into_iter()
+        .filter(|f| match file_id(f) {
+            Some(id) => seen.insert(id),
+            None => true,
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+fn file_id(path: &str) -> Option<(u64, u64)> {
+    use std::os::windows::io::AsRawHandle;
+    use winapi::um::fileapi::{GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION};
+
+    let file = File::open(path).ok()?;
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) } == 0 {
+        return None;
+    }
+
+    let index = (info.nFileIndexHigh as u64) << 32 | info.nFileIndexLow as u64;
+    Some((info.dwVolumeSerialNumber as u64, index))
+}
+
+#[cfg(not(windows))]
+fn file_id(path: &str) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+pub fn total_wasted(groups: &[DuplicateGroup]) -> u64 {
+    groups.iter().map(DuplicateGroup::wasted).sum()
+}
+
+/// One line per file with the number of its group, paths are quoted
+pub fn to_csv(groups: &[DuplicateGroup]) -> String {
+    let mut csv = String::from("group,size,wasted,hash,path\n");
+    for (i, group) in groups.iter().enumerate() {
+        for file in &group.files {
+            csv.push_str(&format!(
+                "{},{},{},{},\"{}\"\n",
+                i + 1,
+                group.size,
+                group.wasted(),
+                group.hash,
+                file.replace('"', "\"\"")
+            ));
+        }
+    }
+    csv
+}
+
+#[derive(Debug)]
+pub enum DuplicateEvent {
+    Progress { stage: Stage, done: usize, total: usize },
+    Finished(Result<Vec<DuplicateGroup>, HashError>),
+}
+
+/// Runs `find_duplicates` on a background thread
+pub type DuplicateJob = Job<DuplicateEvent>;
+
+impl DuplicateJob {
+    pub fn spawn<F>(source: FileSource, options: DuplicateOptions, notify: F) -> DuplicateJob
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        Job::start(
+            move |reporter| {
+                let paths = match source {
+                    FileSource::Tree(root) => walk(&root, || !reporter.is_cancelled()),
+                    FileSource::Files(files) => Ok(files),
+                };
+
+                let mut last_percent = None;
+                let result = paths.and_then(|paths| {
+                    find_duplicates(paths, &options, |stage, done, total| {
+                        let percent = Some((stage, done * 100 / total.max(1)));
+                        if last_percent != percent {
+                            last_percent = percent;
+                            reporter.send(DuplicateEvent::Progress { stage, done, total });
+                        }
+                        !reporter.is_cancelled()
+                    })
+                });
+
+                reporter.send(DuplicateEvent::Finished(result));
+            },
+            notify,
+        )
+    }
+}
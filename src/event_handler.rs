@@ -1,12 +1,26 @@
 use nwg::{EventData, MessageParams, MessageChoice};
-use std::rc::Rc;
-use time::Instant;
-use winapi::um::winuser::{self};
+use std::{fs, mem, ptr, rc::Rc};
+use winapi::{
+    shared::{minwindef::LPARAM, windef::POINT},
+    um::{
+        commctrl::{
+            LVHITTESTINFO, LVIF_IMAGE, LVIF_STATE, LVIF_TEXT, LVIS_SELECTED, LVM_HITTEST, LVN_ENDLABELEDITW, LVN_GETDISPINFOW,
+            LVN_ITEMCHANGED, LVN_ODCACHEHINT, LVN_ODSTATECHANGED, NMLISTVIEW, NMLVCACHEHINT, NMLVDISPINFOW, NMLVODSTATECHANGE,
+        },
+        winuser::{self, NMHDR, WM_MBUTTONUP, WM_NOTIFY},
+    },
+};
 use crate::components::menuable::Menuable;
 
 use crate::{
-    app::BasicAppUi,
-    search_engine::{SearchEngine, parameter_parser::SearchEngineParameter},
+    app::{BasicApp, BasicAppUi},
+    app_state::Action,
+    components::search_result_control::HashReport,
+    context_actions,
+    dual_pane::Side,
+    duplicates::{self, FileSource},
+    keybindings::KeyAction,
+    search_engine::sort::SortKey,
     win::key_codes::VirtualKeyCode
 };
 use clipboard::{ClipboardContext, ClipboardProvider};
@@ -18,22 +32,31 @@ pub fn handle_events(ui: &mut BasicAppUi) {
         if let Some(app) = evt_ui.upgrade() {
             match evt {
                 E::OnButtonClick => {
-                    if handle == app.header.refresh_btn {
-                        app.header.refresh_btn.set_text("");
-                        //TODO: error handling
-                        //not required because setting the text triggers an event app.result_list.refresh(SearchEngine::default().search("", &app.path_bar.get_path().unwrap()))
+                    if handle == app.bulk_rename.apply_btn {
+                        match app.bulk_rename.apply() {
+                            Ok(report) => {
+                                app.record_operation(&report);
+                                let errors = report.errors();
+                                if !errors.is_empty() {
+                                    app.display_error(errors.join("\n"));
+                                }
+                                app.status_bar.task_progress.set_text(&report.to_string());
+                                app.dispatch(Action::Refresh);
+                            }
+                            Err(e) => app.display_error(e),
+                        }
+                    } else if handle == app.bulk_rename.regex || handle == app.bulk_rename.ignore_case {
+                        app.bulk_rename.update_preview();
+                    } else if handle == app.header.refresh_btn {
+                        app.cache.disk_usage.borrow_mut().clear();
+                        app.dispatch(Action::Refresh);
                     } else if handle == app.header.last_page_btn {
-                        //Only triggers when enabled
-                        app.header.path_bar.move_one_back();
-                        //triggers event
-                        app.header.search_input.set_text("");
+                        app.dispatch(Action::Back);
                     } else if handle == app.header.copy_path_btn {
                         let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
                         ctx.set_contents(app.header.path_bar.get_path().unwrap()).unwrap();
                     } else if handle == app.header.parent_page_btn {
-                        app.header.path_bar.move_one_up();
-                        //triggers event
-                        app.header.search_input.set_text("");
+                        app.dispatch(Action::Up);
                     } else if handle == app.status_bar.index_refresh {
                         let ans = nwg::modal_message(&app.window, &MessageParams {
                             title: "Refresh index",
@@ -47,45 +70,118 @@ pub fn handle_events(ui: &mut BasicAppUi) {
                         }
 
                         app.cache.index.borrow_mut().refresh(&app.status_bar.index_date);
+                    } else if handle == app.status_bar.show_hidden {
+                        let checked = app.status_bar.show_hidden.check_state() == nwg::CheckBoxState::Checked;
+                        app.cache.settings.borrow_mut().set_show_hidden_files(checked);
+                        app.dispatch(Action::ShowHidden(checked));
+                        if app.cache.dual_pane.borrow().enabled {
+                            app.dispatch_right(Action::ShowHidden(checked));
+                        }
+                    } else if handle == app.status_bar.disk_usage {
+                        //The cached sizes might have been calculated with other skip flags
+                        app.cache.disk_usage.borrow_mut().clear();
+                        let checked = app.status_bar.disk_usage.check_state() == nwg::CheckBoxState::Checked;
+                        app.dispatch(Action::DiskUsage(checked));
+                    } else if handle == app.status_bar.find_duplicates {
+                        if app.search_results.cancel_duplicate_search() {
+                            return;
+                        }
+                        let path = match app.header.path_bar.get_path() {
+                            Ok(p) => p,
+                            Err(e) => {
+                                app.display_error(e);
+                                return;
+                            }
+                        };
+                        let source = match app.status_bar.index_usage.check_state() {
+                            nwg::CheckBoxState::Checked => {
+                                FileSource::Files(duplicates::index_files(&app.cache.index.borrow(), &path))
+                            }
+                            _ => FileSource::Tree(path),
+                        };
+                        if let Err(e) = app.search_results.start_duplicate_search(source) {
+                            app.display_error(e);
+                        }
+                    } else if handle == app.status_bar.export_duplicates {
+                        if app.cache.duplicates.borrow().is_empty() {
+                            app.display_error(String::from("There are no duplicates to export"));
+                            return;
+                        }
+                        let csv = duplicates::to_csv(app.cache.duplicates.borrow().groups());
+                        if !app.export_dialog.run(Some(&app.window)) {
+                            return;
+                        }
+                        if let Ok(target) = app.export_dialog.get_selected_item() {
+                            let mut target = target.to_string_lossy().to_string();
+                            if !target.to_lowercase().ends_with(".csv") {
+                                target.push_str(".csv");
+                            }
+                            if let Err(e) = fs::write(&target, csv) {
+                                app.display_error(format!("Failed to write {}: {}", target, e));
+                            }
+                        }
+                    } else if handle == app.preview.toggle_btn {
+                        app.preview.set_visible(!app.preview.is_visible(), &app.search_results.list);
+                        app.update_preview();
+                    } else if handle == app.header.search_help_btn {
+                        app.search_help.open();
+                    } else if handle == app.header.path_bar.edit_btn {
+                        app.header.path_bar.set_editing(true);
+                    } else if handle == app.dual_pane.toggle_btn {
+                        app.set_dual_pane(app.dual_pane.toggle_btn.check_state() == nwg::CheckBoxState::Checked);
+                    } else if handle == app.dual_pane.compare_btn {
+                        app.compare_panes();
+                    } else if handle == app.tab_bar.new_tab_btn {
+                        app.new_tab();
+                    } else if let Some(index) = app.tab_bar.tab_of(&handle) {
+                        app.switch_tab(index);
+                    } else if let Some(index) = app.tab_bar.close_of(&handle) {
+                        app.close_tab(index);
+                    } else if let Some(path) = app.header.path_bar.click_segment(&handle) {
+                        app.dispatch(Action::Navigate(path));
+                    } else {
+                        app.header.path_bar.open_siblings(&handle, &app.cache.index.borrow());
                     }
                 }
                 E::OnWindowClose => {
                     if handle == app.window {
+                        app.save_session();
                         nwg::stop_thread_dispatch();
+                    } else if handle == app.bulk_rename.window {
+                        app.bulk_rename.close();
+                    } else if handle == app.search_help.window {
+                        app.search_help.close();
+                    }
+                }
+                E::OnComboxBoxSelection => {
+                    if handle == app.bulk_rename.case {
+                        app.bulk_rename.update_preview();
                     }
                 }
                 E::OnTextInput => {
-                    if handle == app.header.search_input {
+                    if app.bulk_rename.is_input(&handle) {
+                        app.bulk_rename.update_preview();
+                    } else if handle == app.header.path_bar.view {
+                        app.header.path_bar.update_suggestions(&app.cache.index.borrow());
+                    } else if handle == app.header.search_input {
+                        app.header.update_search_suggestions(&app.cache.tabs.borrow().active().help);
                         let txt = app.header.search_input.text();
-                        let pth = app.header.path_bar.get_path();
-                        if let Err(e) = pth {
-                            app.display_error(e);
-                        } else {
-                            let now = Instant::now();
-                            let term = SearchEngineParameter::parse_search_term(&txt);
-                            if term.is_err() {
-                                return;
-                            }
-                            let res = SearchEngine::search(&term.unwrap(), &pth.unwrap(), 0);
-                            if res.is_err() {
-                                return; //Search term invalid. TODO: MAYBE inform user, but most likely search term isnt completed yet
-                            }
-                            app.cache.current_results.replace(res.clone().unwrap());
-                            app.search_results.refresh(res.unwrap());
-                            let elapsed = now.elapsed();
-                            app.status_bar
-                                .search_duration
-                                .set_text(&format!("{}ms", elapsed.whole_milliseconds()));
+                        //Rendering writes the query back into the input, which lands here again
+                        if app.cache.tabs.borrow().active().query != txt {
+                            app.dispatch(Action::Search(txt));
+                        }
+                    } else if handle == app.dual_pane.search_input {
+                        let txt = app.dual_pane.search_input.text();
+                        if app.cache.dual_pane.borrow().right.query != txt {
+                            app.dispatch_right(Action::Search(txt));
                         }
                     }
                 }
                 E::OnListViewColumnClick => {
-                    if handle == app.search_results.list{
-                        app.search_results.sort_by_column(
-                            &mut app.cache.result_sort_direction.borrow_mut(),
-                            evt_data.on_list_view_item_index().1,
-                            app.cache.current_results.borrow_mut().to_vec(),
-                        );
+                    if handle == app.search_results.list {
+                        if let Some(key) = SortKey::from_column(evt_data.on_list_view_item_index().1) {
+                            app.dispatch(Action::Sort(key));
+                        }
                     }
                 }
                 E::OnListViewClick => {
@@ -96,80 +192,352 @@ pub fn handle_events(ui: &mut BasicAppUi) {
                             return;
                         }
                         let path = app.fav_dir_bar.list.item(row, 1, 260).unwrap().text;
-                        app.header.path_bar.move_into_directory(path);
-                        //triggers event
-                        app.header.search_input.set_text("");
+                        app.dispatch_active(Action::Navigate(path));
+                    } else if handle == app.search_results.list {
+                        app.activate_pane(Side::Left);
+                    } else if handle == app.dual_pane.list {
+                        app.activate_pane(Side::Right);
                     }
                 }
                 E::OnListViewDoubleClick => {
                     if handle == app.search_results.list {
                         let (row, _col) = evt_data.on_list_view_item_index();
-                        if row >= app.search_results.list.len() {
-                            //Clicked on empty field
-                            return;
-                        }
-                        let file_type = app.search_results.list.item(row, 2, 10).unwrap().text;
-                        let res = app.search_results.list.item(row, 4, 260).unwrap();
-                        if !file_type.eq("Directory") {
-                            let _ = open::that(&res.text);
-                            return;
-                        }
-
-                        let path = res.text;
-
-                        app.header.path_bar.move_into_directory(path);
-                        //triggers event
-                        app.header.search_input.set_text("");
+                        //Rows past the end are ignored
+                        app.dispatch(Action::Activate(row));
+                    } else if handle == app.dual_pane.list {
+                        let (row, _col) = evt_data.on_list_view_item_index();
+                        app.dispatch_right(Action::Activate(row));
                     }
                 }
-                E::OnKeyPress => {
+                //Alt combinations arrive as system keys
+                E::OnKeyPress | E::OnSysKeyPress => {
                     //VKRETURN = Enter https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes
-                    if handle == app.header.path_bar.view
-                        && evt_data.on_key() == VirtualKeyCode::VK_RETURN as u32
+                    let key = evt_data.on_key();
+                    let typing = handle == app.header.path_bar.view
+                        || handle == app.header.search_input
+                        || handle == app.dual_pane.path_input
+                        || handle == app.dual_pane.search_input;
+                    if handle == app.dual_pane.path_input && key == VirtualKeyCode::VK_RETURN as u32 {
+                        app.dispatch_right(Action::Navigate(app.dual_pane.path_input.text()));
+                    } else if handle == app.header.path_bar.view && key == VirtualKeyCode::VK_RETURN as u32 {
+                        app.header.path_bar.set_editing(false);
+                        app.dispatch(Action::Navigate(app.header.path_bar.view.text()));
+                    } else if handle == app.header.path_bar.view && key == VirtualKeyCode::VK_ESCAPE as u32 {
+                        app.header.path_bar.set_editing(false);
+                    } else if handle == app.header.search_input && key == VirtualKeyCode::VK_ESCAPE as u32 {
+                        app.header.search_suggestions.set_visible(false);
+                    } else if typing
+                        || handle == app.search_results.list
+                        || handle == app.dual_pane.list
+                        || handle == app.fav_dir_bar.list
+                        || handle == app.window
                     {
-                        //triggers event
-                        app.header.search_input.set_text("");
+                        //The selection shortcuts only know the left list
+                        let global_only = typing || handle == app.dual_pane.list;
+                        let action = app.cache.keymap.borrow().action_for_key(
+                            key,
+                            is_down(VirtualKeyCode::VK_CONTROL),
+                            is_down(VirtualKeyCode::VK_SHIFT),
+                            is_down(VirtualKeyCode::VK_MENU),
+                        );
+                        match action {
+                            //The rest would take keys like Delete away from the text
+                            Some(action) if !global_only || action.is_global() => execute_key_action(&app, action),
+                            _ => {}
+                        }
                     }
                 }
                 E::OnListViewRightClick => {
                     if handle == app.search_results.list {
-                        app.search_results.open_menu(&evt_data);
+                        app.activate_pane(Side::Left);
+                        app.search_results.open_menu(&evt_data, &app);
+                    } else if handle == app.dual_pane.list {
+                        app.activate_pane(Side::Right);
+                        app.dual_pane.open_menu(&evt_data, &app);
                     } else if handle == app.fav_dir_bar.list {
-                        app.fav_dir_bar.open_menu(&evt_data);
+                        app.fav_dir_bar.open_menu(&evt_data, &app);
                     }                    
                 }
-                E::OnMenuItemSelected => {
-                    app.search_results.execute_menu_item_click(&handle, Rc::clone(&app));
-                    app.fav_dir_bar.execute_menu_item_click(&handle, Rc::clone(&app));
+                E::OnListBoxDoubleClick => {
+                    if handle == app.header.path_bar.suggestions {
+                        app.header.path_bar.accept_suggestion();
+                    } else if handle == app.header.search_suggestions {
+                        app.header.accept_search_suggestion();
+                    }
                 }
-                E::OnMouseWheel => {
-                    if handle == app.search_results.list {
-                        app.search_results.add_page(&app.cache);
-                        app.status_bar
-                                .result_count
-                                .set_text(&format!("{} results", app.search_results.list.len()));
+                E::OnMenuItemSelected => {
+                    if let Some(path) = app.header.path_bar.select_sibling(&handle) {
+                        app.dispatch(Action::Navigate(path));
+                    } else {
+                        app.search_results.execute_menu_item_click(&handle, Rc::clone(&app));
+                        app.fav_dir_bar.execute_menu_item_click(&handle, Rc::clone(&app));
+                        app.dual_pane.execute_menu_item_click(&handle, Rc::clone(&app));
                     }
                 }
-                E::OnListViewItemInsert => {
-                    if handle == app.search_results.list {
-                        app.status_bar
-                                .result_count
-                                .set_text(&format!("{} results", app.search_results.list.len()));
+                E::OnNotice => {
+                    if handle == app.search_results.usage_notice {
+                        let (status, done) = app.search_results.poll_usage_job(&app.cache);
+                        app.status_bar.task_progress.set_text(&status);
+                        if done {
+                            app.dispatch(Action::Refresh);
+                        }
+                    } else if handle == app.search_results.duplicate_notice {
+                        let status = app.search_results.poll_duplicate_job(&app.cache);
+                        app.status_bar.task_progress.set_text(&status);
+                        app.render();
+                    } else if handle == app.search_results.hash_notice {
+                        let (progress, report) = app.search_results.poll_hash_job();
+                        app.status_bar.task_progress.set_text(&progress);
+                        match report {
+                            Some(HashReport::Errors(e)) if !e.is_empty() => app.display_error(e.join("\n")),
+                            Some(HashReport::Verification(lines)) => {
+                                nwg::modal_info_message(&app.window, "Checksum verification", &lines.join("\n"));
+                            }
+                            _ => {}
+                        }
+                    } else if handle == app.preview.notice {
+                        app.preview.poll_job(&app.cache.formatter.borrow());
+                    } else if handle == app.search_results.action_notice {
+                        if let Some((label, outputs)) = app.search_results.poll_action_job() {
+                            let failed = outputs.iter().any(|o| o.status.is_none() && !o.stderr.is_empty());
+                            let captured = outputs.iter().any(|o| o.status.is_some());
+                            let mut text = context_actions::summary(&outputs);
+                            if text.len() > 4000 {
+                                let end = (0..=4000).rev().find(|i| text.is_char_boundary(*i)).unwrap_or(0);
+                                text.truncate(end);
+                                text.push_str("\n...");
+                            }
+                            if failed {
+                                app.display_error(text);
+                            } else if captured {
+                                nwg::modal_info_message(&app.window, &label, &text);
+                            }
+                        }
+                    } else if handle == app.search_results.file_notice {
+                        let (progress, report) = app.search_results.poll_file_job();
+                        app.status_bar.task_progress.set_text(&progress);
+                        if let Some(report) = report {
+                            app.record_operation(&report);
+                            let errors = report.errors();
+                            if !errors.is_empty() {
+                                app.display_error(errors.join("\n"));
+                            }
+                            app.dispatch(Action::Refresh);
+                            if app.cache.dual_pane.borrow().enabled {
+                                app.dispatch_right(Action::Refresh);
+                            }
+                        }
                     }
                 }
                 _ => {}
             }
-
-            //TODO optimize to only update if an event was called that can change the depth. This way it fires a million times
-            unsafe {
-                winuser::EnableWindow(app.header.last_page_btn.handle.hwnd().unwrap(), app.header.path_bar.any_last_page() as i32);
-                winuser::EnableWindow(app.header.parent_page_btn.handle.hwnd().unwrap(), (app.header.path_bar.depth() > 0) as i32);
-            }
         }
     };
 
+    ui.dialog_handlers.borrow_mut().push(nwg::full_bind_event_handler(
+        &ui.inner.bulk_rename.window.handle,
+        handle_events.clone(),
+    ));
+    ui.dialog_handlers.borrow_mut().push(nwg::full_bind_event_handler(
+        &ui.inner.search_help.window.handle,
+        handle_events.clone(),
+    ));
+
     *ui.default_handler.borrow_mut() = Some(nwg::full_bind_event_handler(
         &ui.inner.window.handle,
         handle_events,
     ));
 }
+
+/// Both result lists are virtual, windows asks for the text of every visible cell through WM_NOTIFY
+pub fn handle_list_notifications(ui: &mut BasicAppUi) -> Result<(), nwg::NwgError> {
+    let evt_ui = Rc::downgrade(&ui.inner);
+    let handler = nwg::bind_raw_event_handler(&ui.inner.window.handle, 0x10000, move |_hwnd, msg, _w, l| {
+        if msg != WM_NOTIFY {
+            return None;
+        }
+        let app = evt_ui.upgrade()?;
+        let hdr = unsafe { &*(l as *const NMHDR) };
+        let right = hdr.hwndFrom == app.dual_pane.list.handle.hwnd()?;
+        if !right && hdr.hwndFrom != app.search_results.list.handle.hwnd()? {
+            return None;
+        }
+
+        match hdr.code {
+            LVN_GETDISPINFOW => {
+                let info = unsafe { &mut *(l as *mut NMLVDISPINFOW) };
+                if info.item.iItem < 0 {
+                    return None;
+                }
+                if info.item.mask & LVIF_IMAGE != 0 && info.item.iSubItem == 0 && !right {
+                    if let Some(category) = app.search_results.category_at(&app.cache, info.item.iItem as usize) {
+                        info.item.iImage = category.icon_index() as i32;
+                    }
+                }
+                if info.item.mask & LVIF_TEXT == 0 || info.item.pszText.is_null() || info.item.cchTextMax <= 0 {
+                    return Some(0);
+                }
+                let (row, col) = (info.item.iItem as usize, info.item.iSubItem as usize);
+                let text = match right {
+                    true => app.dual_pane.cell_text(&app.cache, row, col),
+                    false => app.search_results.cell_text(&app.cache, row, col),
+                }
+                .unwrap_or_default();
+                let wide: Vec<u16> = text
+                    .encode_utf16()
+                    .take(info.item.cchTextMax as usize - 1)
+                    .collect();
+                unsafe {
+                    ptr::copy_nonoverlapping(wide.as_ptr(), info.item.pszText, wide.len());
+                    *info.item.pszText.add(wide.len()) = 0;
+                }
+                Some(0)
+            }
+            LVN_ODCACHEHINT => {
+                let hint = unsafe { &*(l as *const NMLVCACHEHINT) };
+                let (from, to) = (hint.iFrom.max(0) as usize, hint.iTo.max(0) as usize);
+                match right {
+                    true => app.dual_pane.prefetch(&app.cache, from, to),
+                    false => app.search_results.prefetch(&app.cache, from, to),
+                }
+                Some(0)
+            }
+            //Only the selection is of interest, the virtual list has no other item state
+            LVN_ITEMCHANGED => {
+                let change = unsafe { &*(l as *const NMLISTVIEW) };
+                if change.uChanged & LVIF_STATE != 0 && (change.uNewState ^ change.uOldState) & LVIS_SELECTED != 0 {
+                    let rows = (change.iItem >= 0).then(|| change.iItem as usize..=change.iItem as usize);
+                    let selected = change.uNewState & LVIS_SELECTED != 0;
+                    match right {
+                        true => app.dual_pane.selection_changed(rows, selected),
+                        false => {
+                            app.search_results.selection_changed(rows, selected);
+                            app.update_preview();
+                        }
+                    }
+                }
+                None
+            }
+            //Shift ranges of the virtual list only come as one notification
+            LVN_ODSTATECHANGED => {
+                let change = unsafe { &*(l as *const NMLVODSTATECHANGE) };
+                if (change.uNewState ^ change.uOldState) & LVIS_SELECTED != 0 {
+                    let rows = Some(change.iFrom.max(0) as usize..=change.iTo.max(0) as usize);
+                    let selected = change.uNewState & LVIS_SELECTED != 0;
+                    match right {
+                        true => app.dual_pane.selection_changed(rows, selected),
+                        false => app.search_results.selection_changed(rows, selected),
+                    }
+                }
+                None
+            }
+            //Only the left list can edit labels
+            LVN_ENDLABELEDITW if !right => {
+                let info = unsafe { &*(l as *const NMLVDISPINFOW) };
+                //null when the edit was cancelled
+                if info.item.pszText.is_null() || info.item.iItem < 0 {
+                    return Some(0);
+                }
+                let new_name = unsafe {
+                    let len = (0..).take_while(|&i| *info.item.pszText.add(i) != 0).count();
+                    String::from_utf16_lossy(std::slice::from_raw_parts(info.item.pszText, len))
+                };
+                let report = app.search_results.rename_row(
+                    &app.cache,
+                    info.item.iItem as usize,
+                    new_name.trim(),
+                    &mut |c| app.ask_conflict(c),
+                );
+                if let Some(report) = report {
+                    app.record_operation(&report);
+                    let errors = report.errors();
+                    if !errors.is_empty() {
+                        app.display_error(errors.join("\n"));
+                    }
+                    app.dispatch(Action::Refresh);
+                }
+                //the list is virtual, the new name shows up with the refresh
+                Some(0)
+            }
+            _ => None,
+        }
+    })?;
+
+    ui.raw_handlers.borrow_mut().push(handler);
+
+    Ok(())
+}
+
+/// Middle click opens the directory of the row in a tab, like a link in a browser
+pub fn handle_middle_clicks(ui: &mut BasicAppUi) -> Result<(), nwg::NwgError> {
+    let lists = [ui.inner.search_results.list.handle, ui.inner.fav_dir_bar.list.handle];
+    for list in lists {
+        let evt_ui = Rc::downgrade(&ui.inner);
+        let handler = nwg::bind_raw_event_handler(&list, 0x10001, move |hwnd, msg, _w, l| {
+            if msg != WM_MBUTTONUP {
+                return None;
+            }
+            let app = evt_ui.upgrade()?;
+            let mut info: LVHITTESTINFO = unsafe { mem::zeroed() };
+            info.pt = POINT {
+                x: (l & 0xFFFF) as i16 as i32,
+                y: ((l >> 16) & 0xFFFF) as i16 as i32,
+            };
+            let row = unsafe { winuser::SendMessageW(hwnd, LVM_HITTEST, 0, &mut info as *mut _ as LPARAM) };
+            if row < 0 {
+                return None;
+            }
+
+            let path = match list == app.search_results.list.handle {
+                true => app
+                    .search_results
+                    .result_at(&app.cache, row as usize)
+                    .filter(|r| r.file_type.is_dir())?
+                    .full_path,
+                false => app.fav_dir_bar.list.item(row as usize, 1, 260)?.text,
+            };
+            app.open_tab(path, false);
+            Some(0)
+        })?;
+        ui.raw_handlers.borrow_mut().push(handler);
+    }
+
+    Ok(())
+}
+
+fn is_down(key: VirtualKeyCode) -> bool {
+    unsafe { winuser::GetKeyState(key as i32) < 0 }
+}
+
+fn execute_key_action(app: &BasicApp, action: KeyAction) {
+    crate::debug!("Shortcut"; action = action);
+    match action {
+        KeyAction::Back => app.dispatch_active(Action::Back),
+        KeyAction::Up => app.dispatch_active(Action::Up),
+        KeyAction::Refresh => {
+            app.cache.disk_usage.borrow_mut().clear();
+            app.dispatch_active(Action::Refresh);
+        }
+        KeyAction::FocusPathBar => app.header.path_bar.set_editing(true),
+        KeyAction::FocusSearch => app.header.search_input.set_focus(),
+        KeyAction::SearchHelp => app.search_help.open(),
+        KeyAction::Undo => app.undo_operation(false),
+        KeyAction::Redo => app.undo_operation(true),
+        KeyAction::NewTab => app.new_tab(),
+        KeyAction::CloseTab => {
+            let active = app.cache.tabs.borrow().active_index();
+            app.close_tab(active);
+        }
+        KeyAction::NextTab | KeyAction::PreviousTab => {
+            let step = match action {
+                KeyAction::NextTab => 1,
+                _ => -1,
+            };
+            let index = app.cache.tabs.borrow().neighbour(step);
+            app.switch_tab(index);
+        }
+        action => {
+            app.search_results.execute_key_action(action, app);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    fmt::{self, Display},
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::search_engine::result::SearchEngineResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Header line and one quoted row per result
+    Csv,
+    /// One json object per line
+    JsonLines,
+    /// Only the paths, one per line
+    PathList,
+    /// Only the paths, each terminated by a NUL byte like `find -print0`
+    NulPathList,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [
+        ExportFormat::Csv,
+        ExportFormat::JsonLines,
+        ExportFormat::PathList,
+        ExportFormat::NulPathList,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::JsonLines => "JSON Lines",
+            ExportFormat::PathList => "Path list",
+            ExportFormat::NulPathList => "Path list (NUL separated)",
+        }
+    }
+
+    /// Appended to the target if it has none
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::JsonLines => "jsonl",
+            ExportFormat::PathList | ExportFormat::NulPathList => "txt",
+        }
+    }
+
+    /// Names used on the command line: csv, jsonl, lines, nul
+    pub fn from_name(name: &str) -> Option<ExportFormat> {
+        match name.trim().to_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "jsonl" | "json" | "ndjson" => Some(ExportFormat::JsonLines),
+            "lines" | "list" | "txt" => Some(ExportFormat::PathList),
+            "nul" | "null" | "print0" => Some(ExportFormat::NulPathList),
+            _ => None,
+        }
+    }
+
+    /// Guesses from the target file, `None` for unknown extensions
+    pub fn from_path(path: &Path) -> Option<ExportFormat> {
+        let ext = path.extension()?.to_string_lossy().to_lowercase();
+        match ext.as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "jsonl" | "json" | "ndjson" => Some(ExportFormat::JsonLines),
+            "txt" | "lst" => Some(ExportFormat::PathList),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportColumn {
+    Path,
+    Name,
+    Extension,
+    /// Raw bytes
+    Size,
+    /// ISO 8601 in utc
+    Modified,
+    Type,
+}
+
+impl ExportColumn {
+    pub const ALL: [ExportColumn; 6] = [
+        ExportColumn::Path,
+        ExportColumn::Name,
+        ExportColumn::Extension,
+        ExportColumn::Size,
+        ExportColumn::Modified,
+        ExportColumn::Type,
+    ];
+
+    /// Csv header and json key
+    pub fn key(&self) -> &'static str {
+        match self {
+            ExportColumn::Path => "path",
+            ExportColumn::Name => "name",
+            ExportColumn::Extension => "extension",
+            ExportColumn::Size => "size",
+            ExportColumn::Modified => "modified",
+            ExportColumn::Type => "type",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ExportColumn> {
+        let name = name.trim().to_lowercase();
+        ExportColumn::ALL.into_iter().find(|c| c.key() == name)
+    }
+
+    /// Comma separated like `path,size`
+    pub fn parse_list(list: &str) -> Result<Vec<ExportColumn>, String> {
+        list.split(',')
+            .filter(|c| !c.trim().is_empty())
+            .map(|c| ExportColumn::from_name(c).ok_or_else(|| format!("Unknown column '{}'", c.trim())))
+            .collect()
+    }
+
+    /// The value as plain text, e.g. for tables
+    pub fn cell(&self, result: &SearchEngineResult) -> String {
+        match self.value(result) {
+            Value::Text(t) => t,
+            Value::Number(n) => n.to_string(),
+        }
+    }
+
+    fn value(&self, result: &SearchEngineResult) -> Value {
+        match self {
+            ExportColumn::Path => Value::Text(result.full_path.clone()),
+            ExportColumn::Name => Value::Text(result.name.clone()),
+            ExportColumn::Extension => Value::Text(match result.file_type.is_dir() {
+                true => String::new(),
+                false => Path::new(&result.name)
+                    .extension()
+                    .map(|e| e.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            }),
+            ExportColumn::Size => Value::Number(result.size),
+            ExportColumn::Modified => Value::Text(iso_8601(result.modified)),
+            ExportColumn::Type => Value::Text(result.category.label().into()),
+        }
+    }
+}
+
+pub fn default_columns() -> Vec<ExportColumn> {
+    vec![
+        ExportColumn::Path,
+        ExportColumn::Name,
+        ExportColumn::Size,
+        ExportColumn::Modified,
+        ExportColumn::Type,
+    ]
+}
+
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Ignored by the path lists
+    pub columns: Vec<ExportColumn>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            format: ExportFormat::Csv,
+            columns: default_columns(),
+        }
+    }
+}
+
+enum Value {
+    Text(String),
+    Number(u64),
+}
+
+/// Seconds precision in utc, e.g. `2023-11-05T14:03:09Z`
+pub fn iso_8601(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_else(|e| -(e.duration().as_secs() as i64));
+    OffsetDateTime::from_unix_timestamp(secs)
+        .ok()
+        .and_then(|t| t.format(&Rfc3339).ok())
+        .unwrap_or_default()
+}
+
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.into(),
+    }
+}
+
+pub fn write<W: Write>(results: &[SearchEngineResult], options: &ExportOptions, out: &mut W) -> io::Result<()> {
+    match options.format {
+        ExportFormat::Csv => {
+            let header: Vec<&str> = options.columns.iter().map(|c| c.key()).collect();
+            writeln!(out, "{}", header.join(","))?;
+            for r in results {
+                let row: Vec<String> = options
+                    .columns
+                    .iter()
+                    .map(|c| match c.value(r) {
+                        Value::Text(t) => csv_field(&t),
+                        Value::Number(n) => n.to_string(),
+                    })
+                    .collect();
+                writeln!(out, "{}", row.join(","))?;
+            }
+        }
+        ExportFormat::JsonLines => {
+            for r in results {
+                let object: serde_json::Map<String, serde_json::Value> = options
+                    .columns
+                    .iter()
+                    .map(|c| {
+                        let value = match c.value(r) {
+                            Value::Text(t) => serde_json::Value::from(t),
+                            Value::Number(n) => serde_json::Value::from(n),
+                        };
+                        (c.key().to_string(), value)
+                    })
+                    .collect();
+                serde_json::to_writer(&mut *out, &object)?;
+                writeln!(out)?;
+            }
+        }
+        ExportFormat::PathList => {
+            for r in results {
+                writeln!(out, "{}", r.full_path)?;
+            }
+        }
+        ExportFormat::NulPathList => {
+            for r in results {
+                out.write_all(r.full_path.as_bytes())?;
+                out.write_all(&[0])?;
+            }
+        }
+    }
+    out.flush()
+}
+
+/// Creates or overwrites the target
+pub fn write_file(results: &[SearchEngineResult], options: &ExportOptions, target: &Path) -> Result<(), String> {
+    let fail = |e: io::Error| format!("Failed to write {}: {}", target.display(), e);
+    let mut out = BufWriter::new(File::create(target).map_err(fail)?);
+    write(results, options, &mut out).map_err(fail)
+}
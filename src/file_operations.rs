@@ -0,0 +1,757 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::job::Job;
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Characters Windows doesnt allow in file names
+const INVALID_NAME_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// What happens if the target of a copy, move or rename already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    #[default]
+    Ask,
+    Skip,
+    /// Folders are merged, only the files in both are replaced
+    Overwrite,
+    /// Adds a number to the new name, e.g. `file (2).txt`
+    KeepBoth,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeleteMode {
+    /// Recycle bin on Windows, the freedesktop trash elsewhere
+    Trash,
+    Permanent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OperationKind {
+    Copy,
+    Move,
+    Rename,
+    NewFolder,
+    Delete(DeleteMode),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Done,
+    Skipped(String),
+    Failed(String),
+}
+
+/// What happened to a single file or folder of an operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemReport {
+    pub source: String,
+    /// Where the item ended up, `None` for deletions and items that werent touched
+    pub target: Option<String>,
+    pub outcome: Outcome,
+    /// The target existed and was overwritten, the old version is gone
+    pub replaced: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationReport {
+    pub kind: OperationKind,
+    pub items: Vec<ItemReport>,
+    /// Bytes of the copied and moved items
+    pub bytes: u64,
+    pub cancelled: bool,
+}
+
+impl OperationReport {
+    pub(crate) fn new(kind: OperationKind) -> OperationReport {
+        OperationReport {
+            kind,
+            items: Vec::new(),
+            bytes: 0,
+            cancelled: false,
+        }
+    }
+
+    pub(crate) fn push(&mut self, source: &str, target: Option<String>, outcome: Outcome) {
+        self.items.push(ItemReport {
+            source: source.into(),
+            target,
+            outcome,
+            replaced: false,
+        });
+    }
+
+    pub(crate) fn push_replaced(&mut self, source: &str, target: &Path) {
+        self.push(source, Some(target.to_string_lossy().to_string()), Outcome::Done);
+        if let Some(item) = self.items.last_mut() {
+            item.replaced = true;
+        }
+    }
+
+    pub fn done(&self) -> usize {
+        self.items.iter().filter(|i| i.outcome == Outcome::Done).count()
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|i| matches!(i.outcome, Outcome::Skipped(_)))
+            .count()
+    }
+
+    pub fn errors(&self) -> Vec<String> {
+        self.items
+            .iter()
+            .filter_map(|i| match &i.outcome {
+                Outcome::Failed(e) => Some(format!("{}: {}", i.source, e)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for OperationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = match self.kind {
+            OperationKind::Copy => "Copied",
+            OperationKind::Move => "Moved",
+            OperationKind::Rename => "Renamed",
+            OperationKind::NewFolder => "Created",
+            OperationKind::Delete(DeleteMode::Trash) => "Moved to trash",
+            OperationKind::Delete(DeleteMode::Permanent) => "Deleted",
+        };
+        write!(f, "{} {}", verb, self.done())?;
+        if self.skipped() > 0 {
+            write!(f, ", {} skipped", self.skipped())?;
+        }
+        if self.done() + self.skipped() < self.items.len() {
+            write!(f, ", {} failed", self.items.len() - self.done() - self.skipped())?;
+        }
+        if self.cancelled {
+            write!(f, ", cancelled")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    /// Index of the item that is worked on
+    pub item: usize,
+    pub items: usize,
+    pub done: u64,
+    pub total: u64,
+}
+
+/// Decides conflicts while the policy is `Ask`, answering `Ask` again skips the item
+pub type AskFn<'a> = dyn FnMut(&Conflict) -> ConflictPolicy + 'a;
+/// Returning false cancels the remaining work
+pub type ProgressFn<'a> = dyn FnMut(&Progress) -> bool + 'a;
+
+/// Copies files and folders into `target_dir`, folders with everything inside
+pub fn copy(
+    sources: &[String],
+    target_dir: &str,
+    policy: ConflictPolicy,
+    ask: &mut AskFn,
+    progress: &mut ProgressFn,
+) -> OperationReport {
+    transfer(OperationKind::Copy, sources, target_dir, policy, ask, progress)
+}
+
+/// Moves files and folders into `target_dir`. Moves to other drives copy and delete afterwards.
+pub fn move_to(
+    sources: &[String],
+    target_dir: &str,
+    policy: ConflictPolicy,
+    ask: &mut AskFn,
+    progress: &mut ProgressFn,
+) -> OperationReport {
+    transfer(OperationKind::Move, sources, target_dir, policy, ask, progress)
+}
+
+/// Sources whose name already exists in `target_dir`, meant to ask before the operation starts
+pub fn conflicts(sources: &[String], target_dir: &str) -> Vec<Conflict> {
+    sources
+        .iter()
+        .filter_map(|s| {
+            let target = target_path(s, target_dir)?;
+            target.exists().then(|| Conflict {
+                source: s.clone(),
+                target: target.to_string_lossy().to_string(),
+            })
+        })
+        .collect()
+}
+
+fn target_path(source: &str, target_dir: &str) -> Option<PathBuf> {
+    Path::new(source).file_name().map(|n| Path::new(target_dir).join(n))
+}
+
+fn transfer(
+    kind: OperationKind,
+    sources: &[String],
+    target_dir: &str,
+    policy: ConflictPolicy,
+    ask: &mut AskFn,
+    progress: &mut ProgressFn,
+) -> OperationReport {
+    let mut report = OperationReport::new(kind);
+    let sizes: Vec<u64> = sources.iter().map(|s| size_of(Path::new(s))).collect();
+    let mut state = Progress {
+        item: 0,
+        items: sources.len(),
+        done: 0,
+        total: sizes.iter().sum(),
+    };
+
+    for (i, source) in sources.iter().enumerate() {
+        state.item = i;
+        let before = state.done;
+        if report.cancelled {
+            report.push(source, None, Outcome::Skipped("Cancelled".into()));
+            continue;
+        }
+
+        let target = match target_path(source, target_dir) {
+            Some(t) => t,
+            None => {
+                report.push(source, None, Outcome::Failed("Not a file or folder".into()));
+                continue;
+            }
+        };
+        let source_path = Path::new(source);
+        if !source_path.exists() {
+            report.push(source, None, Outcome::Failed("Doesnt exist".into()));
+            continue;
+        }
+        if is_inside(Path::new(target_dir), source_path) {
+            report.push(source, None, Outcome::Failed("A folder cant be put into itself".into()));
+            continue;
+        }
+
+        let (target, replaces) = match resolve_conflict(source, target, policy, ask) {
+            Ok(t) => t,
+            Err(outcome) => {
+                report.push(source, None, outcome);
+                continue;
+            }
+        };
+        //Folders are merged instead, what only the target has stays
+        if replaces && is_folder(source_path) && is_folder(&target) {
+            let result = merge(kind, source_path, &target, &mut state, progress);
+            if result.is_ok() && state.done < before + sizes[i] {
+                state.done = before + sizes[i];
+                progress(&state);
+            }
+            let target_name = Some(target.to_string_lossy().to_string());
+            match result {
+                Ok(()) => report.push_replaced(source, &target),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {
+                    report.cancelled = true;
+                    report.push(source, target_name, Outcome::Skipped("Cancelled".into()));
+                }
+                Err(e) => report.push(source, target_name, Outcome::Failed(e.to_string())),
+            }
+            continue;
+        }
+        //An existing target is only replaced once the new one is complete
+        let destination = match replaces {
+            true => temp_sibling(&target),
+            false => target.clone(),
+        };
+
+        let result = match kind {
+            OperationKind::Move => move_item(source_path, &destination, &mut state, progress),
+            _ => copy_item(source_path, &destination, &mut state, progress),
+        };
+        //Renames on the same drive write nothing, so the size known from the start counts at once
+        if result.is_ok() && state.done < before + sizes[i] {
+            state.done = before + sizes[i];
+            progress(&state);
+        }
+        match result {
+            Ok(()) if replaces => match replace(&destination, &target) {
+                Ok(()) => report.push_replaced(source, &target),
+                //The source of a move is already gone, so the new version is kept
+                Err(e) => report.push(
+                    source,
+                    Some(destination.to_string_lossy().to_string()),
+                    Outcome::Failed(format!("Failed to replace {}: {}", target.display(), e)),
+                ),
+            },
+            Ok(()) => report.push(source, Some(target.to_string_lossy().to_string()), Outcome::Done),
+            Err(e) if is_source_left(&e) => {
+                let copy = match replaces && replace(&destination, &target).is_ok() {
+                    true => target,
+                    false => destination,
+                };
+                report.push(source, Some(copy.to_string_lossy().to_string()), Outcome::Failed(e.to_string()));
+            }
+            Err(e) => {
+                //Removes what was written so far, the source and an existing target are untouched
+                let _ = remove(&destination);
+                match e.kind() {
+                    io::ErrorKind::Interrupted => {
+                        report.cancelled = true;
+                        report.push(source, None, Outcome::Skipped("Cancelled".into()));
+                    }
+                    _ => report.push(source, None, Outcome::Failed(e.to_string())),
+                }
+            }
+        }
+    }
+
+    report.bytes = state.done;
+    report
+}
+
+/// Returns the path to write to and whether it exists and is replaced, or why the item is left alone.
+/// Nothing is removed here, see `replace`.
+fn resolve_conflict(
+    source: &str,
+    target: PathBuf,
+    policy: ConflictPolicy,
+    ask: &mut AskFn,
+) -> Result<(PathBuf, bool), Outcome> {
+    if !target.exists() {
+        return Ok((target, false));
+    }
+
+    let policy = match policy {
+        ConflictPolicy::Ask => ask(&Conflict {
+            source: source.into(),
+            target: target.to_string_lossy().to_string(),
+        }),
+        p => p,
+    };
+
+    match policy {
+        ConflictPolicy::Ask | ConflictPolicy::Skip => Err(Outcome::Skipped("Already exists".into())),
+        ConflictPolicy::KeepBoth => Ok((unique_path(&target), false)),
+        ConflictPolicy::Overwrite if same_file(Path::new(source), &target) => {
+            Err(Outcome::Skipped("Source and target are the same".into()))
+        }
+        ConflictPolicy::Overwrite => Ok((target, true)),
+    }
+}
+
+/// Free hidden name next to `target` to write a replacement to first
+fn temp_sibling(target: &Path) -> PathBuf {
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp = target.with_file_name(format!(".{}.partial", name));
+    match temp.exists() {
+        true => unique_path(&temp),
+        false => temp,
+    }
+}
+
+/// Puts `new` in the place of `target`. The old target is moved aside first and only removed
+/// once `new` took its place, if that fails it is moved back.
+fn replace(new: &Path, target: &Path) -> io::Result<()> {
+    let old = temp_sibling(target);
+    fs::rename(target, &old)?;
+    if let Err(e) = fs::rename(new, target) {
+        let _ = fs::rename(&old, target);
+        return Err(e);
+    }
+    //The new item is in place, a leftover of the old one is no reason to fail
+    let _ = remove(&old);
+    Ok(())
+}
+
+/// `name (2).ext`, `name (3).ext`, ... whatever is free first
+pub fn unique_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    //Folders keep dots in their name as they are
+    let (stem, ext) = match (path.is_dir(), name.rsplit_once('.')) {
+        (false, Some((stem, ext))) if !stem.is_empty() => (stem.to_string(), Some(ext.to_string())),
+        _ => (name, None),
+    };
+
+    (2..)
+        .map(|i| {
+            let name = match &ext {
+                Some(e) => format!("{} ({}).{}", stem, i, e),
+                None => format!("{} ({})", stem, i),
+            };
+            path.with_file_name(name)
+        })
+        .find(|p| !p.exists())
+        .expect("Ran out of numbers")
+}
+
+/// Compares the real paths, so a different case on Windows, `..` or a linked folder dont get around it.
+/// Falls back to the paths as they are if either doesnt exist.
+fn is_inside(dir: &Path, folder: &Path) -> bool {
+    match (fs::canonicalize(dir), fs::canonicalize(folder)) {
+        (Ok(dir), Ok(folder)) => dir.starts_with(folder),
+        _ => dir.starts_with(folder),
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn size_of(path: &Path) -> u64 {
+    match fs::symlink_metadata(path) {
+        Ok(m) if m.is_dir() => fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|e| size_of(&e.path())).sum())
+            .unwrap_or(0),
+        Ok(m) => m.len(),
+        Err(_) => 0,
+    }
+}
+
+/// Links to folders count as files, so they are replaced and not merged
+fn is_folder(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|m| m.is_dir())
+}
+
+/// Puts everything of the folder `source` into the existing folder `target`. Folders in both are merged the same way,
+/// files in both are replaced one by one and whatever only the target has is kept. Moves remove the emptied source.
+fn merge(
+    kind: OperationKind,
+    source: &Path,
+    target: &Path,
+    state: &mut Progress,
+    progress: &mut ProgressFn,
+) -> io::Result<()> {
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let from = entry.path();
+        let to = target.join(entry.file_name());
+        match fs::symlink_metadata(&to) {
+            Ok(m) if m.is_dir() && entry.file_type()?.is_dir() => merge(kind, &from, &to, state, progress)?,
+            //Same as a replaced item, the old one is only removed once the new one is complete
+            Ok(_) => {
+                let temp = temp_sibling(&to);
+                if let Err(e) = put(kind, &from, &temp, state, progress) {
+                    if is_source_left(&e) {
+                        let _ = replace(&temp, &to);
+                    }
+                    return Err(e);
+                }
+                replace(&temp, &to)?;
+            }
+            Err(_) => put(kind, &from, &to, state, progress)?,
+        }
+    }
+
+    match kind {
+        OperationKind::Move => fs::remove_dir(source),
+        _ => Ok(()),
+    }
+}
+
+/// Copies or moves to `target`, which doesnt exist yet. What was written is removed again if it fails,
+/// unless a move copied everything and only the source couldnt be removed.
+fn put(
+    kind: OperationKind,
+    source: &Path,
+    target: &Path,
+    state: &mut Progress,
+    progress: &mut ProgressFn,
+) -> io::Result<()> {
+    let result = match kind {
+        OperationKind::Move => move_item(source, target, state, progress),
+        _ => copy_item(source, target, state, progress),
+    };
+    if let Err(e) = &result {
+        if !is_source_left(e) {
+            let _ = remove(target);
+        }
+    }
+    result
+}
+
+fn copy_item(source: &Path, target: &Path, state: &mut Progress, progress: &mut ProgressFn) -> io::Result<()> {
+    if !fs::metadata(source)?.is_dir() {
+        return copy_file(source, target, state, progress);
+    }
+
+    fs::create_dir(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let child = target.join(entry.file_name());
+        match entry.file_type()? {
+            t if t.is_dir() => copy_item(&entry.path(), &child, state, progress)?,
+            //Linked folders arent followed, they could point back up
+            t if t.is_symlink() && entry.path().is_dir() => {}
+            _ => copy_file(&entry.path(), &child, state, progress)?,
+        }
+    }
+    Ok(())
+}
+
+fn copy_file(source: &Path, target: &Path, state: &mut Progress, progress: &mut ProgressFn) -> io::Result<()> {
+    let mut reader = File::open(source)?;
+    let mut writer = File::create(target)?;
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+
+    let result = loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break Ok(()),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => break Err(e),
+        };
+        if let Err(e) = writer.write_all(&buffer[..read]) {
+            break Err(e);
+        }
+        state.done += read as u64;
+        if !progress(state) {
+            break Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+        }
+    };
+
+    if let Err(e) = result {
+        //Half written files are worth nothing
+        drop(writer);
+        let _ = fs::remove_file(target);
+        return Err(e);
+    }
+    fs::set_permissions(target, reader.metadata()?.permissions())
+}
+
+fn move_item(source: &Path, target: &Path, state: &mut Progress, progress: &mut ProgressFn) -> io::Result<()> {
+    match fs::rename(source, target) {
+        Ok(()) => return Ok(()),
+        //Renaming doesnt work across drives, everything else is a real error
+        Err(e) if crosses_devices(&e) => {}
+        Err(e) => return Err(e),
+    }
+
+    copy_item(source, target, state, progress)?;
+    remove(source).map_err(|e| io::Error::new(e.kind(), SourceLeft(e)))
+}
+
+/// `io::ErrorKind::CrossesDevices` is too new for the Rust version of the CI, so the codes are checked directly
+fn crosses_devices(e: &io::Error) -> bool {
+    //ERROR_NOT_SAME_DEVICE on Windows, EXDEV elsewhere
+    let code = match cfg!(windows) {
+        true => 17,
+        false => 18,
+    };
+    e.raw_os_error() == Some(code)
+}
+
+/// A move between drives copied everything, but the source couldnt be removed afterwards,
+/// at least partly. The copy is complete and must be kept.
+#[derive(Debug)]
+struct SourceLeft(io::Error);
+
+impl fmt::Display for SourceLeft {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Copied, but failed to remove the source: {}", self.0)
+    }
+}
+
+impl std::error::Error for SourceLeft {}
+
+fn is_source_left(e: &io::Error) -> bool {
+    e.get_ref().is_some_and(|e| e.is::<SourceLeft>())
+}
+
+/// Copies a single item to exactly `target`, without any conflict handling
+pub(crate) fn copy_path(source: &Path, target: &Path) -> io::Result<()> {
+    copy_item(source, target, &mut single_progress(), &mut |_| true)
+}
+
+/// Moves or renames a single item to exactly `target`, without any conflict handling
+pub(crate) fn move_path(source: &Path, target: &Path) -> io::Result<()> {
+    move_item(source, target, &mut single_progress(), &mut |_| true)
+}
+
+/// Nobody looks at it, so the size isnt worth walking the item for
+fn single_progress() -> Progress {
+    Progress {
+        item: 0,
+        items: 1,
+        done: 0,
+        total: 0,
+    }
+}
+
+pub(crate) fn remove(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path)?.is_dir() {
+        true => fs::remove_dir_all(path),
+        false => fs::remove_file(path),
+    }
+}
+
+/// Checks a new file or folder name, `None` if it is fine
+pub fn invalid_name_reason(name: &str) -> Option<String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Some("The name cant be empty".into());
+    }
+    if trimmed == "." || trimmed == ".." {
+        return Some(format!("'{}' isnt a valid name", trimmed));
+    }
+    if let Some(c) = name.chars().find(|c| INVALID_NAME_CHARS.contains(c) || c.is_control()) {
+        return Some(format!("The name cant contain '{}'", c));
+    }
+    if name.ends_with('.') || name.ends_with(' ') {
+        return Some("The name cant end with a dot or space".into());
+    }
+    None
+}
+
+pub fn rename(path: &str, new_name: &str, policy: ConflictPolicy, ask: &mut AskFn) -> OperationReport {
+    let mut report = OperationReport::new(OperationKind::Rename);
+    if let Some(reason) = invalid_name_reason(new_name) {
+        report.push(path, None, Outcome::Failed(reason));
+        return report;
+    }
+
+    let source = Path::new(path);
+    let target = source.with_file_name(new_name);
+    //Only changing the case is no conflict, the target is the file itself
+    let case_only = target.to_string_lossy().to_lowercase() == path.to_lowercase()
+        && (!target.exists() || same_file(source, &target));
+    let target = match case_only {
+        true => Ok((target, false)),
+        false => resolve_conflict(path, target, policy, ask),
+    };
+
+    match target {
+        Ok((t, replaces)) => {
+            let result = match replaces {
+                true => replace(source, &t),
+                false => fs::rename(source, &t),
+            };
+            match result {
+                Ok(()) if replaces => report.push_replaced(path, &t),
+                Ok(()) => report.push(path, Some(t.to_string_lossy().to_string()), Outcome::Done),
+                Err(e) => report.push(path, None, Outcome::Failed(e.to_string())),
+            }
+        }
+        Err(outcome) => report.push(path, None, outcome),
+    }
+    report
+}
+
+/// Creates the folder, if the name is taken a number is added
+pub fn new_folder(parent: &str, name: &str) -> OperationReport {
+    let mut report = OperationReport::new(OperationKind::NewFolder);
+    if let Some(reason) = invalid_name_reason(name) {
+        report.push(parent, None, Outcome::Failed(reason));
+        return report;
+    }
+
+    let mut target = Path::new(parent).join(name);
+    if target.exists() {
+        target = unique_path(&target);
+    }
+    match fs::create_dir(&target) {
+        Ok(()) => report.push(parent, Some(target.to_string_lossy().to_string()), Outcome::Done),
+        Err(e) => report.push(parent, None, Outcome::Failed(e.to_string())),
+    }
+    report
+}
+
+pub fn delete(paths: &[String], mode: DeleteMode) -> OperationReport {
+    let mut report = OperationReport::new(OperationKind::Delete(mode));
+
+    for path in paths {
+        let result = match mode {
+            DeleteMode::Permanent => remove(Path::new(path)).map_err(|e| e.to_string()),
+            DeleteMode::Trash => trash::delete(path).map_err(|e| e.to_string()),
+        };
+        match result {
+            Ok(()) => report.push(path, None, Outcome::Done),
+            Err(e) => report.push(path, None, Outcome::Failed(e)),
+        }
+    }
+    report
+}
+
+/// Operations that can run in the background
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    Copy { sources: Vec<String>, target_dir: String },
+    Move { sources: Vec<String>, target_dir: String },
+    Delete { paths: Vec<String>, mode: DeleteMode },
+}
+
+impl Operation {
+    /// `decisions` answers the conflicts that were asked about beforehand, everything else is skipped
+    pub fn run(
+        &self,
+        policy: ConflictPolicy,
+        decisions: &HashMap<String, ConflictPolicy>,
+        progress: &mut ProgressFn,
+    ) -> OperationReport {
+        let mut ask = |c: &Conflict| decisions.get(&c.source).copied().unwrap_or(ConflictPolicy::Skip);
+        match self {
+            Operation::Copy { sources, target_dir } => copy(sources, target_dir, policy, &mut ask, progress),
+            Operation::Move { sources, target_dir } => move_to(sources, target_dir, policy, &mut ask, progress),
+            Operation::Delete { paths, mode } => delete(paths, *mode),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FileEvent {
+    Progress(Progress),
+    Finished(OperationReport),
+}
+
+/// Runs an `Operation` on a background thread
+pub type FileJob = Job<FileEvent>;
+
+impl FileJob {
+    pub fn spawn<F>(
+        operation: Operation,
+        policy: ConflictPolicy,
+        decisions: HashMap<String, ConflictPolicy>,
+        notify: F,
+    ) -> FileJob
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        Job::start(
+            move |reporter| {
+                let mut last_percent = None;
+                let report = operation.run(policy, &decisions, &mut |p| {
+                    //Only report whole percent steps, the ui doesnt need more
+                    let percent = p.done * 100 / p.total.max(1);
+                    if last_percent != Some(percent) {
+                        last_percent = Some(percent);
+                        reporter.send(FileEvent::Progress(p.clone()));
+                    }
+                    !reporter.is_cancelled()
+                });
+
+                reporter.send(FileEvent::Finished(report));
+            },
+            notify,
+        )
+    }
+}
@@ -0,0 +1,153 @@
+use std::{
+    fmt::{self, Display},
+    fs::{File, FileType},
+    io::Read,
+    path::Path,
+};
+
+/// Bytes read from extensionless files to recognize them
+const MAGIC_BYTES: usize = 16;
+
+/// What a result is, shown in the type column. The order is the sort order of the column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FileCategory {
+    Directory,
+    Image,
+    Video,
+    Audio,
+    Archive,
+    Code,
+    Document,
+    Executable,
+    Symlink,
+    /// Any file that isnt recognized
+    Other,
+}
+
+impl FileCategory {
+    /// In sort order, also the order of the icons in the result list
+    pub const ALL: [FileCategory; 10] = [
+        FileCategory::Directory,
+        FileCategory::Image,
+        FileCategory::Video,
+        FileCategory::Audio,
+        FileCategory::Archive,
+        FileCategory::Code,
+        FileCategory::Document,
+        FileCategory::Executable,
+        FileCategory::Symlink,
+        FileCategory::Other,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FileCategory::Directory => "Directory",
+            FileCategory::Image => "Image",
+            FileCategory::Video => "Video",
+            FileCategory::Audio => "Audio",
+            FileCategory::Archive => "Archive",
+            FileCategory::Code => "Code",
+            FileCategory::Document => "Document",
+            FileCategory::Executable => "Executable",
+            FileCategory::Symlink => "Symlink",
+            FileCategory::Other => "File",
+        }
+    }
+
+    /// Case insensitive, also takes the plural like `images` and `folder` for directories
+    pub fn from_name(name: &str) -> Option<FileCategory> {
+        let name = name.trim().to_lowercase();
+        match name.as_str() {
+            "folder" | "folders" | "dir" | "dirs" | "directories" => return Some(FileCategory::Directory),
+            "file" | "files" => return Some(FileCategory::Other),
+            _ => {}
+        }
+        FileCategory::ALL.into_iter().find(|c| {
+            let label = c.label().to_lowercase();
+            name == label || name == format!("{}s", label)
+        })
+    }
+
+    /// Position of the icon in the image list of the result list
+    pub fn icon_index(&self) -> usize {
+        FileCategory::ALL.iter().position(|c| c == self).unwrap_or(0)
+    }
+
+    pub fn from_extension(extension: &str) -> Option<FileCategory> {
+        let category = match extension.to_lowercase().as_str() {
+            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "ico" | "webp" | "tif" | "tiff" | "svg" | "heic" | "raw" | "psd" => {
+                FileCategory::Image
+            }
+            "mp4" | "mkv" | "avi" | "mov" | "wmv" | "webm" | "flv" | "m4v" | "mpg" | "mpeg" | "3gp" => FileCategory::Video,
+            "mp3" | "wav" | "flac" | "ogg" | "m4a" | "aac" | "wma" | "opus" | "mid" | "midi" => FileCategory::Audio,
+            "zip" | "7z" | "rar" | "tar" | "gz" | "tgz" | "bz2" | "xz" | "zst" | "cab" | "iso" | "jar" => {
+                FileCategory::Archive
+            }
+            "rs" | "c" | "h" | "cpp" | "hpp" | "cs" | "java" | "kt" | "py" | "js" | "ts" | "jsx" | "tsx" | "go"
+            | "rb" | "php" | "swift" | "lua" | "sh" | "ps1" | "sql" | "html" | "css" | "scss" | "json" | "toml"
+            | "yaml" | "yml" | "xml" => FileCategory::Code,
+            "txt" | "md" | "pdf" | "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "odt" | "ods" | "odp"
+            | "rtf" | "csv" | "log" | "epub" => FileCategory::Document,
+            "exe" | "msi" | "bat" | "cmd" | "com" | "dll" | "appx" | "msix" => FileCategory::Executable,
+            _ => return None,
+        };
+        Some(category)
+    }
+
+    /// Recognizes common formats by their first bytes
+    pub fn from_magic(head: &[u8]) -> Option<FileCategory> {
+        let at = |offset: usize, magic: &[u8]| head.get(offset..offset + magic.len()) == Some(magic);
+
+        let riff = |kind: &[u8]| at(0, b"RIFF") && at(8, kind);
+
+        let category = if at(0, b"\x89PNG") || at(0, b"\xFF\xD8\xFF") || at(0, b"GIF8") || at(0, b"BM") || riff(b"WEBP") {
+            FileCategory::Image
+        } else if riff(b"WAVE") || at(0, b"ID3") || at(0, b"OggS") || at(0, b"fLaC") || at(4, b"ftypM4A") {
+            FileCategory::Audio
+        } else if riff(b"AVI ") || at(4, b"ftyp") || at(0, b"\x1A\x45\xDF\xA3") {
+            FileCategory::Video
+        } else if at(0, b"PK\x03\x04") || at(0, b"\x1F\x8B") || at(0, b"7z\xBC\xAF\x27\x1C") || at(0, b"Rar!") {
+            FileCategory::Archive
+        } else if at(0, b"%PDF") || at(0, b"{\\rtf") {
+            FileCategory::Document
+        } else if at(0, b"MZ") || at(0, b"\x7FELF") {
+            FileCategory::Executable
+        } else if at(0, b"#!") {
+            FileCategory::Code
+        } else {
+            return None;
+        };
+        Some(category)
+    }
+}
+
+impl Display for FileCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Uses the extension, files without one are recognized by their content
+pub fn classify(path: &Path, file_type: &FileType) -> FileCategory {
+    if file_type.is_dir() {
+        return FileCategory::Directory;
+    } else if file_type.is_symlink() {
+        return FileCategory::Symlink;
+    }
+
+    match path.extension() {
+        Some(ext) => FileCategory::from_extension(&ext.to_string_lossy()),
+        None => read_magic(path).and_then(|head| FileCategory::from_magic(&head)),
+    }
+    .unwrap_or(FileCategory::Other)
+}
+
+fn read_magic(path: &Path) -> Option<Vec<u8>> {
+    let mut head = Vec::with_capacity(MAGIC_BYTES);
+    File::open(path)
+        .ok()?
+        .take(MAGIC_BYTES as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+    Some(head)
+}
@@ -0,0 +1,147 @@
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use time::{
+    format_description::{self, OwnedFormatItem},
+    OffsetDateTime, UtcOffset,
+};
+
+pub const DEFAULT_DATE_PATTERN: &str = "[year]-[month]-[day] [hour]:[minute]";
+
+const BINARY_UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+const SI_UNITS: [&str; 6] = ["B", "kB", "MB", "GB", "TB", "PB"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeUnits {
+    /// Powers of 1024, KiB, MiB, ...
+    #[default]
+    Binary,
+    /// Powers of 1000, kB, MB, ...
+    Si,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateStyle {
+    /// "3 hours ago"
+    Relative,
+    /// A `time` format description, see https://time-rs.github.io/book/api/format-description.html
+    Pattern(String),
+}
+
+impl Default for DateStyle {
+    fn default() -> Self {
+        DateStyle::Pattern(DEFAULT_DATE_PATTERN.into())
+    }
+}
+
+/// Formats sizes and dates for display. The date pattern is parsed and the local offset is looked up once.
+pub struct Formatter {
+    units: SizeUnits,
+    pattern: Option<OwnedFormatItem>,
+    offset: UtcOffset,
+}
+
+impl Formatter {
+    pub fn new(units: SizeUnits, style: &DateStyle) -> Result<Formatter, String> {
+        //Fails on some platforms if more than one thread is running, utc is better than nothing then
+        let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+        Self::with_offset(units, style, offset)
+    }
+
+    pub fn with_offset(units: SizeUnits, style: &DateStyle, offset: UtcOffset) -> Result<Formatter, String> {
+        let pattern = match style {
+            DateStyle::Relative => None,
+            DateStyle::Pattern(p) => Some(
+                format_description::parse_owned::<1>(p)
+                    .map_err(|e| format!("Invalid date format '{}': {}", p, e))?,
+            ),
+        };
+
+        Ok(Formatter {
+            units,
+            pattern,
+            offset,
+        })
+    }
+
+    pub fn size(&self, bytes: u64) -> String {
+        format_size(bytes, self.units)
+    }
+
+    pub fn date(&self, t: SystemTime) -> String {
+        self.date_at(t, SystemTime::now())
+    }
+
+    /// Same as `date` with a fixed point in time for relative dates
+    pub fn date_at(&self, t: SystemTime, now: SystemTime) -> String {
+        match &self.pattern {
+            Some(p) => OffsetDateTime::from(t)
+                .to_offset(self.offset)
+                .format(p)
+                .unwrap_or_default(),
+            None => format_relative(t, now),
+        }
+    }
+}
+
+impl Default for Formatter {
+    fn default() -> Self {
+        Formatter::new(SizeUnits::default(), &DateStyle::default()).unwrap()
+    }
+}
+
+/// Picks the largest unit that keeps the value at or above 1, e.g. `1.5 KiB` or `5.0 GB`
+pub fn format_size(bytes: u64, units: SizeUnits) -> String {
+    let (base, names) = match units {
+        SizeUnits::Binary => (1024.0, BINARY_UNITS),
+        SizeUnits::Si => (1000.0, SI_UNITS),
+    };
+
+    if (bytes as f64) < base {
+        return format!("{} B", bytes);
+    }
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    //Rounding can push a value up to the base, e.g. 1023.97 KiB, so that has to move up as well
+    while unit + 1 < names.len() && round_for_display(value) >= base {
+        value /= base;
+        unit += 1;
+    }
+
+    match value < 100.0 {
+        true => format!("{:.1} {}", value, names[unit]),
+        false => format!("{:.0} {}", value, names[unit]),
+    }
+}
+
+fn round_for_display(value: f64) -> f64 {
+    match value < 100.0 {
+        true => (value * 10.0).round() / 10.0,
+        false => value.round(),
+    }
+}
+
+pub fn format_relative(t: SystemTime, now: SystemTime) -> String {
+    let (secs, future) = match now.duration_since(t) {
+        Ok(d) => (d.as_secs(), false),
+        Err(e) => (e.duration().as_secs(), true),
+    };
+
+    let (amount, unit) = match secs {
+        s if s < 60 => return "just now".into(),
+        s if s < 60 * 60 => (s / 60, "minute"),
+        s if s < 60 * 60 * 24 => (s / (60 * 60), "hour"),
+        s if s < 60 * 60 * 24 * 30 => (s / (60 * 60 * 24), "day"),
+        s if s < 60 * 60 * 24 * 365 => (s / (60 * 60 * 24 * 30), "month"),
+        s => (s / (60 * 60 * 24 * 365), "year"),
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+
+    match future {
+        true => format!("in {} {}{}", amount, unit, plural),
+        false => format!("{} {}{} ago", amount, unit, plural),
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    fmt,
+    fmt::Write as _,
+    fs::{self, File},
+    io::{self, Read},
+};
+
+use lhash::{Md5, Sha1, Sha256, Sha512};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::job::{Job, Reporter};
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HashFunction {
+    MD5,
+    SHA1,
+    SHA256,
+    SHA512,
+    BLAKE3,
+    CRC32,
+    XXH3,
+}
+
+impl HashFunction {
+    pub const ALL: [HashFunction; 7] = [
+        HashFunction::MD5,
+        HashFunction::SHA1,
+        HashFunction::SHA256,
+        HashFunction::SHA512,
+        HashFunction::BLAKE3,
+        HashFunction::CRC32,
+        HashFunction::XXH3,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashFunction::MD5 => "MD5",
+            HashFunction::SHA1 => "SHA1",
+            HashFunction::SHA256 => "SHA256",
+            HashFunction::SHA512 => "SHA512",
+            HashFunction::BLAKE3 => "BLAKE3",
+            HashFunction::CRC32 => "CRC32",
+            HashFunction::XXH3 => "XXH3",
+        }
+    }
+
+    /// Case insensitive counterpart of `name`
+    pub fn from_name(name: &str) -> Option<HashFunction> {
+        Self::ALL
+            .into_iter()
+            .find(|hf| hf.name().eq_ignore_ascii_case(name))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HashError {
+    IsDirectory(String),
+    Io(String, String),
+    Cancelled,
+}
+
+impl fmt::Display for HashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashError::IsDirectory(p) => write!(f, "{} is a directory", p),
+            HashError::Io(p, e) => write!(f, "Failed to hash {}: {}", p, e),
+            HashError::Cancelled => write!(f, "Hashing was cancelled"),
+        }
+    }
+}
+
+/// Running state of one of the hash functions
+enum HashState {
+    Md5(Md5),
+    Sha1(Sha1),
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Blake3(Box<blake3::Hasher>),
+    Crc32(crc32fast::Hasher),
+    Xxh3(Box<Xxh3>),
+}
+
+impl HashState {
+    fn new(hf: HashFunction) -> HashState {
+        match hf {
+            HashFunction::MD5 => HashState::Md5(Md5::new()),
+            HashFunction::SHA1 => HashState::Sha1(Sha1::new()),
+            HashFunction::SHA256 => HashState::Sha256(Sha256::new()),
+            HashFunction::SHA512 => HashState::Sha512(Sha512::new()),
+            HashFunction::BLAKE3 => HashState::Blake3(Box::new(blake3::Hasher::new())),
+            HashFunction::CRC32 => HashState::Crc32(crc32fast::Hasher::new()),
+            HashFunction::XXH3 => HashState::Xxh3(Box::new(Xxh3::new())),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            HashState::Md5(h) => h.update(data),
+            HashState::Sha1(h) => h.update(data),
+            HashState::Sha256(h) => h.update(data),
+            HashState::Sha512(h) => h.update(data),
+            HashState::Blake3(h) => {
+                h.update(data);
+            }
+            HashState::Crc32(h) => h.update(data),
+            HashState::Xxh3(h) => h.update(data),
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        match self {
+            HashState::Md5(mut h) => h.result().to_vec(),
+            HashState::Sha1(mut h) => h.result().to_vec(),
+            HashState::Sha256(mut h) => h.result().to_vec(),
+            HashState::Sha512(mut h) => h.result().to_vec(),
+            HashState::Blake3(h) => h.finalize().as_bytes().to_vec(),
+            HashState::Crc32(h) => h.finalize().to_be_bytes().to_vec(),
+            HashState::Xxh3(h) => h.digest().to_be_bytes().to_vec(),
+        }
+    }
+}
+
+/// Hashes everything the reader returns in chunks, `progress` gets the amount of bytes read so far.
+/// Returning false from `progress` stops the hashing.
+pub fn hash_reader<R, F>(mut reader: R, hf: HashFunction, mut progress: F) -> Result<Vec<u8>, io::Error>
+where
+    R: Read,
+    F: FnMut(u64) -> bool,
+{
+    let mut state = HashState::new(hf);
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut done = 0u64;
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        state.update(&buffer[..read]);
+        done += read as u64;
+        if !progress(done) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+        }
+    }
+
+    Ok(state.finish())
+}
+
+pub fn hash_file<F>(path: &str, hf: HashFunction, progress: F) -> Result<String, HashError>
+where
+    F: FnMut(u64) -> bool,
+{
+    let to_err = |e: io::Error| HashError::Io(path.into(), e.to_string());
+    if fs::metadata(path).map_err(to_err)?.is_dir() {
+        return Err(HashError::IsDirectory(path.into()));
+    }
+
+    let file = File::open(path).map_err(to_err)?;
+    match hash_reader(file, hf, progress) {
+        Ok(hash) => Ok(to_hex(&hash)),
+        Err(e) if e.kind() == io::ErrorKind::Interrupted => Err(HashError::Cancelled),
+        Err(e) => Err(to_err(e)),
+    }
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut acc, &byte| {
+        write!(acc, "{:02X}", byte).expect("Failed to write to String");
+        acc
+    })
+}
+
+#[derive(Debug)]
+pub enum HashEvent {
+    /// Overall progress over all files of the job
+    Progress { file: usize, done: u64, total: u64 },
+    File(String, Result<String, HashError>),
+    Finished,
+}
+
+/// Hashes a list of files on a background thread. `notify` is called from the worker
+/// after every event so the ui thread knows when to `poll`.
+pub type HashJob = Job<HashEvent>;
+
+impl HashJob {
+    pub fn spawn<F>(paths: Vec<String>, hf: HashFunction, notify: F) -> HashJob
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        Self::spawn_each(paths.into_iter().map(|p| (p, hf)).collect(), notify)
+    }
+
+    /// Same as `spawn` with a separate hash function per file
+    pub fn spawn_each<F>(files: Vec<(String, HashFunction)>, notify: F) -> HashJob
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        Job::start(
+            move |reporter| {
+                Self::run(files, reporter);
+                reporter.send(HashEvent::Finished);
+            },
+            notify,
+        )
+    }
+
+    fn run(files: Vec<(String, HashFunction)>, reporter: &Reporter<HashEvent>) {
+        let total: u64 = files
+            .iter()
+            .filter_map(|(p, _)| fs::metadata(p).ok())
+            .filter(|m| m.is_file())
+            .map(|m| m.len())
+            .sum();
+        let mut finished_bytes = 0u64;
+        let mut last_percent = None;
+
+        for (i, (path, hf)) in files.iter().enumerate() {
+            let mut file_bytes = 0;
+            let result = hash_file(path, *hf, |done| {
+                file_bytes = done;
+                //Only report whole percent steps, the ui doesnt need more
+                let percent = (finished_bytes + done) * 100 / total.max(1);
+                if last_percent != Some(percent) {
+                    last_percent = Some(percent);
+                    reporter.send(HashEvent::Progress {
+                        file: i,
+                        done: finished_bytes + done,
+                        total,
+                    });
+                }
+                !reporter.is_cancelled()
+            });
+            finished_bytes += file_bytes;
+
+            let stop = result == Err(HashError::Cancelled);
+            reporter.send(HashEvent::File(path.clone(), result));
+            if stop {
+                return;
+            }
+        }
+    }
+}
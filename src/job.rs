@@ -0,0 +1,89 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+/// Work on a background thread that reports events of type `E`. The ui polls the events
+/// whenever `notify` woke it up. Dropping the job cancels it.
+pub struct Job<E> {
+    receiver: Receiver<E>,
+    cancelled: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// Handed to the work of a job to send its events and to check whether it should stop
+pub struct Reporter<E> {
+    sender: Sender<E>,
+    cancelled: Arc<AtomicBool>,
+    notify: Box<dyn Fn() + Send + Sync>,
+}
+
+impl<E> Reporter<E> {
+    /// Sends the event and wakes the ui, does nothing once the job is gone
+    pub fn send(&self, event: E) {
+        let _ = self.sender.send(event);
+        (self.notify)();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// For work that checks a flag of its own
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.cancelled)
+    }
+}
+
+impl<E: Send + 'static> Job<E> {
+    pub fn start<W, F>(work: W, notify: F) -> Job<E>
+    where
+        W: FnOnce(&Reporter<E>) + Send + 'static,
+        F: Fn() + Send + Sync + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let reporter = Reporter {
+            sender,
+            cancelled: Arc::clone(&cancelled),
+            notify: Box::new(notify),
+        };
+
+        let handle = thread::spawn(move || work(&reporter));
+
+        Job {
+            receiver,
+            cancelled,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl<E> Job<E> {
+    /// Everything that happened since the last call, never blocks
+    pub fn poll(&self) -> Vec<E> {
+        self.receiver.try_iter().collect()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Waits for the worker, mainly for headless use
+    pub fn wait(mut self) -> Vec<E> {
+        if let Some(h) = self.handle.take() {
+            let _ = h.join();
+        }
+        self.poll()
+    }
+}
+
+impl<E> Drop for Job<E> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
@@ -0,0 +1,327 @@
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    file_operations::{self, DeleteMode, OperationKind, OperationReport, Outcome},
+    logging,
+    ring_buffer::RingBuffer,
+};
+
+pub const JOURNAL_FILE: &str = "journal.json";
+/// The ring buffer keeps one slot free, so this allows 49 entries per direction
+pub const JOURNAL_SIZE: usize = 50;
+
+/// Where the gui keeps the journal, next to the session in the data directory
+pub fn journal_path() -> PathBuf {
+    logging::data_dir().join(JOURNAL_FILE)
+}
+
+/// Size and modified date of a file when it was recorded, to notice changes made afterwards
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stamp {
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl Stamp {
+    /// `None` for folders and missing files, folders arent compared
+    pub fn of(path: &str) -> Option<Stamp> {
+        let meta = fs::metadata(path).ok().filter(|m| m.is_file())?;
+        Some(Stamp {
+            len: meta.len(),
+            modified: meta.modified().ok(),
+        })
+    }
+}
+
+/// A single reversible change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    /// Moves and renames
+    Moved { from: String, to: String, stamp: Option<Stamp> },
+    Copied { source: String, copy: String, stamp: Option<Stamp> },
+    CreatedFolder { path: String },
+    /// The item is found in the trash again by its original path
+    Trashed { path: String },
+}
+
+impl Action {
+    /// Why the action cant be undone anymore, `None` if it can
+    fn undo_blocker(&self) -> Option<String> {
+        match self {
+            Action::Moved { from, to, stamp } => changed(to, stamp).or_else(|| occupied_by_other(from, to)),
+            Action::Copied { copy, stamp, .. } => changed(copy, stamp),
+            Action::CreatedFolder { path } => match fs::read_dir(path).map(|mut e| e.next().is_none()) {
+                Ok(true) => None,
+                Ok(false) => Some(format!("{} isnt empty anymore", path)),
+                Err(_) => Some(format!("{} doesnt exist anymore", path)),
+            },
+            Action::Trashed { path } => occupied(path),
+        }
+    }
+
+    fn redo_blocker(&self) -> Option<String> {
+        match self {
+            Action::Moved { from, to, .. } => missing(from).or_else(|| occupied_by_other(to, from)),
+            Action::Copied { source, copy, .. } => missing(source).or_else(|| occupied(copy)),
+            Action::CreatedFolder { path } => occupied(path),
+            Action::Trashed { path } => missing(path),
+        }
+    }
+
+    fn undo(&self) -> Result<(), String> {
+        match self {
+            Action::Moved { from, to, .. } => file_operations::move_path(Path::new(to), Path::new(from))
+                .map_err(|e| format!("Failed to move {} back: {}", to, e)),
+            Action::Copied { copy, .. } => {
+                file_operations::remove(Path::new(copy)).map_err(|e| format!("Failed to remove {}: {}", copy, e))
+            }
+            Action::CreatedFolder { path } => {
+                fs::remove_dir(path).map_err(|e| format!("Failed to remove {}: {}", path, e))
+            }
+            Action::Trashed { path } => restore_from_trash(path),
+        }
+    }
+
+    /// Redoing can change the files, so the stamps are taken again
+    fn redo(&mut self) -> Result<(), String> {
+        match self {
+            Action::Moved { from, to, stamp } => {
+                file_operations::move_path(Path::new(from), Path::new(to))
+                    .map_err(|e| format!("Failed to move {}: {}", from, e))?;
+                *stamp = Stamp::of(to);
+            }
+            Action::Copied { source, copy, stamp } => {
+                file_operations::copy_path(Path::new(source), Path::new(copy))
+                    .map_err(|e| format!("Failed to copy {}: {}", source, e))?;
+                *stamp = Stamp::of(copy);
+            }
+            Action::CreatedFolder { path } => {
+                fs::create_dir(&*path).map_err(|e| format!("Failed to create {}: {}", path, e))?
+            }
+            Action::Trashed { path } => {
+                trash::delete(&*path).map_err(|e| format!("Failed to move {} to the trash: {}", path, e))?
+            }
+        }
+        Ok(())
+    }
+}
+
+fn changed(path: &str, stamp: &Option<Stamp>) -> Option<String> {
+    if !Path::new(path).exists() {
+        return Some(format!("{} doesnt exist anymore", path));
+    }
+    match stamp.is_some() && Stamp::of(path) != *stamp {
+        true => Some(format!("{} was changed in the meantime", path)),
+        false => None,
+    }
+}
+
+fn occupied(path: &str) -> Option<String> {
+    Path::new(path)
+        .exists()
+        .then(|| format!("{} exists again", path))
+}
+
+/// After a case only rename the item itself is found at the other path
+fn occupied_by_other(path: &str, item: &str) -> Option<String> {
+    match path.to_lowercase() == item.to_lowercase() {
+        true => None,
+        false => occupied(path),
+    }
+}
+
+fn missing(path: &str) -> Option<String> {
+    match Path::new(path).exists() {
+        true => None,
+        false => Some(format!("{} doesnt exist anymore", path)),
+    }
+}
+
+/// Restores the most recently trashed item of `path`
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+fn restore_from_trash(path: &str) -> Result<(), String> {
+    let items = trash::os_limited::list().map_err(|e| format!("Failed to read the trash: {}", e))?;
+    let item = items
+        .into_iter()
+        .filter(|i| i.original_parent.join(&i.name) == Path::new(path))
+        .max_by_key(|i| i.time_deleted)
+        .ok_or_else(|| format!("{} isnt in the trash anymore", path))?;
+
+    trash::os_limited::restore_all([item]).map_err(|e| format!("Failed to restore {}: {}", path, e))
+}
+
+#[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+fn restore_from_trash(path: &str) -> Result<(), String> {
+    Err(format!("{} cant be restored from the trash on this system", path))
+}
+
+/// The actions of one operation, undone and redone together
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// e.g. `Moved 3`
+    pub description: String,
+    pub actions: Vec<Action>,
+}
+
+impl JournalEntry {
+    /// Entry for everything the operation finished. Permanent deletes and overwritten files cant be brought back,
+    /// so items that replaced something are left out. Undoing them would only remove the new version as well.
+    /// `None` if nothing is left to undo.
+    pub fn from_report(report: &OperationReport) -> Option<JournalEntry> {
+        let actions: Vec<Action> = report
+            .items
+            .iter()
+            .filter(|i| i.outcome == Outcome::Done && !i.replaced)
+            .filter_map(|i| {
+                let target = i.target.clone();
+                match report.kind {
+                    OperationKind::Copy => target.map(|copy| Action::Copied {
+                        source: i.source.clone(),
+                        stamp: Stamp::of(&copy),
+                        copy,
+                    }),
+                    OperationKind::Move | OperationKind::Rename => target.map(|to| Action::Moved {
+                        from: i.source.clone(),
+                        stamp: Stamp::of(&to),
+                        to,
+                    }),
+                    OperationKind::NewFolder => target.map(|path| Action::CreatedFolder { path }),
+                    OperationKind::Delete(DeleteMode::Trash) => Some(Action::Trashed {
+                        path: i.source.clone(),
+                    }),
+                    OperationKind::Delete(DeleteMode::Permanent) => None,
+                }
+            })
+            .collect();
+
+        (!actions.is_empty()).then(|| JournalEntry {
+            description: report.to_string(),
+            actions,
+        })
+    }
+
+    /// Checks every action first, so nothing is touched if a single one cant be undone.
+    /// If one fails anyway, the error comes with the number of actions from the end that were undone before.
+    fn undo(&self) -> Result<(), (usize, String)> {
+        if let Some(reason) = self.actions.iter().find_map(|a| a.undo_blocker()) {
+            return Err((0, format!("Cant undo '{}': {}", self.description, reason)));
+        }
+        for (done, action) in self.actions.iter().rev().enumerate() {
+            action.undo().map_err(|e| (done, e))?;
+        }
+        Ok(())
+    }
+
+    /// Same as `undo`, the number is counted from the start
+    fn redo(&mut self) -> Result<(), (usize, String)> {
+        if let Some(reason) = self.actions.iter().find_map(|a| a.redo_blocker()) {
+            return Err((0, format!("Cant redo '{}': {}", self.description, reason)));
+        }
+        for (done, action) in self.actions.iter_mut().enumerate() {
+            action.redo().map_err(|e| (done, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Undo and redo stacks of the finished file operations
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Journal {
+    undo: RingBuffer<JournalEntry>,
+    redo: RingBuffer<JournalEntry>,
+}
+
+impl Default for Journal {
+    fn default() -> Self {
+        Journal {
+            undo: RingBuffer::new(JOURNAL_SIZE),
+            redo: RingBuffer::new(JOURNAL_SIZE),
+        }
+    }
+}
+
+impl Journal {
+    /// Starts empty if there is no journal yet or it cant be read
+    pub fn load(path: impl AsRef<Path>) -> Journal {
+        File::open(path)
+            .ok()
+            .and_then(|f| serde_json::from_reader(BufReader::new(f)).ok())
+            .unwrap_or_default()
+    }
+
+    /// Creates the directory of `path` if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to save the journal: {}", e))?;
+        }
+        let file = File::create(path).map_err(|e| format!("Failed to save the journal: {}", e))?;
+        serde_json::to_writer(BufWriter::new(file), self).map_err(|e| format!("Failed to save the journal: {}", e))
+    }
+
+    /// A new operation makes the undone ones unreachable
+    pub fn record(&mut self, entry: JournalEntry) {
+        self.undo.push(entry);
+        self.redo.clear();
+    }
+
+    pub fn undo_description(&self) -> Option<&str> {
+        self.undo.peek().map(|e| e.description.as_str())
+    }
+
+    pub fn redo_description(&self) -> Option<&str> {
+        self.redo.peek().map(|e| e.description.as_str())
+    }
+
+    /// Returns the description of the undone entry. If it fails the actions that were undone until then
+    /// can be redone on their own, the others stay where they were.
+    pub fn undo(&mut self) -> Result<String, String> {
+        let mut entry = self.undo.pop().ok_or("Nothing to undo")?;
+        match entry.undo() {
+            Ok(()) => {
+                let description = entry.description.clone();
+                self.redo.push(entry);
+                Ok(description)
+            }
+            Err((done, e)) => {
+                if done > 0 {
+                    let undone = entry.actions.split_off(entry.actions.len() - done);
+                    self.redo.push(JournalEntry {
+                        description: entry.description.clone(),
+                        actions: undone,
+                    });
+                }
+                self.undo.push(entry);
+                Err(e)
+            }
+        }
+    }
+
+    /// Same as `undo` the other way round
+    pub fn redo(&mut self) -> Result<String, String> {
+        let mut entry = self.redo.pop().ok_or("Nothing to redo")?;
+        match entry.redo() {
+            Ok(()) => {
+                let description = entry.description.clone();
+                self.undo.push(entry);
+                Ok(description)
+            }
+            Err((done, e)) => {
+                if done > 0 {
+                    let rest = entry.actions.split_off(done);
+                    self.undo.push(entry.clone());
+                    entry.actions = rest;
+                }
+                self.redo.push(entry);
+                Err(e)
+            }
+        }
+    }
+}
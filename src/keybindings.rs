@@ -0,0 +1,296 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+pub use crate::win::key_codes::VirtualKeyCode;
+
+/// A key together with the modifiers held down, e.g. `Ctrl+Shift+C`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub key: VirtualKeyCode,
+}
+
+impl KeyChord {
+    pub fn new(key: VirtualKeyCode) -> KeyChord {
+        KeyChord {
+            ctrl: false,
+            shift: false,
+            alt: false,
+            key,
+        }
+    }
+
+    pub fn ctrl(mut self) -> KeyChord {
+        self.ctrl = true;
+        self
+    }
+
+    pub fn shift(mut self) -> KeyChord {
+        self.shift = true;
+        self
+    }
+
+    pub fn alt(mut self) -> KeyChord {
+        self.alt = true;
+        self
+    }
+
+    /// Compares with a key event, `code` is the virtual key code of the event
+    pub fn matches(&self, code: u32, ctrl: bool, shift: bool, alt: bool) -> bool {
+        self.key as u32 == code && self.ctrl == ctrl && self.shift == shift && self.alt == alt
+    }
+
+    /// Modifiers are separated by `+` in any order, the key comes last: `Ctrl+Shift+C`, `alt + left`, `F5`
+    pub fn parse(text: &str) -> Result<KeyChord, String> {
+        let parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let (key, modifiers) = match parts.split_last() {
+            Some((key, modifiers)) if !key.is_empty() => (key, modifiers),
+            _ => return Err(format!("'{}' has no key", text)),
+        };
+
+        let key = VirtualKeyCode::from_name(key).ok_or_else(|| format!("Unknown key '{}' in '{}'", key, text))?;
+        let mut chord = KeyChord::new(key);
+        for modifier in modifiers {
+            let flag = match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => &mut chord.ctrl,
+                "shift" => &mut chord.shift,
+                "alt" => &mut chord.alt,
+                _ => return Err(format!("Unknown modifier '{}' in '{}'", modifier, text)),
+            };
+            if *flag {
+                return Err(format!("'{}' repeats {}", text, modifier));
+            }
+            *flag = true;
+        }
+        Ok(chord)
+    }
+}
+
+impl Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            f.write_str("Ctrl+")?;
+        }
+        if self.shift {
+            f.write_str("Shift+")?;
+        }
+        if self.alt {
+            f.write_str("Alt+")?;
+        }
+        f.write_str(&self.key.name())
+    }
+}
+
+/// Everything a shortcut can trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyAction {
+    Back,
+    Up,
+    Refresh,
+    /// Switches the path bar into editing
+    FocusPathBar,
+    FocusSearch,
+    SearchHelp,
+    Rename,
+    Trash,
+    Delete,
+    CopyPath,
+    SelectAll,
+    InvertSelection,
+    Undo,
+    Redo,
+    NewTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+}
+
+impl KeyAction {
+    pub const ALL: [KeyAction; 18] = [
+        KeyAction::Back,
+        KeyAction::Up,
+        KeyAction::Refresh,
+        KeyAction::FocusPathBar,
+        KeyAction::FocusSearch,
+        KeyAction::SearchHelp,
+        KeyAction::Rename,
+        KeyAction::Trash,
+        KeyAction::Delete,
+        KeyAction::CopyPath,
+        KeyAction::SelectAll,
+        KeyAction::InvertSelection,
+        KeyAction::Undo,
+        KeyAction::Redo,
+        KeyAction::NewTab,
+        KeyAction::CloseTab,
+        KeyAction::NextTab,
+        KeyAction::PreviousTab,
+    ];
+
+    /// Key in the settings
+    pub fn key(&self) -> &'static str {
+        match self {
+            KeyAction::Back => "back",
+            KeyAction::Up => "up",
+            KeyAction::Refresh => "refresh",
+            KeyAction::FocusPathBar => "focus_path_bar",
+            KeyAction::FocusSearch => "focus_search",
+            KeyAction::SearchHelp => "search_help",
+            KeyAction::Rename => "rename",
+            KeyAction::Trash => "trash",
+            KeyAction::Delete => "delete",
+            KeyAction::CopyPath => "copy_path",
+            KeyAction::SelectAll => "select_all",
+            KeyAction::InvertSelection => "invert_selection",
+            KeyAction::Undo => "undo",
+            KeyAction::Redo => "redo",
+            KeyAction::NewTab => "new_tab",
+            KeyAction::CloseTab => "close_tab",
+            KeyAction::NextTab => "next_tab",
+            KeyAction::PreviousTab => "previous_tab",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<KeyAction> {
+        let key = key.trim().to_lowercase();
+        KeyAction::ALL.into_iter().find(|a| a.key() == key)
+    }
+
+    /// Global actions also work while typing in the path bar or the search,
+    /// the others would take keys like Delete or Ctrl+C away from the text
+    pub fn is_global(&self) -> bool {
+        matches!(
+            self,
+            KeyAction::Back
+                | KeyAction::Up
+                | KeyAction::Refresh
+                | KeyAction::FocusPathBar
+                | KeyAction::FocusSearch
+                | KeyAction::SearchHelp
+                | KeyAction::NewTab
+                | KeyAction::CloseTab
+                | KeyAction::NextTab
+                | KeyAction::PreviousTab
+        )
+    }
+
+    fn default_chord(&self) -> KeyChord {
+        use VirtualKeyCode::*;
+        match self {
+            KeyAction::Back => KeyChord::new(VK_LEFT).alt(),
+            KeyAction::Up => KeyChord::new(VK_UP).alt(),
+            KeyAction::Refresh => KeyChord::new(VK_F5),
+            KeyAction::FocusPathBar => KeyChord::new(VK_L).ctrl(),
+            KeyAction::FocusSearch => KeyChord::new(VK_F).ctrl(),
+            KeyAction::SearchHelp => KeyChord::new(VK_F1),
+            KeyAction::Rename => KeyChord::new(VK_F2),
+            KeyAction::Trash => KeyChord::new(VK_DELETE),
+            KeyAction::Delete => KeyChord::new(VK_DELETE).shift(),
+            KeyAction::CopyPath => KeyChord::new(VK_C).ctrl(),
+            KeyAction::SelectAll => KeyChord::new(VK_A).ctrl(),
+            KeyAction::InvertSelection => KeyChord::new(VK_I).ctrl(),
+            KeyAction::Undo => KeyChord::new(VK_Z).ctrl(),
+            KeyAction::Redo => KeyChord::new(VK_Y).ctrl(),
+            KeyAction::NewTab => KeyChord::new(VK_T).ctrl(),
+            KeyAction::CloseTab => KeyChord::new(VK_W).ctrl(),
+            KeyAction::NextTab => KeyChord::new(VK_TAB).ctrl(),
+            KeyAction::PreviousTab => KeyChord::new(VK_TAB).ctrl().shift(),
+        }
+    }
+}
+
+impl Display for KeyAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+/// Which chord runs which action, a chord belongs to one action at most
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(KeyAction, KeyChord)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap {
+            bindings: KeyAction::ALL.iter().map(|a| (*a, a.default_chord())).collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Applies the shortcuts of the settings on top of the defaults, keyed by `KeyAction::key`.
+    /// An empty chord unbinds the action. Conflicts are returned with everything else that went wrong:
+    /// a chord of a default binding moves to the override, between two overrides the later action
+    /// in `KeyAction::ALL` keeps its old shortcut.
+    pub fn with_overrides(overrides: &HashMap<String, String>) -> (Keymap, Vec<String>) {
+        let mut problems = Vec::new();
+        let mut parsed: Vec<(KeyAction, Option<KeyChord>)> = Vec::new();
+        for (key, chord) in overrides {
+            let action = match KeyAction::from_key(key) {
+                Some(a) => a,
+                None => {
+                    problems.push(format!("Unknown shortcut action '{}'", key));
+                    continue;
+                }
+            };
+            match chord.trim().is_empty() {
+                true => parsed.push((action, None)),
+                false => match KeyChord::parse(chord) {
+                    Ok(c) => parsed.push((action, Some(c))),
+                    Err(e) => problems.push(format!("Shortcut of {}: {}", action, e)),
+                },
+            }
+        }
+        //Settings are a map, this keeps the outcome of conflicts the same every start
+        parsed.sort_by_key(|(a, _)| KeyAction::ALL.iter().position(|x| x == a));
+
+        let mut map = Keymap::default();
+        let mut overridden: Vec<(KeyAction, Option<KeyChord>)> = Vec::new();
+        for (action, chord) in parsed {
+            if let Some(chord) = chord {
+                if let Some((other, _)) = overridden.iter().find(|(_, c)| *c == Some(chord)) {
+                    problems.push(format!("{} is used by {} and {}, {} keeps its shortcut", chord, other, action, action));
+                    continue;
+                }
+                let taken = map.bindings.iter().find(|(a, c)| *c == chord && *a != action).map(|(a, _)| *a);
+                if let Some(other) = taken {
+                    if !overrides.contains_key(other.key()) {
+                        problems.push(format!(
+                            "{} is used by {} and {}, {} loses it. Give {} another shortcut or \"\" to keep it unbound",
+                            chord, other, action, other, other
+                        ));
+                    }
+                    map.bindings.retain(|(a, _)| *a != other);
+                }
+            }
+            map.bindings.retain(|(a, _)| *a != action);
+            if let Some(chord) = chord {
+                map.bindings.push((action, chord));
+            }
+            overridden.push((action, chord));
+        }
+        (map, problems)
+    }
+
+    pub fn action(&self, chord: &KeyChord) -> Option<KeyAction> {
+        self.bindings.iter().find(|(_, c)| c == chord).map(|(a, _)| *a)
+    }
+
+    /// The action of a key event, see `KeyChord::matches`
+    pub fn action_for_key(&self, code: u32, ctrl: bool, shift: bool, alt: bool) -> Option<KeyAction> {
+        self.bindings
+            .iter()
+            .find(|(_, c)| c.matches(code, ctrl, shift, alt))
+            .map(|(a, _)| *a)
+    }
+
+    pub fn chord(&self, action: KeyAction) -> Option<KeyChord> {
+        self.bindings.iter().find(|(a, _)| *a == action).map(|(_, c)| *c)
+    }
+}
@@ -1,21 +1,37 @@
+#[cfg(windows)]
 mod app;
+pub mod app_state;
+pub mod assets;
+pub mod breadcrumb;
+pub mod bulk_rename;
+pub mod checksum;
+pub mod cli;
+#[cfg(windows)]
 pub mod components;
+pub mod context_actions;
+pub mod disk_usage;
+pub mod dual_pane;
+pub mod duplicates;
+pub mod export;
+pub mod file_operations;
+pub mod file_types;
+#[cfg(windows)]
 mod event_handler;
-mod memory;
+pub mod formatting;
+pub mod hashing;
+pub mod job;
+pub mod journal;
+pub mod keybindings;
+pub mod logging;
+pub mod memory;
+pub mod preview;
+#[cfg(windows)]
 mod resource_manager;
 pub mod search_engine;
+pub mod selection;
+pub mod tabs;
 mod win;
+#[cfg(windows)]
 mod settings;
 pub mod ring_buffer;
-
-#[macro_export]
-macro_rules! debug {
-    ($($e:expr),+) => {
-        {
-            #[cfg(debug_assertions)]
-            {
-                dbg!($($e),+)
-            }
-        }
-    };
-}
\ No newline at end of file
+pub mod virtual_list;
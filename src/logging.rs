@@ -0,0 +1,333 @@
+use std::{
+    cell::RefCell,
+    env,
+    fmt::{self, Display},
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Mutex,
+    },
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+pub const LOG_FILE: &str = "win-expl.log";
+/// The log is rotated before it grows past this
+pub const DEFAULT_MAX_BYTES: u64 = 1024 * 1024;
+/// Rotated logs that are kept, `win-expl.log.1` is the newest
+pub const DEFAULT_MAX_FILES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    /// Nothing is written
+    Off,
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Level::Off => "OFF",
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Level> {
+        match name.trim().to_lowercase().as_str() {
+            "off" | "none" => Some(Level::Off),
+            "error" => Some(Level::Error),
+            "warn" | "warning" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+
+    fn from_u8(v: u8) -> Level {
+        match v {
+            1 => Level::Error,
+            2 => Level::Warn,
+            3 => Level::Info,
+            4 => Level::Debug,
+            5 => Level::Trace,
+            _ => Level::Off,
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Logs get written once `init` was called, until then everything is dropped
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Off as u8);
+static SINK: Mutex<Option<RotatingFile>> = Mutex::new(None);
+
+thread_local! {
+    /// Spans of the current thread, outermost first
+    static SPANS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Logs into `dir`, replaces the log of an earlier call
+pub fn init(dir: &Path, level: Level) -> Result<(), String> {
+    let file = RotatingFile::open(dir.join(LOG_FILE), DEFAULT_MAX_BYTES, DEFAULT_MAX_FILES)
+        .map_err(|e| format!("Failed to open the log in {}: {}", dir.display(), e))?;
+    if let Ok(mut sink) = SINK.lock() {
+        *sink = Some(file);
+    }
+    set_level(level);
+    Ok(())
+}
+
+pub fn set_level(level: Level) {
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn level() -> Level {
+    Level::from_u8(MAX_LEVEL.load(Ordering::Relaxed))
+}
+
+pub fn enabled(level: Level) -> bool {
+    level != Level::Off && level <= self::level()
+}
+
+/// Used by the macros, which check `enabled` first so the arguments arent formatted for nothing
+pub fn log(level: Level, target: &str, message: fmt::Arguments, fields: &[(&str, &dyn Display)]) {
+    let fields: Vec<(&str, String)> = fields.iter().map(|(k, v)| (*k, v.to_string())).collect();
+    let line = SPANS.with(|spans| {
+        format_line(
+            OffsetDateTime::now_utc(),
+            level,
+            target,
+            &spans.borrow(),
+            &message.to_string(),
+            &fields,
+        )
+    });
+
+    if let Ok(mut sink) = SINK.lock() {
+        if let Some(file) = sink.as_mut() {
+            //There is nowhere left to report it to
+            let _ = file.write_line(&line);
+        }
+    }
+}
+
+/// One line like `2023-11-05T14:03:09.120Z INFO  win_expl::cli: search{query=txt}: Done results=3`
+pub fn format_line(
+    time: OffsetDateTime,
+    level: Level,
+    target: &str,
+    spans: &[String],
+    message: &str,
+    fields: &[(&str, String)],
+) -> String {
+    let mut line = format!(
+        "{}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z {:<5} {}:",
+        time.year(),
+        time.month() as u8,
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second(),
+        time.millisecond(),
+        level.label(),
+        target
+    );
+    if !spans.is_empty() {
+        line.push(' ');
+        line.push_str(&spans.join(":"));
+        line.push(':');
+    }
+    line.push(' ');
+    //Multi line messages would break the one record per line layout
+    line.push_str(&message.replace('\n', "\\n"));
+    for (key, value) in fields {
+        line.push_str(&format!(" {}={}", key, quote(value)));
+    }
+    line
+}
+
+/// Values with spaces or quotes are quoted, so the fields can be split again
+fn quote(value: &str) -> String {
+    match value.is_empty() || value.contains([' ', '"', '=', '\n', '\t']) {
+        true => format!("{:?}", value),
+        false => value.into(),
+    }
+}
+
+/// Every record logged while the span lives carries its name and fields.
+/// Dropping it logs how long it took.
+pub struct Span {
+    target: &'static str,
+    started: Instant,
+}
+
+impl Span {
+    pub fn enter(target: &'static str, name: &str, fields: Vec<(&'static str, String)>) -> Span {
+        let fields: Vec<String> = fields.iter().map(|(k, v)| format!("{}={}", k, quote(v))).collect();
+        let label = match fields.is_empty() {
+            true => name.to_string(),
+            false => format!("{}{{{}}}", name, fields.join(" ")),
+        };
+        SPANS.with(|spans| spans.borrow_mut().push(label));
+        Span { target, started: Instant::now() }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if enabled(Level::Debug) {
+            let elapsed = self.started.elapsed().as_millis();
+            log(Level::Debug, self.target, format_args!("Done"), &[("elapsed_ms", &elapsed)]);
+        }
+        SPANS.with(|spans| spans.borrow_mut().pop());
+    }
+}
+
+/// Appends lines to a file and moves it to `<file>.1` once it is full. Older logs move up one number
+/// and the ones past `max_files` are deleted.
+pub struct RotatingFile {
+    path: PathBuf,
+    //Closed while rotating, Windows cant rename open files
+    file: Option<File>,
+    len: u64,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    pub fn open(path: PathBuf, max_bytes: u64, max_files: usize) -> io::Result<RotatingFile> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let len = file.metadata()?.len();
+        Ok(RotatingFile {
+            path,
+            file: Some(file),
+            len,
+            max_bytes,
+            max_files,
+        })
+    }
+
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let size = line.len() as u64 + 1;
+        if self.len > 0 && self.len + size > self.max_bytes {
+            self.rotate()?;
+        }
+        let file = match self.file.as_mut() {
+            Some(f) => f,
+            None => return Err(io::Error::other("The log is closed")),
+        };
+        writeln!(file, "{}", line)?;
+        self.len += size;
+        Ok(())
+    }
+
+    /// Path of the n-th rotated log, 0 is the current one
+    pub fn rotated_path(&self, n: usize) -> PathBuf {
+        match n {
+            0 => self.path.clone(),
+            n => PathBuf::from(format!("{}.{}", self.path.display(), n)),
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file = None;
+        let _ = fs::remove_file(self.rotated_path(self.max_files));
+        for n in (0..self.max_files).rev() {
+            let from = self.rotated_path(n);
+            if from.exists() {
+                fs::rename(from, self.rotated_path(n + 1))?;
+            }
+        }
+
+        //Without rotated logs the current one is just started over
+        let file = OpenOptions::new().create(true).write(true).truncate(true).open(&self.path)?;
+        self.file = Some(file);
+        self.len = 0;
+        Ok(())
+    }
+}
+
+/// Per user directory of the explorer, `%APPDATA%\win-expl` on Windows
+pub fn data_dir() -> PathBuf {
+    let base = match cfg!(windows) {
+        true => env::var_os("APPDATA").map(PathBuf::from),
+        false => env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".local").join("share"))),
+    };
+    base.unwrap_or_else(|| PathBuf::from(".")).join("win-expl")
+}
+
+pub fn default_log_dir() -> PathBuf {
+    data_dir().join("logs")
+}
+
+/// `log!(Level::Info, "Indexed {}", root; dirs = stats.dirs)`, the key value pairs behind the `;` are optional
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $fmt:literal $(, $arg:expr)* $(; $($key:ident = $value:expr),+)?) => {
+        if $crate::logging::enabled($level) {
+            $crate::logging::log(
+                $level,
+                module_path!(),
+                format_args!($fmt $(, $arg)*),
+                &[$($((stringify!($key), &$value as &dyn ::std::fmt::Display)),+)?],
+            );
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($t:tt)+) => { $crate::log!($crate::logging::Level::Error, $($t)+) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($t:tt)+) => { $crate::log!($crate::logging::Level::Warn, $($t)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($t:tt)+) => { $crate::log!($crate::logging::Level::Info, $($t)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($t:tt)+) => { $crate::log!($crate::logging::Level::Debug, $($t)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($t:tt)+) => { $crate::log!($crate::logging::Level::Trace, $($t)+) };
+}
+
+/// `let _span = span!("search", query = q);` keep the guard alive as long as the span should last
+#[macro_export]
+macro_rules! span {
+    ($name:literal $(, $key:ident = $value:expr)*) => {
+        $crate::logging::Span::enter(module_path!(), $name, vec![$((stringify!($key), $value.to_string())),*])
+    };
+}
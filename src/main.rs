@@ -1,33 +1,60 @@
+//Only the library is of use outside of Windows
+#![cfg_attr(not(windows), allow(dead_code, unused_imports))]
+
+#[cfg(windows)]
 use app::BasicApp;
+#[cfg(windows)]
 use nwg::NativeUi;
 
+#[cfg(windows)]
 mod app;
+pub mod app_state;
+pub mod assets;
+pub mod breadcrumb;
+pub mod bulk_rename;
+pub mod checksum;
+pub mod cli;
+#[cfg(windows)]
 mod components;
+pub mod context_actions;
+pub mod disk_usage;
+pub mod dual_pane;
+pub mod duplicates;
+pub mod export;
+pub mod file_operations;
+pub mod file_types;
+#[cfg(windows)]
 mod event_handler;
-mod memory;
+pub mod formatting;
+pub mod hashing;
+pub mod job;
+pub mod journal;
+pub mod keybindings;
+pub mod logging;
+pub mod memory;
+pub mod preview;
+#[cfg(windows)]
 mod resource_manager;
 pub mod search_engine;
+pub mod selection;
+pub mod tabs;
 mod win;
+#[cfg(windows)]
 mod settings;
 pub mod ring_buffer;
+pub mod virtual_list;
 
 pub struct Test {}
 
-#[macro_export]
-macro_rules! debug {
-    ($($e:expr),+) => {
-        {
-            #[cfg(debug_assertions)]
-            {
-                dbg!($($e),+)
-            }
-        }
-    };
-}
-
+#[cfg(windows)]
 fn main() {
     nwg::init().expect("Failed to init Native Windows GUI");
     nwg::Font::set_global_family("Segoe UI").expect("Failed to set default font");
     let _ui = BasicApp::build_ui(Default::default()).expect("Failed to build UI");
     nwg::dispatch_thread_events();
 }
+
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("The explorer only runs on Windows, the library can be used everywhere");
+}
@@ -1,13 +1,25 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
-use crate::{components::search_result_control::SortDirection, settings::Settings, search_engine::result::SearchEngineResult};
+use crate::{disk_usage::UsageCache, dual_pane::DualPane, duplicates::DuplicateSets, formatting::Formatter, journal::Journal, keybindings::Keymap, settings::Settings, tabs::Tabs};
 
 use super::index::Index;
 
 #[derive(Default)]
 pub struct Cache {
-    pub current_results: RefCell<Vec<SearchEngineResult>>,
-    pub result_sort_direction: RefCell<(usize, SortDirection)>,
+    /// Path, query and results of every tab, the gui renders the active one after every action
+    pub tabs: RefCell<Tabs>,
+    /// State of the second pane, the first one shows the active tab
+    pub dual_pane: RefCell<DualPane>,
     pub settings: RefCell<Settings>,
-    pub index: RefCell<Index>
+    pub index: RefCell<Index>,
+    pub formatter: RefCell<Formatter>,
+    /// Set while the results show duplicate sets, the results are in the same order as the groups
+    pub duplicates: RefCell<DuplicateSets>,
+    pub disk_usage: RefCell<UsageCache>,
+    /// Size of the directory whose disk usage is shown, `None` for normal listings
+    pub usage_total: Cell<Option<u64>>,
+    /// Finished file operations that can be undone
+    pub journal: RefCell<Journal>,
+    /// Defaults with the shortcuts of the settings applied
+    pub keymap: RefCell<Keymap>,
 }
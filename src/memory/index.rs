@@ -1,8 +1,10 @@
-use std::{time::SystemTime, fs, io::BufReader, path::Path, ffi::OsStr};
+use std::{time::SystemTime, fs, io::BufReader, path::MAIN_SEPARATOR};
 
-use serde::{Serialize, Deserialize, de::IntoDeserializer};
+use serde::{Serialize, Deserialize};
 
-use crate::debug;
+pub const INDEX_FILE: &str = "index.json";
+/// Levels of directories below the root that are indexed
+pub const DEFAULT_INDEX_DEPTH: usize = 8;
 
 //TODO
 pub struct Index {
@@ -11,12 +13,21 @@ pub struct Index {
 }
 
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum IndexType {
+    /// The name of the root is its full path
     Dir(String, Vec<IndexType>),
     File(String)
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct IndexStats {
+    pub dirs: usize,
+    pub files: usize,
+    /// Deepest level of directories below the root
+    pub depth: usize,
+}
+
 impl Default for Index {
     fn default() -> Self {
         Self { root: None, modified_date: SystemTime::now() }
@@ -24,11 +35,60 @@ impl Default for Index {
 }
 
 impl Index {
+    /// Walks `root` up to `depth` levels of directories, unreadable ones are indexed as empty
+    pub fn build(root: &str, depth: usize) -> Index {
+        let trimmed = root.trim_end_matches(['\\', '/']);
+        let name = match trimmed.is_empty() {
+            true => root,
+            false => trimmed,
+        };
+        let _span = crate::span!("index", root = name, depth = depth);
+        let mut node = Self::traverse_and_generate(root, depth);
+        if let IndexType::Dir(n, _) = &mut node {
+            *n = name.into();
+        }
+        let index = Index { root: Some(node), modified_date: SystemTime::now() };
+        let stats = index.stats();
+        crate::info!("Indexed"; dirs = stats.dirs, files = stats.files);
+        index
+    }
+
     //TODO refresh partially by using current index
+    #[cfg(windows)]
     pub fn refresh(&mut self, modified_label: &nwg::Label){
-        self.root = Some(Self::traverse_and_generate("C:\\", 8));
-        let _ = fs::write("index.json", serde_json::to_string_pretty(&self.root).unwrap());
-        println!("Done indexing.")
+        *self = Self::build("C:\\", DEFAULT_INDEX_DEPTH);
+        if let Err(e) = self.save(INDEX_FILE) {
+            crate::error!("{}", e);
+        }
+    }
+
+    /// Full path of the indexed directory
+    pub fn root_path(&self) -> Option<&str> {
+        match &self.root {
+            Some(IndexType::Dir(name, _)) => Some(name),
+            _ => None,
+        }
+    }
+
+    pub fn stats(&self) -> IndexStats {
+        fn count(node: &IndexType, level: usize, stats: &mut IndexStats) {
+            match node {
+                IndexType::Dir(_, children) => {
+                    stats.dirs += 1;
+                    stats.depth = stats.depth.max(level);
+                    for c in children {
+                        count(c, level + 1, stats);
+                    }
+                }
+                IndexType::File(_) => stats.files += 1,
+            }
+        }
+
+        let mut stats = IndexStats::default();
+        if let Some(root) = &self.root {
+            count(root, 0, &mut stats);
+        }
+        stats
     }
 
     //Ignores syslink files
@@ -36,23 +96,43 @@ impl Index {
         let mut valid_entries = Vec::new();
         if let Ok(entries) = fs::read_dir(root) {
             for entry in entries.flatten() {
-                if entry.file_type().unwrap().is_dir() {
-                    if depth > 0 {
-                        valid_entries.push(Self::traverse_and_generate(&entry.path().to_string_lossy(), depth-1));
+                let name = entry.file_name().to_string_lossy().to_string();
+                match entry.file_type() {
+                    Ok(t) if t.is_dir() => {
+                        if depth > 0 {
+                            let path = format!("{}{}{}", root.trim_end_matches(['\\', '/']), MAIN_SEPARATOR, name);
+                            let mut child = Self::traverse_and_generate(&path, depth - 1);
+                            if let IndexType::Dir(n, _) = &mut child {
+                                *n = name;
+                            }
+                            valid_entries.push(child);
+                        }
                     }
-                } else {
-                    valid_entries.push(IndexType::File(entry.file_name().into_string().unwrap()));
+                    Ok(_) => valid_entries.push(IndexType::File(name)),
+                    Err(_) => {}
                 }
             }
         }
 
-        IndexType::Dir(Path::new(root).file_stem().unwrap_or(OsStr::new("C:")).to_string_lossy().to_string(), valid_entries)
+        IndexType::Dir(root.into(), valid_entries)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.root).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+    }
+
+    pub fn load(path: &str) -> Result<Index, String> {
+        let f = fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+        let root = serde_json::from_reader(BufReader::new(&f)).map_err(|e| format!("Invalid index file {}: {}", path, e))?;
+        let modified_date = f.metadata().and_then(|m| m.modified()).unwrap_or_else(|_| SystemTime::now());
+        Ok(Index { root, modified_date })
     }
 
     pub fn check_for_updates(&mut self) {
-        if let Ok(f) = fs::File::open("index.json") {
-            self.root = serde_json::from_reader(BufReader::new(&f)).expect("Invalid index file structure");
-            self.modified_date = f.metadata().unwrap().modified().unwrap();
+        match Self::load(INDEX_FILE) {
+            Ok(index) => *self = index,
+            Err(e) => crate::debug!("{}", e),
         }
     }
-}
\ No newline at end of file
+}
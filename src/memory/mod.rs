@@ -1,2 +1,3 @@
+#[cfg(windows)]
 pub mod cache;
 pub mod index;
\ No newline at end of file
@@ -0,0 +1,490 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{self, Display},
+    fs::{self, File},
+    io::{self, BufReader, Read},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use flate2::read::GzDecoder;
+
+use crate::job::Job;
+
+/// Text previews stop after this many bytes
+pub const TEXT_PREVIEW_BYTES: usize = 64 * 1024;
+/// Hex dumps stop after this many bytes
+pub const HEX_PREVIEW_BYTES: usize = 4 * 1024;
+/// Longest side of a thumbnail in pixels
+pub const THUMBNAIL_SIZE: u32 = 256;
+/// Previewers decide based on this many bytes of the file
+const HEAD_BYTES: usize = 512;
+const MAX_ARCHIVE_ENTRIES: usize = 1000;
+const PREVIEW_CACHE_SIZE: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    /// Fallback for everything that isnt valid utf8, most old windows text files are
+    Windows1252,
+}
+
+impl Display for TextEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf8Bom => "UTF-8 BOM",
+            TextEncoding::Utf16Le => "UTF-16 LE",
+            TextEncoding::Utf16Be => "UTF-16 BE",
+            TextEncoding::Windows1252 => "Windows-1252",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+    /// Rows from top to bottom, 4 bytes per pixel in BGRA order like windows bitmaps
+    pub bgra: Vec<u8>,
+}
+
+impl Thumbnail {
+    pub fn from_rgba(img: image::RgbaImage) -> Thumbnail {
+        let (width, height) = img.dimensions();
+        let mut bgra = img.into_raw();
+        for pixel in bgra.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        Thumbnail { width, height, bgra }
+    }
+
+    /// 32 bit bmp file, the only format the ui can load without extra decoders
+    pub fn to_bmp(&self) -> Vec<u8> {
+        let pixels = self.bgra.len() as u32;
+        let mut bmp = Vec::with_capacity(54 + self.bgra.len());
+        //File header
+        bmp.extend_from_slice(b"BM");
+        bmp.extend_from_slice(&(54 + pixels).to_le_bytes());
+        bmp.extend_from_slice(&0u32.to_le_bytes());
+        bmp.extend_from_slice(&54u32.to_le_bytes());
+        //Info header, a negative height means the rows are stored top down
+        bmp.extend_from_slice(&40u32.to_le_bytes());
+        bmp.extend_from_slice(&(self.width as i32).to_le_bytes());
+        bmp.extend_from_slice(&(-(self.height as i32)).to_le_bytes());
+        bmp.extend_from_slice(&1u16.to_le_bytes());
+        bmp.extend_from_slice(&32u16.to_le_bytes());
+        bmp.extend_from_slice(&0u32.to_le_bytes());
+        bmp.extend_from_slice(&pixels.to_le_bytes());
+        bmp.extend_from_slice(&[0; 16]);
+        bmp.extend_from_slice(&self.bgra);
+        bmp
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreviewContent {
+    Text {
+        text: String,
+        encoding: TextEncoding,
+        truncated: bool,
+    },
+    Image {
+        width: u32,
+        height: u32,
+        thumbnail: Thumbnail,
+    },
+    Archive {
+        /// At most `MAX_ARCHIVE_ENTRIES`
+        entries: Vec<ArchiveEntry>,
+        total: usize,
+    },
+    Hex {
+        dump: String,
+        truncated: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preview {
+    pub path: PathBuf,
+    /// Name of the previewer that made it
+    pub previewer: &'static str,
+    pub size: u64,
+    pub modified: SystemTime,
+    pub readonly: bool,
+    pub content: PreviewContent,
+}
+
+/// Turns a file into something the preview pane can show. Previewers are asked in order,
+/// the first that accepts the file and doesnt fail wins.
+pub trait Previewer: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// `head` are the first bytes of the file
+    fn accepts(&self, path: &Path, head: &[u8]) -> bool;
+    fn preview(&self, path: &Path) -> Result<PreviewContent, String>;
+}
+
+pub struct TextPreviewer;
+pub struct ImagePreviewer;
+pub struct ArchivePreviewer;
+/// Accepts everything, has to be the last one
+pub struct HexPreviewer;
+
+/// The registered previewers, the built in ones are asked last
+pub struct Previewers {
+    list: Vec<Box<dyn Previewer>>,
+}
+
+impl Default for Previewers {
+    fn default() -> Self {
+        Previewers {
+            list: vec![
+                Box::new(ImagePreviewer),
+                Box::new(ArchivePreviewer),
+                Box::new(TextPreviewer),
+                Box::new(HexPreviewer),
+            ],
+        }
+    }
+}
+
+impl Previewers {
+    /// Asked before every previewer that is already registered
+    pub fn register(&mut self, previewer: Box<dyn Previewer>) {
+        self.list.insert(0, previewer);
+    }
+
+    pub fn preview(&self, path: &Path) -> Result<Preview, String> {
+        let metadata = fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if metadata.is_dir() {
+            return Err(String::from("Folders have no preview"));
+        }
+        let head = read_head(path, HEAD_BYTES).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+        let mut last_error = None;
+        for previewer in self.list.iter().filter(|p| p.accepts(path, &head)) {
+            match previewer.preview(path) {
+                Ok(content) => {
+                    return Ok(Preview {
+                        path: path.to_path_buf(),
+                        previewer: previewer.name(),
+                        size: metadata.len(),
+                        modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                        readonly: metadata.permissions().readonly(),
+                        content,
+                    })
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| String::from("No preview available")))
+    }
+}
+
+fn read_head(path: &Path, len: usize) -> io::Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(len);
+    File::open(path)?.take(len as u64).read_to_end(&mut head)?;
+    Ok(head)
+}
+
+/// Guesses the encoding from the byte order mark or the bytes themselves, `None` for binary data
+pub fn detect_encoding(bytes: &[u8]) -> Option<TextEncoding> {
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return Some(TextEncoding::Utf8Bom);
+    } else if bytes.starts_with(&[0xFF, 0xFE]) {
+        return Some(TextEncoding::Utf16Le);
+    } else if bytes.starts_with(&[0xFE, 0xFF]) {
+        return Some(TextEncoding::Utf16Be);
+    }
+    if bytes.contains(&0) {
+        return None;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => Some(TextEncoding::Utf8),
+        //Cut off in the middle of a character
+        Err(e) if e.error_len().is_none() => Some(TextEncoding::Utf8),
+        Err(_) => {
+            //Control characters besides whitespace mean it isnt text
+            let control = bytes
+                .iter()
+                .filter(|b| **b < 0x20 && !matches!(**b, b'\n' | b'\r' | b'\t' | 0x0C))
+                .count();
+            (control * 100 <= bytes.len()).then_some(TextEncoding::Windows1252)
+        }
+    }
+}
+
+/// Characters 0x80 to 0x9F of Windows-1252, the rest matches latin1
+const WINDOWS_1252: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}', '\u{90}', '‘', '’',
+    '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+pub fn decode_text(bytes: &[u8], encoding: TextEncoding) -> String {
+    let utf16 = |bytes: &[u8], from: fn([u8; 2]) -> u16| {
+        let units = bytes.get(2..).unwrap_or_default().chunks_exact(2).map(|c| from([c[0], c[1]]));
+        char::decode_utf16(units)
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect()
+    };
+
+    match encoding {
+        TextEncoding::Utf8 => String::from_utf8_lossy(bytes).to_string(),
+        TextEncoding::Utf8Bom => String::from_utf8_lossy(bytes.get(3..).unwrap_or_default()).to_string(),
+        TextEncoding::Utf16Le => utf16(bytes, u16::from_le_bytes),
+        TextEncoding::Utf16Be => utf16(bytes, u16::from_be_bytes),
+        TextEncoding::Windows1252 => bytes
+            .iter()
+            .map(|b| match b {
+                0x80..=0x9F => WINDOWS_1252[(b - 0x80) as usize],
+                _ => *b as char,
+            })
+            .collect(),
+    }
+}
+
+/// Classic `offset  hex  |ascii|` layout with 16 bytes per line
+pub fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(i, line)| {
+            let hex: Vec<String> = line.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = line
+                .iter()
+                .map(|b| match b.is_ascii_graphic() || *b == b' ' {
+                    true => *b as char,
+                    false => '.',
+                })
+                .collect();
+            format!("{:08x}  {:<47}  |{}|", i * 16, hex.join(" "), ascii)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl Previewer for TextPreviewer {
+    fn name(&self) -> &'static str {
+        "Text"
+    }
+
+    fn accepts(&self, _path: &Path, head: &[u8]) -> bool {
+        detect_encoding(head).is_some()
+    }
+
+    fn preview(&self, path: &Path) -> Result<PreviewContent, String> {
+        let bytes = read_head(path, TEXT_PREVIEW_BYTES + 1).map_err(|e| e.to_string())?;
+        let truncated = bytes.len() > TEXT_PREVIEW_BYTES;
+        let bytes = &bytes[..bytes.len().min(TEXT_PREVIEW_BYTES)];
+        let encoding = detect_encoding(bytes).ok_or_else(|| String::from("Not a text file"))?;
+
+        Ok(PreviewContent::Text {
+            text: decode_text(bytes, encoding),
+            encoding,
+            truncated,
+        })
+    }
+}
+
+impl Previewer for ImagePreviewer {
+    fn name(&self) -> &'static str {
+        "Image"
+    }
+
+    fn accepts(&self, _path: &Path, head: &[u8]) -> bool {
+        image::guess_format(head).is_ok()
+    }
+
+    fn preview(&self, path: &Path) -> Result<PreviewContent, String> {
+        let img = image::io::Reader::open(path)
+            .and_then(|r| r.with_guessed_format())
+            .map_err(|e| e.to_string())?
+            .decode()
+            .map_err(|e| e.to_string())?;
+        let thumbnail = Thumbnail::from_rgba(img.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgba8());
+        Ok(PreviewContent::Image {
+            width: img.width(),
+            height: img.height(),
+            thumbnail,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchivePreviewer {
+    fn kind(path: &Path, head: &[u8]) -> Option<ArchiveKind> {
+        let name = path.to_string_lossy().to_lowercase();
+        if head.starts_with(b"PK\x03\x04") || head.starts_with(b"PK\x05\x06") {
+            Some(ArchiveKind::Zip)
+        } else if head.len() >= 262 && &head[257..262] == b"ustar" {
+            Some(ArchiveKind::Tar)
+        } else if head.starts_with(&[0x1F, 0x8B]) && (name.ends_with(".tar.gz") || name.ends_with(".tgz")) {
+            Some(ArchiveKind::TarGz)
+        } else {
+            None
+        }
+    }
+
+    fn list_tar<R: Read>(reader: R) -> Result<(Vec<ArchiveEntry>, usize), String> {
+        let mut archive = tar::Archive::new(reader);
+        let mut entries = Vec::new();
+        let mut total = 0;
+        for entry in archive.entries().map_err(|e| e.to_string())? {
+            let entry = entry.map_err(|e| e.to_string())?;
+            total += 1;
+            if entries.len() < MAX_ARCHIVE_ENTRIES {
+                entries.push(ArchiveEntry {
+                    name: entry.path().map_err(|e| e.to_string())?.to_string_lossy().to_string(),
+                    size: entry.header().size().unwrap_or(0),
+                    is_dir: entry.header().entry_type().is_dir(),
+                });
+            }
+        }
+        Ok((entries, total))
+    }
+}
+
+impl Previewer for ArchivePreviewer {
+    fn name(&self) -> &'static str {
+        "Archive"
+    }
+
+    fn accepts(&self, path: &Path, head: &[u8]) -> bool {
+        Self::kind(path, head).is_some()
+    }
+
+    fn preview(&self, path: &Path) -> Result<PreviewContent, String> {
+        let head = read_head(path, HEAD_BYTES).map_err(|e| e.to_string())?;
+        let file = BufReader::new(File::open(path).map_err(|e| e.to_string())?);
+
+        let (entries, total) = match Self::kind(path, &head) {
+            Some(ArchiveKind::Zip) => {
+                let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+                let mut entries = Vec::new();
+                for i in 0..archive.len().min(MAX_ARCHIVE_ENTRIES) {
+                    let entry = archive.by_index_raw(i).map_err(|e| e.to_string())?;
+                    entries.push(ArchiveEntry {
+                        name: entry.name().to_string(),
+                        size: entry.size(),
+                        is_dir: entry.is_dir(),
+                    });
+                }
+                (entries, archive.len())
+            }
+            Some(ArchiveKind::Tar) => Self::list_tar(file)?,
+            Some(ArchiveKind::TarGz) => Self::list_tar(GzDecoder::new(file))?,
+            None => return Err(String::from("Not an archive")),
+        };
+        Ok(PreviewContent::Archive { entries, total })
+    }
+}
+
+impl Previewer for HexPreviewer {
+    fn name(&self) -> &'static str {
+        "Hex"
+    }
+
+    fn accepts(&self, _path: &Path, _head: &[u8]) -> bool {
+        true
+    }
+
+    fn preview(&self, path: &Path) -> Result<PreviewContent, String> {
+        let bytes = read_head(path, HEX_PREVIEW_BYTES + 1).map_err(|e| e.to_string())?;
+        let truncated = bytes.len() > HEX_PREVIEW_BYTES;
+        Ok(PreviewContent::Hex {
+            dump: hex_dump(&bytes[..bytes.len().min(HEX_PREVIEW_BYTES)]),
+            truncated,
+        })
+    }
+}
+
+/// Finished previews by path, an entry is only used while the file keeps its modified date
+#[derive(Default)]
+pub struct PreviewCache {
+    previews: HashMap<PathBuf, Arc<Preview>>,
+    order: VecDeque<PathBuf>,
+}
+
+impl PreviewCache {
+    pub fn get(&self, path: &Path) -> Option<Arc<Preview>> {
+        let preview = self.previews.get(path)?;
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        (preview.modified == modified).then(|| Arc::clone(preview))
+    }
+
+    /// Drops the oldest preview once the cache is full
+    pub fn insert(&mut self, preview: Arc<Preview>) {
+        if self.previews.insert(preview.path.clone(), Arc::clone(&preview)).is_none() {
+            self.order.push_back(preview.path.clone());
+        }
+        while self.order.len() > PREVIEW_CACHE_SIZE {
+            if let Some(oldest) = self.order.pop_front() {
+                self.previews.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.previews.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.previews.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.previews.clear();
+        self.order.clear();
+    }
+}
+
+/// Makes a single preview on a background thread. There is nothing to cancel,
+/// an outdated job is simply dropped.
+pub struct PreviewJob {
+    pub path: PathBuf,
+    job: Job<Result<Preview, String>>,
+}
+
+impl PreviewJob {
+    pub fn spawn<F>(previewers: Arc<Previewers>, path: PathBuf, notify: F) -> PreviewJob
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let worker_path = path.clone();
+        let job = Job::start(move |reporter| reporter.send(previewers.preview(&worker_path)), notify);
+
+        PreviewJob { path, job }
+    }
+
+    /// The preview once it is done, never blocks
+    pub fn poll(&self) -> Option<Result<Preview, String>> {
+        self.job.poll().pop()
+    }
+
+    /// Waits for the preview, mainly for headless use
+    pub fn wait(self) -> Result<Preview, String> {
+        self.job
+            .wait()
+            .pop()
+            .unwrap_or_else(|| Err(String::from("The preview stopped unexpectedly")))
+    }
+}
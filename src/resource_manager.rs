@@ -1,37 +1,35 @@
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use nwg::Bitmap;
 
-pub struct Resources {
-    resource_paths: HashMap<ResourceType, String>
-}
+pub use crate::assets::ResourceType;
+use crate::assets::AssetStore;
 
-#[derive(Eq, PartialEq, Hash)]
-pub enum ResourceType {
-    ArrowLeft,
-    ArrowUp,
-    Refresh,
-    Copy,
+/// Bitmaps are built on first use and shared afterwards
+pub struct Resources {
+    assets: AssetStore,
+    bitmaps: RefCell<HashMap<ResourceType, Rc<Bitmap>>>,
 }
 
 impl Resources {
-    //TODO: add caching later on to only need whats necessary at first
     pub fn new() -> Resources {
-        let mut data = HashMap::new();
-
-        data.insert(ResourceType::ArrowLeft, "./assets/arrow-left.png".into());
-        data.insert(ResourceType::ArrowUp, "./assets/arrow-up.png".into());
-        data.insert(ResourceType::Refresh, "./assets/refresh.png".into());
-        data.insert(ResourceType::Copy, "./assets/copy.png".into());
-
         Resources {
-            resource_paths: data
+            assets: AssetStore::with_default_theme(),
+            bitmaps: RefCell::default(),
         }
     }
 
-    pub fn get_bitmap(&self, t: ResourceType) -> Result<Bitmap, nwg::NwgError> {
-        let path = self.resource_paths.get(&t).unwrap();
-        Bitmap::from_file(path, false)
+    pub fn get_bitmap(&self, t: ResourceType) -> Result<Rc<Bitmap>, nwg::NwgError> {
+        if let Some(b) = self.bitmaps.borrow().get(&t) {
+            return Ok(Rc::clone(b));
+        }
+
+        let bmp = self.assets.image(t).to_bmp();
+        let mut bitmap = Bitmap::default();
+        Bitmap::builder().source_bin(Some(&bmp)).build(&mut bitmap)?;
+        let bitmap = Rc::new(bitmap);
+        self.bitmaps.borrow_mut().insert(t, Rc::clone(&bitmap));
+        Ok(bitmap)
     }
 }
 
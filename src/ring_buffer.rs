@@ -1,4 +1,9 @@
-#[derive(Clone)]
+use serde::{Deserialize, Serialize};
+
+/// Only the size and the items are saved, the buffer is built anew on load.
+/// That way a file that was edited by hand cant leave the indices in a state `push` or `pop` would panic in.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "SavedRingBuffer<T>", into = "SavedRingBuffer<T>")]
 pub struct RingBuffer<T> where T: Clone {
     buffer: Vec<Option<T>>,
     size: usize,
@@ -38,10 +43,63 @@ impl<T: std::clone::Clone> RingBuffer<T> {
     pub fn all_read(&self) -> bool {
         self.read_index == self.write_index
     }
+
+    /// The item `pop` would return, without removing it
+    pub fn peek(&self) -> Option<&T> {
+        if self.all_read() {
+            None
+        } else {
+            self.buffer[self.write_index].as_ref()
+        }
+    }
+
+    /// The items `pop` would return, oldest first
+    pub fn to_vec(&self) -> Vec<T> {
+        let mut items = Vec::new();
+        let mut index = self.read_index;
+        while index != self.write_index {
+            index = (index + 1) % self.size;
+            items.extend(self.buffer[index].clone());
+        }
+        items
+    }
+
+    pub fn clear(&mut self) {
+        *self = RingBuffer::new(self.size);
+    }
 }
 
 impl Default for RingBuffer<String> {
     fn default() -> Self {
         RingBuffer::new(10)
     }
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedRingBuffer<T> {
+    size: usize,
+    /// Oldest first
+    items: Vec<T>,
+}
+
+impl<T: Clone> TryFrom<SavedRingBuffer<T>> for RingBuffer<T> {
+    type Error = String;
+
+    fn try_from(saved: SavedRingBuffer<T>) -> Result<Self, Self::Error> {
+        if saved.size == 0 {
+            return Err(String::from("A ring buffer needs a size"));
+        }
+        let mut buffer = RingBuffer::new(saved.size);
+        saved.items.into_iter().for_each(|item| buffer.push(item));
+        Ok(buffer)
+    }
+}
+
+impl<T: Clone> From<RingBuffer<T>> for SavedRingBuffer<T> {
+    fn from(buffer: RingBuffer<T>) -> Self {
+        SavedRingBuffer {
+            size: buffer.size,
+            items: buffer.to_vec(),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    fmt, fs,
+    io::{self, ErrorKind},
+};
+
+use super::{
+    result::SearchEngineResult,
+    sort::{self, SortOrder},
+};
+
+/// Lists the direct content of a single directory. Used for browsing, searching goes through `SearchEngine`.
+pub struct DirectoryListing {}
+
+#[derive(Debug, Clone, Default)]
+pub struct ListingOptions {
+    pub show_hidden: bool,
+    pub sort: SortOrder,
+}
+
+#[derive(Debug, Default)]
+pub struct Listing {
+    pub entries: Vec<SearchEngineResult>,
+    /// Amount of entries left out because they are hidden
+    pub hidden_count: usize,
+    /// Entries that couldnt be read, e.g. locked or removed while listing
+    pub errors: Vec<(String, String)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ListingError {
+    NotFound(String),
+    PermissionDenied(String),
+    NotADirectory(String),
+    Other(String, String),
+}
+
+impl fmt::Display for ListingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListingError::NotFound(p) => write!(f, "Directory {} does not exist", p),
+            ListingError::PermissionDenied(p) => write!(f, "Access to {} denied", p),
+            ListingError::NotADirectory(p) => write!(f, "{} is not a directory", p),
+            ListingError::Other(p, e) => write!(f, "Failed to list {}: {}", p, e),
+        }
+    }
+}
+
+impl ListingError {
+    fn from_io(path: &str, e: io::Error) -> ListingError {
+        match e.kind() {
+            ErrorKind::NotFound => ListingError::NotFound(path.into()),
+            ErrorKind::PermissionDenied => ListingError::PermissionDenied(path.into()),
+            _ => ListingError::Other(path.into(), e.to_string()),
+        }
+    }
+}
+
+impl DirectoryListing {
+    pub fn list(path: &str, options: &ListingOptions) -> Result<Listing, ListingError> {
+        let meta = fs::metadata(path).map_err(|e| ListingError::from_io(path, e))?;
+        if !meta.is_dir() {
+            return Err(ListingError::NotADirectory(path.into()));
+        }
+
+        let entries = fs::read_dir(path).map_err(|e| ListingError::from_io(path, e))?;
+        let mut listing = Listing::default();
+
+        for entry in entries {
+            let entry = match entry {
+                Ok(e) => e,
+                Err(e) => {
+                    listing.errors.push((path.into(), e.to_string()));
+                    continue;
+                }
+            };
+
+            if !options.show_hidden && is_hidden(&entry) {
+                listing.hidden_count += 1;
+                continue;
+            }
+
+            match SearchEngineResult::read(&entry) {
+                Ok(r) => listing.entries.push(r),
+                Err(e) => listing
+                    .errors
+                    .push((entry.path().to_string_lossy().into(), e.to_string())),
+            }
+        }
+
+        listing.entries = sort::sort_results(listing.entries, &options.sort);
+
+        Ok(listing)
+    }
+}
+
+#[cfg(windows)]
+fn is_hidden(entry: &fs::DirEntry) -> bool {
+    use crate::win::file_attributes::FileAttributes;
+    use std::os::windows::fs::MetadataExt;
+
+    entry
+        .metadata()
+        .map(|m| m.file_attributes() & FileAttributes::FILE_ATTRIBUTE_HIDDEN as u32 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(windows))]
+fn is_hidden(entry: &fs::DirEntry) -> bool {
+    entry.file_name().to_string_lossy().starts_with('.')
+}
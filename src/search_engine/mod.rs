@@ -1,11 +1,14 @@
 use std::fs::{self};
 
-use crate::search_engine::parameter_parser::SearchEngineParameter;
+use crate::{file_types, search_engine::parameter_parser::SearchEngineParameter};
 
 use self::result::SearchEngineResult;
 
+pub mod directory_listing;
 pub mod parameter_parser;
 pub mod result;
+pub mod search_help;
+pub mod sort;
 
 #[derive(Default, Clone)]
 pub struct SearchEngine {}
@@ -35,6 +38,11 @@ impl SearchEngine {
                             None => true,
                         };
 
+                        is_ok &= match &p.category {
+                            Some(c) => e.file_type().map(|t| file_types::classify(&e.path(), &t) == *c).unwrap_or(false),
+                            None => true,
+                        };
+
                         if is_ok {
                             Some(SearchEngineResult::from(&e))
                         } else {
@@ -1,12 +1,18 @@
-use std::{fs::DirEntry, os::windows::fs::MetadataExt};
+use std::fs::DirEntry;
 
-use crate::win::file_attributes::FileAttributes;
+use crate::{
+    file_types::FileCategory,
+    win::file_attributes::{self, FileAttributes},
+};
 
-#[derive(Debug, Clone)]
+use super::search_help::SearchHelp;
+
+#[derive(Debug, Clone, Default)]
 pub struct SearchEngineParameter {
     pub depth: usize,
     pub term: Option<String>,
     pub extension: Option<String>,
+    pub category: Option<FileCategory>,
     pub search_readonly_dirs: bool,
     pub search_hidden_dirs: bool,
     pub search_bin_dirs: bool,
@@ -18,13 +24,21 @@ pub struct SearchEngineParameter {
 impl SearchEngineParameter {
     #[allow(clippy::all)]
     pub fn parse_search_term(t: &str) -> Result<SearchEngineParameter, ()> {
+        Self::parse_with(t, &SearchHelp::default())
+    }
+
+    /// Applies the filters registered in `help`, unknown or broken ones are skipped. `SearchHelp::validate` explains them.
+    #[allow(clippy::all)]
+    pub fn parse_with(t: &str, help: &SearchHelp) -> Result<SearchEngineParameter, ()> {
         let parts: Vec<String> = t.split('!').map(|s| s.to_string()).collect();
 
-        let file_args: Vec<String> = parts.first().unwrap().split('.').map(|s| s.to_string()).collect();
+        let (term, keys) = help.split_keys(parts.first().unwrap());
+        let file_args: Vec<String> = term.split('.').map(|s| s.to_string()).collect();
 
         let mut params = SearchEngineParameter {
             term: None,
             extension: None,
+            category: None,
             depth: 0,
             search_readonly_dirs: false,
             search_hidden_dirs: false,
@@ -34,7 +48,7 @@ impl SearchEngineParameter {
             search_windows_folder: false,
         };
 
-        if parts.first().unwrap().contains('.') {
+        if term.contains('.') {
             if file_args.len() > 1 {
                 params.term = Some(file_args[0].clone());
                 params.extension = Some(file_args[1].clone());
@@ -51,24 +65,13 @@ impl SearchEngineParameter {
             }
         }
 
+        for (filter, value) in keys {
+            let _ = (filter.apply)(&mut params, value);
+        }
+
         for pa in parts.iter().skip(1) {
-            let p = pa.trim();
-            if p.starts_with('d') {
-                if let Ok(d) = p.replace('d', "").parse::<usize>() {
-                    params.depth = d;
-                }
-            } else if p.eq("r") {
-                params.search_readonly_dirs = true;
-            } else if p.eq("h") {
-                params.search_hidden_dirs = true;
-            } else if p.eq("b") {
-                params.search_bin_dirs = true;
-            } else if p.eq("l") {
-                params.search_lib_dirs = true;
-            } else if p.eq("t") {
-                params.search_tmp_dirs = true;
-            } else if p.eq("w") {
-                params.search_windows_folder = true;
+            if let Some((filter, value)) = help.find_flag(pa.trim()) {
+                let _ = (filter.apply)(&mut params, value);
             }
         }
 
@@ -76,12 +79,17 @@ impl SearchEngineParameter {
     }
 
     pub fn dir_can_be_searched(dir: &DirEntry, p: &SearchEngineParameter) -> bool {
-        let mut can_be = true;
-
-        let meta_attr = dir.metadata().unwrap().file_attributes();
+        let meta_attr = file_attributes::attributes_of(&dir.metadata().unwrap());
         let binding = dir.file_name();
         let name = binding.to_str().unwrap();
 
+        Self::name_can_be_searched(name, meta_attr, p)
+    }
+
+    /// Same as `dir_can_be_searched` for directories that are only known by name and attributes
+    pub fn name_can_be_searched(name: &str, meta_attr: u32, p: &SearchEngineParameter) -> bool {
+        let mut can_be = true;
+
         if !p.search_readonly_dirs {
             let read_only = meta_attr & FileAttributes::FILE_ATTRIBUTE_READONLY as u32;
             can_be &= read_only == 0;
@@ -1,4 +1,6 @@
-use std::{time::SystemTime, fs::{FileType, DirEntry}, os::windows::fs::MetadataExt};
+use std::{time::SystemTime, fs::{self, FileType, DirEntry}, io, path::Path};
+
+use crate::file_types::{self, FileCategory};
 
 #[derive(Clone, Debug)]
 pub struct SearchEngineResult {
@@ -7,18 +9,46 @@ pub struct SearchEngineResult {
     pub file_type: FileType,
     pub size: u64,
     pub full_path: String,
+    /// Classified once when the result is read, files without an extension need their first bytes read
+    pub category: FileCategory,
 }
 
-impl From<&DirEntry> for SearchEngineResult {
-    fn from(value: &DirEntry) -> Self {
-        let metadata = value.metadata().unwrap();
+impl SearchEngineResult {
+    /// Same as `from` but hands back io errors instead of panicking, e.g. on entries that vanished or are locked
+    pub fn read(value: &DirEntry) -> io::Result<SearchEngineResult> {
+        let metadata = value.metadata()?;
+        let path = value.path();
+
+        Ok(SearchEngineResult {
+            name: value.file_name().to_string_lossy().into(),
+            modified: metadata.modified()?,
+            file_type: metadata.file_type(),
+            size: metadata.len(),
+            category: file_types::classify(&path, &metadata.file_type()),
+            full_path: path.to_string_lossy().into(),
+        })
+    }
+
+    /// For results that dont come from a directory listing, e.g. the files of a duplicate set
+    pub fn from_path(path: &str) -> io::Result<SearchEngineResult> {
+        let metadata = fs::metadata(path)?;
 
-        SearchEngineResult {
-            name: value.file_name().to_str().unwrap().into(),
-            modified: metadata.modified().unwrap(),
+        Ok(SearchEngineResult {
+            name: Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().into())
+                .unwrap_or_else(|| path.into()),
+            modified: metadata.modified()?,
             file_type: metadata.file_type(),
-            size: metadata.file_size(),
-            full_path: value.path().to_str().unwrap().into(),
-        }
+            size: metadata.len(),
+            category: file_types::classify(Path::new(path), &metadata.file_type()),
+            full_path: path.into(),
+        })
     }
-}
\ No newline at end of file
+}
+
+impl From<&DirEntry> for SearchEngineResult {
+    fn from(value: &DirEntry) -> Self {
+        SearchEngineResult::read(value).unwrap()
+    }
+}
@@ -0,0 +1,271 @@
+use crate::file_types::FileCategory;
+
+use super::parameter_parser::SearchEngineParameter;
+
+/// Sets the parameter from the text behind the filter name, e.g. `2` of `!d2`
+pub type ApplyFn = fn(&mut SearchEngineParameter, &str) -> Result<(), String>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterKind {
+    /// `!name` behind the search term
+    Flag,
+    /// `name:value` inside of the search term, file names cant contain a colon so they dont clash
+    Key,
+}
+
+/// A filter or flag of the search input together with its documentation
+#[derive(Debug, Clone)]
+pub struct SearchFilter {
+    pub name: &'static str,
+    pub kind: FilterKind,
+    /// What the user types, e.g. `!d<depth>`
+    pub syntax: &'static str,
+    pub description: &'static str,
+    pub example: &'static str,
+    /// Whether text follows the name like the depth of `!d2`
+    pub takes_value: bool,
+    pub apply: ApplyFn,
+}
+
+impl SearchFilter {
+    /// `!name` or `name:`
+    pub fn token(&self) -> String {
+        match self.kind {
+            FilterKind::Flag => format!("!{}", self.name),
+            FilterKind::Key => format!("{}:", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    /// The whole search input after accepting the suggestion
+    pub text: String,
+    pub label: String,
+}
+
+/// Every filter the search input understands. The parser, the help dialog, the suggestions
+/// and the validation all read from here, so a new filter only has to be registered.
+#[derive(Debug, Clone)]
+pub struct SearchHelp {
+    filters: Vec<SearchFilter>,
+}
+
+impl Default for SearchHelp {
+    fn default() -> Self {
+        let mut help = SearchHelp::empty();
+        for filter in builtin_filters() {
+            help.register(filter);
+        }
+        help
+    }
+}
+
+impl SearchHelp {
+    pub fn empty() -> SearchHelp {
+        SearchHelp { filters: Vec::new() }
+    }
+
+    /// Replaces a filter with the same token
+    pub fn register(&mut self, filter: SearchFilter) {
+        self.filters.retain(|f| f.token() != filter.token());
+        self.filters.push(filter);
+    }
+
+    /// In registration order
+    pub fn filters(&self) -> &[SearchFilter] {
+        &self.filters
+    }
+
+    /// The flag of a `!` part without the `!` and the text behind its name
+    pub fn find_flag<'a>(&self, part: &'a str) -> Option<(&SearchFilter, &'a str)> {
+        self.filters
+            .iter()
+            .filter(|f| f.kind == FilterKind::Flag)
+            .find_map(|f| match f.takes_value {
+                true => part.strip_prefix(f.name).map(|value| (f, value)),
+                false => part.eq(f.name).then_some((f, "")),
+            })
+    }
+
+    /// The filter of a `key:value` word and its value, `None` if the word isnt one or the key is unknown
+    pub fn find_key<'a>(&self, word: &'a str) -> Option<(&SearchFilter, &'a str)> {
+        let (key, value) = word.split_once(':')?;
+        self.filters
+            .iter()
+            .find(|f| f.kind == FilterKind::Key && f.name.eq_ignore_ascii_case(key))
+            .map(|f| (f, value))
+    }
+
+    /// Takes the known `key:value` words out of the search term, the rest stays as it was
+    pub fn split_keys<'a>(&self, term: &'a str) -> (String, Vec<(&SearchFilter, &'a str)>) {
+        let mut keys = Vec::new();
+        let mut rest = Vec::new();
+        for word in term.split(' ') {
+            match self.find_key(word) {
+                Some(key) => keys.push(key),
+                None => rest.push(word),
+            }
+        }
+        (rest.join(" "), keys)
+    }
+
+    /// Explains the first problem of the query. Incomplete input like a trailing `!` is fine.
+    pub fn validate(&self, query: &str) -> Result<(), String> {
+        let mut scratch = SearchEngineParameter::default();
+        let mut parts = query.split('!');
+
+        for word in parts.next().unwrap_or_default().split_whitespace().filter(|w| w.contains(':')) {
+            let (filter, value) = self
+                .find_key(word)
+                .ok_or_else(|| format!("Unknown filter {}, press F1 for help", word))?;
+            (filter.apply)(&mut scratch, value).map_err(|e| format!("{}: {}", filter.syntax, e))?;
+        }
+
+        for part in parts.map(str::trim).filter(|p| !p.is_empty()) {
+            let (filter, value) = self
+                .find_flag(part)
+                .ok_or_else(|| format!("Unknown flag !{}, press F1 for help", part))?;
+            (filter.apply)(&mut scratch, value).map_err(|e| format!("{}: {}", filter.syntax, e))?;
+        }
+
+        SearchEngineParameter::parse_with(query, self)
+            .map(|_| ())
+            .map_err(|_| String::from("The extension behind the dot is missing"))
+    }
+
+    /// Completions for the flag or key the user is typing at the end of the query.
+    /// Flags show up after a `!`, keys once the word contains a colon.
+    pub fn suggestions(&self, query: &str, max: usize) -> Vec<Suggestion> {
+        let suggestion = |f: &SearchFilter, text: String| Suggestion {
+            text,
+            label: format!("{}  {}, e.g. {}", f.syntax, f.description, f.example),
+        };
+
+        if let Some(pos) = query.rfind('!') {
+            let typed = &query[pos + 1..];
+            if typed.contains(char::is_whitespace) {
+                return Vec::new();
+            }
+            let used: Vec<&str> = query[..pos].split('!').skip(1).map(str::trim).collect();
+            return self
+                .filters
+                .iter()
+                .filter(|f| f.kind == FilterKind::Flag && f.name.starts_with(typed))
+                //Complete flags without a value are done already
+                .filter(|f| f.takes_value || !(used.contains(&f.name) || f.name == typed))
+                .map(|f| suggestion(f, format!("{}!{}", &query[..pos], f.name)))
+                .take(max)
+                .collect();
+        }
+
+        let word = query.rsplit(char::is_whitespace).next().unwrap_or_default();
+        let (key, value) = match word.split_once(':') {
+            Some(k) => k,
+            None => return Vec::new(),
+        };
+        let before = &query[..query.len() - word.len()];
+        let keys = self.filters.iter().filter(|f| f.kind == FilterKind::Key);
+        let matching: Vec<&SearchFilter> = keys.clone().filter(|f| f.name.eq_ignore_ascii_case(key)).collect();
+
+        match matching.is_empty() {
+            //Unknown key, shows every key to pick from
+            true => keys.map(|f| suggestion(f, format!("{}{}", before, f.token()))).take(max).collect(),
+            false => matching
+                .into_iter()
+                .map(|f| suggestion(f, format!("{}{}{}", before, f.token(), value)))
+                .take(max)
+                .collect(),
+        }
+    }
+}
+
+fn parse_depth(p: &mut SearchEngineParameter, value: &str) -> Result<(), String> {
+    p.depth = value
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| String::from("The depth has to be a number"))?;
+    Ok(())
+}
+
+fn builtin_filters() -> Vec<SearchFilter> {
+    let flag = |name, syntax, description, example, apply: ApplyFn| SearchFilter {
+        name,
+        kind: FilterKind::Flag,
+        syntax,
+        description,
+        example,
+        takes_value: false,
+        apply,
+    };
+
+    vec![
+        SearchFilter {
+            takes_value: true,
+            ..flag("d", "!d<depth>", "Also searches this many folders deep", "report!d2", parse_depth)
+        },
+        flag("r", "!r", "Also searches read only folders", ".txt!d3!r", |p, _| {
+            p.search_readonly_dirs = true;
+            Ok(())
+        }),
+        flag("h", "!h", "Also searches hidden folders", ".txt!d3!h", |p, _| {
+            p.search_hidden_dirs = true;
+            Ok(())
+        }),
+        flag("b", "!b", "Also searches bin folders", ".dll!d3!b", |p, _| {
+            p.search_bin_dirs = true;
+            Ok(())
+        }),
+        flag("l", "!l", "Also searches lib folders", ".so!d3!l", |p, _| {
+            p.search_lib_dirs = true;
+            Ok(())
+        }),
+        flag("t", "!t", "Also searches tmp folders", ".log!d3!t", |p, _| {
+            p.search_tmp_dirs = true;
+            Ok(())
+        }),
+        flag("w", "!w", "Also searches windows folders", "notepad!d2!w", |p, _| {
+            p.search_windows_folder = true;
+            Ok(())
+        }),
+        SearchFilter {
+            name: "ext",
+            kind: FilterKind::Key,
+            syntax: "ext:<extension>",
+            description: "Only files with this extension, same as name.extension",
+            example: "report ext:pdf",
+            takes_value: true,
+            apply: |p, value| match value.trim_start_matches('.') {
+                "" => Err(String::from("The extension is missing")),
+                ext => {
+                    p.extension = Some(ext.to_string());
+                    Ok(())
+                }
+            },
+        },
+        SearchFilter {
+            name: "type",
+            kind: FilterKind::Key,
+            syntax: "type:<category>",
+            description: "Only image, video, audio, archive, code, document, executable, folder or file",
+            example: "holiday type:image",
+            takes_value: true,
+            apply: |p, value| match FileCategory::from_name(value) {
+                Some(c) => {
+                    p.category = Some(c);
+                    Ok(())
+                }
+                None => Err(format!("{} isnt a known type", value)),
+            },
+        },
+        SearchFilter {
+            name: "depth",
+            kind: FilterKind::Key,
+            syntax: "depth:<depth>",
+            description: "Also searches this many folders deep, same as !d",
+            example: "report depth:2",
+            takes_value: true,
+            apply: parse_depth,
+        },
+    ]
+}
@@ -0,0 +1,231 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use super::result::SearchEngineResult;
+
+/// Amount of columns kept in a sort order, older keys drop out
+const MAX_SORT_COLUMNS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SortKey {
+    Name,
+    Modified,
+    Type,
+    Size,
+    Extension,
+    Path,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SortColumn {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+/// Keys are compared in order, the next one only breaks ties of the previous.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SortOrder {
+    pub folders_first: bool,
+    pub columns: Vec<SortColumn>,
+}
+
+impl Default for SortOrder {
+    fn default() -> Self {
+        SortOrder {
+            folders_first: true,
+            columns: vec![SortColumn {
+                key: SortKey::Name,
+                descending: false,
+            }],
+        }
+    }
+}
+
+impl SortKey {
+    /// Maps the columns of the result list, see `SearchResultControl`
+    pub fn from_column(col_index: usize) -> Option<SortKey> {
+        match col_index {
+            0 => Some(SortKey::Name),
+            1 => Some(SortKey::Modified),
+            2 => Some(SortKey::Type),
+            3 => Some(SortKey::Size),
+            4 => Some(SortKey::Path),
+            _ => None,
+        }
+    }
+}
+
+impl SortOrder {
+    /// Same behaviour as clicking on a column header. The current primary key flips its direction,
+    /// any other key becomes the primary key in ascending order and the old keys break ties.
+    pub fn toggle(&mut self, key: SortKey) {
+        if let Some(first) = self.columns.first_mut() {
+            if first.key == key {
+                first.descending = !first.descending;
+                return;
+            }
+        }
+
+        self.columns.retain(|c| c.key != key);
+        self.columns.insert(
+            0,
+            SortColumn {
+                key,
+                descending: false,
+            },
+        );
+        self.columns.truncate(MAX_SORT_COLUMNS);
+    }
+
+    pub fn primary(&self) -> Option<SortColumn> {
+        self.columns.first().copied()
+    }
+}
+
+/// Values that are expensive to compute are prepared once per result instead of once per comparison
+struct PreparedKeys {
+    is_dir: bool,
+    name: Vec<NaturalChunk>,
+    extension: String,
+    path: Vec<NaturalChunk>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum NaturalChunk {
+    /// Digits without leading zeros and the amount of removed zeros
+    Number(String, usize),
+    /// Case folded text and the original text for a stable tie break
+    Text(String, String),
+}
+
+pub fn sort_results(results: Vec<SearchEngineResult>, order: &SortOrder) -> Vec<SearchEngineResult> {
+    let mut prepared: Vec<(PreparedKeys, SearchEngineResult)> = results
+        .into_iter()
+        .map(|r| (PreparedKeys::from(&r), r))
+        .collect();
+
+    prepared.sort_by(|(ka, a), (kb, b)| {
+        let mut ord = match order.folders_first {
+            true => kb.is_dir.cmp(&ka.is_dir),
+            false => Ordering::Equal,
+        };
+
+        for col in order.columns.iter() {
+            if ord != Ordering::Equal {
+                break;
+            }
+            ord = compare(col.key, ka, a, kb, b);
+            if col.descending {
+                ord = ord.reverse();
+            }
+        }
+
+        ord
+    });
+
+    prepared.into_iter().map(|(_, r)| r).collect()
+}
+
+fn compare(
+    key: SortKey,
+    ka: &PreparedKeys,
+    a: &SearchEngineResult,
+    kb: &PreparedKeys,
+    b: &SearchEngineResult,
+) -> Ordering {
+    match key {
+        SortKey::Name => compare_natural(&ka.name, &kb.name),
+        SortKey::Modified => a.modified.cmp(&b.modified),
+        SortKey::Type => a.category.cmp(&b.category),
+        SortKey::Size => a.size.cmp(&b.size),
+        SortKey::Extension => ka.extension.cmp(&kb.extension),
+        SortKey::Path => compare_natural(&ka.path, &kb.path),
+    }
+}
+
+impl From<&SearchEngineResult> for PreparedKeys {
+    fn from(r: &SearchEngineResult) -> Self {
+        let extension = match r.file_type.is_dir() {
+            true => String::new(),
+            false => r
+                .name
+                .rsplit_once('.')
+                .map(|(_, ext)| ext.to_lowercase())
+                .unwrap_or_default(),
+        };
+
+        PreparedKeys {
+            is_dir: r.file_type.is_dir(),
+            name: natural_chunks(&r.name),
+            extension,
+            path: natural_chunks(&r.full_path),
+        }
+    }
+}
+
+/// Splits `file10.txt` into `file`, `10` and `.txt` so numbers can be compared by value.
+/// Text is folded with the unicode lower case mapping, this is not a full locale collation.
+fn natural_chunks(s: &str) -> Vec<NaturalChunk> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut in_number = false;
+
+    for c in s.chars() {
+        let is_digit = c.is_ascii_digit();
+        if is_digit != in_number && !current.is_empty() {
+            chunks.push(NaturalChunk::new(std::mem::take(&mut current), in_number));
+        }
+        in_number = is_digit;
+        current.push(c);
+    }
+    if !current.is_empty() {
+        chunks.push(NaturalChunk::new(current, in_number));
+    }
+
+    chunks
+}
+
+impl NaturalChunk {
+    fn new(s: String, is_number: bool) -> NaturalChunk {
+        if is_number {
+            let trimmed = s.trim_start_matches('0');
+            let zeros = s.len() - trimmed.len();
+            NaturalChunk::Number(trimmed.into(), zeros)
+        } else {
+            NaturalChunk::Text(s.to_lowercase(), s)
+        }
+    }
+}
+
+fn compare_natural(a: &[NaturalChunk], b: &[NaturalChunk]) -> Ordering {
+    //Folded values decide first, the originals only if everything else is equal
+    let mut tie_break = Ordering::Equal;
+
+    for (ca, cb) in a.iter().zip(b.iter()) {
+        let ord = match (ca, cb) {
+            (NaturalChunk::Number(na, za), NaturalChunk::Number(nb, zb)) => {
+                let ord = na.len().cmp(&nb.len()).then_with(|| na.cmp(nb));
+                if tie_break == Ordering::Equal {
+                    tie_break = zb.cmp(za);
+                }
+                ord
+            }
+            (NaturalChunk::Text(fa, oa), NaturalChunk::Text(fb, ob)) => {
+                if tie_break == Ordering::Equal {
+                    tie_break = oa.cmp(ob);
+                }
+                fa.cmp(fb)
+            }
+            //Numbers before text, like the explorer does
+            (NaturalChunk::Number(..), NaturalChunk::Text(..)) => Ordering::Less,
+            (NaturalChunk::Text(..), NaturalChunk::Number(..)) => Ordering::Greater,
+        };
+
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+
+    a.len().cmp(&b.len()).then(tie_break)
+}
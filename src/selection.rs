@@ -0,0 +1,79 @@
+use std::{collections::BTreeSet, ops::RangeInclusive};
+
+/// Selected rows of a list. Ctrl and shift clicks are handled by the list view itself,
+/// its change notifications are mirrored here so actions dont have to ask the control.
+/// Shift ranges of virtual lists arrive as a single range notification.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    rows: BTreeSet<usize>,
+    row_count: usize,
+}
+
+impl Selection {
+    /// Has to be called whenever the rows change, the old rows mean nothing anymore
+    pub fn reset(&mut self, row_count: usize) {
+        self.rows.clear();
+        self.row_count = row_count;
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.row_count
+    }
+
+    /// Number of selected rows
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn contains(&self, row: usize) -> bool {
+        self.rows.contains(&row)
+    }
+
+    /// Ascending
+    pub fn rows(&self) -> Vec<usize> {
+        self.rows.iter().copied().collect()
+    }
+
+    /// The rows an action on `row` applies to, the whole selection if the row is part of it
+    pub fn rows_for(&self, row: usize) -> Vec<usize> {
+        match self.contains(row) {
+            true => self.rows(),
+            false => vec![row],
+        }
+    }
+
+    pub fn set(&mut self, row: usize, selected: bool) {
+        self.set_range(row..=row, selected);
+    }
+
+    /// Rows past the end are ignored
+    pub fn set_range(&mut self, rows: RangeInclusive<usize>, selected: bool) {
+        let end = (*rows.end()).min(self.row_count.saturating_sub(1));
+        if self.row_count == 0 || *rows.start() > end {
+            return;
+        }
+        for row in *rows.start()..=end {
+            match selected {
+                true => self.rows.insert(row),
+                false => self.rows.remove(&row),
+            };
+        }
+    }
+
+    pub fn select_all(&mut self) {
+        self.set_range(0..=usize::MAX, true);
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+
+    /// Selects everything that wasnt selected and the other way around
+    pub fn invert(&mut self) {
+        self.rows = (0..self.row_count).filter(|r| !self.rows.contains(r)).collect();
+    }
+}
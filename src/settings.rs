@@ -1,10 +1,41 @@
-use std::{fs::File, io::{BufReader, BufWriter}};
+use std::{collections::HashMap, fs::{self, File}, io::{BufReader, BufWriter}, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    export::{self, ExportColumn},
+    file_operations::ConflictPolicy,
+    formatting::{DateStyle, SizeUnits},
+    logging::{self, Level},
+};
+
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// Where the settings are kept, in the data directory next to the session and the journal
+pub fn settings_path() -> PathBuf {
+    logging::data_dir().join(SETTINGS_FILE)
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct Settings {
-    pub favorite_folders: Vec<FavoriteFolder>
+    pub favorite_folders: Vec<FavoriteFolder>,
+    #[serde(default)]
+    pub show_hidden_files: bool,
+    #[serde(default)]
+    pub size_units: SizeUnits,
+    #[serde(default)]
+    pub date_format: DateStyle,
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
+    /// Columns of csv and json exports
+    #[serde(default = "export::default_columns")]
+    pub export_columns: Vec<ExportColumn>,
+    /// Least important records written to the log, `off` disables it
+    #[serde(default)]
+    pub log_level: Level,
+    /// Shortcuts that differ from the defaults, e.g. `"refresh": "Ctrl+R"`. An empty one unbinds the action.
+    #[serde(default)]
+    pub keybindings: HashMap<String, String>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -15,14 +46,17 @@ struct FavoriteFolder {
 }
 
 impl Default for Settings {
+    /// Until they are saved for the first time, the settings of the working directory are taken over
     fn default() -> Self {
-        serde_json::from_reader(BufReader::new(File::open("settings.json").unwrap())).unwrap()
+        let file = File::open(settings_path()).or_else(|_| File::open(SETTINGS_FILE)).unwrap();
+        serde_json::from_reader(BufReader::new(file)).unwrap()
     }
 }
 
 impl Settings {
     pub fn save_to_file(&self){
-        let file = File::create("settings.json").unwrap();
+        fs::create_dir_all(logging::data_dir()).unwrap();
+        let file = File::create(settings_path()).unwrap();
         serde_json::to_writer_pretty(BufWriter::new(file), self).unwrap();
     }
     
@@ -30,4 +64,9 @@ impl Settings {
         self.favorite_folders.push(FavoriteFolder { name, path });
         self.save_to_file();
     }
+
+    pub fn set_show_hidden_files(&mut self, show: bool) {
+        self.show_hidden_files = show;
+        self.save_to_file();
+    }
 }
\ No newline at end of file
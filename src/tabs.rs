@@ -0,0 +1,132 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_state::{AppState, SavedTab},
+    logging,
+};
+
+pub const SESSION_FILE: &str = "session.json";
+
+/// Where the gui keeps the session, in the data directory so it doesnt depend on the working directory
+pub fn session_path() -> PathBuf {
+    logging::data_dir().join(SESSION_FILE)
+}
+
+/// Every tab browses on its own, only the active one is shown
+pub struct Tabs {
+    tabs: Vec<AppState>,
+    active: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Session {
+    tabs: Vec<SavedTab>,
+    active: usize,
+}
+
+impl Default for Tabs {
+    fn default() -> Self {
+        Tabs {
+            tabs: vec![AppState::default()],
+            active: 0,
+        }
+    }
+}
+
+impl Tabs {
+    pub fn active(&self) -> &AppState {
+        &self.tabs[self.active]
+    }
+
+    pub fn active_mut(&mut self) -> &mut AppState {
+        &mut self.tabs[self.active]
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    pub fn count(&self) -> usize {
+        self.tabs.len()
+    }
+
+    pub fn titles(&self) -> Vec<String> {
+        self.tabs.iter().map(|t| t.title()).collect()
+    }
+
+    /// Inserts the tab right after the active one without switching to it, returns its index
+    pub fn open(&mut self, state: AppState) -> usize {
+        let index = self.active + 1;
+        self.tabs.insert(index, state);
+        index
+    }
+
+    /// The last tab cant be closed. The tab to the right becomes active if the active one is closed.
+    pub fn close(&mut self, index: usize) -> bool {
+        if self.tabs.len() < 2 || index >= self.tabs.len() {
+            return false;
+        }
+        self.tabs.remove(index);
+        if index < self.active || self.active == self.tabs.len() {
+            self.active -= 1;
+        }
+        true
+    }
+
+    pub fn switch(&mut self, index: usize) -> bool {
+        if index >= self.tabs.len() || index == self.active {
+            return false;
+        }
+        self.active = index;
+        true
+    }
+
+    /// Index `step` tabs away from the active one, wraps around at both ends
+    pub fn neighbour(&self, step: isize) -> usize {
+        let count = self.tabs.len() as isize;
+        (self.active as isize + step).rem_euclid(count) as usize
+    }
+
+    /// Creates the directory of `path` if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let session = Session {
+            tabs: self.tabs.iter().map(|t| t.saved()).collect(),
+            active: self.active,
+        };
+        let json = serde_json::to_string_pretty(&session).map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// The tabs come back without results, see `AppState::from_saved`
+    pub fn load(path: impl AsRef<Path>) -> Result<Tabs, String> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let session: Session =
+            serde_json::from_str(&json).map_err(|e| format!("Invalid session file {}: {}", path.display(), e))?;
+        //Tabs without a path only get in the way, the active one keeps its place among the rest
+        let mut tabs = Vec::new();
+        let mut active = 0;
+        for (i, saved) in session.tabs.into_iter().enumerate() {
+            if saved.path.trim().is_empty() {
+                continue;
+            }
+            if i <= session.active {
+                active = tabs.len();
+            }
+            tabs.push(AppState::from_saved(saved));
+        }
+        if tabs.is_empty() {
+            return Ok(Tabs::default());
+        }
+        Ok(Tabs { tabs, active })
+    }
+}
@@ -0,0 +1,78 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    ops::Range,
+    rc::Rc,
+};
+
+/// Rows are only formatted when the view asks for them. The data itself isnt owned,
+/// it is passed on every call so the results dont have to be cloned into the view.
+pub struct VirtualList<R> {
+    rows: RefCell<HashMap<usize, Rc<R>>>,
+    capacity: usize,
+    len: Cell<usize>,
+}
+
+impl<R> VirtualList<R> {
+    pub fn new(capacity: usize) -> VirtualList<R> {
+        VirtualList {
+            rows: RefCell::new(HashMap::new()),
+            capacity,
+            len: Cell::new(0),
+        }
+    }
+
+    /// Has to be called whenever the underlying data changes
+    pub fn reset(&self, len: usize) {
+        self.rows.borrow_mut().clear();
+        self.len.set(len);
+    }
+
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len.get() == 0
+    }
+
+    pub fn cached_rows(&self) -> usize {
+        self.rows.borrow().len()
+    }
+
+    pub fn row<T, F>(&self, data: &[T], index: usize, format: F) -> Option<Rc<R>>
+    where
+        F: Fn(&T) -> R,
+    {
+        if index >= self.len.get() {
+            return None;
+        }
+        if let Some(r) = self.rows.borrow().get(&index) {
+            return Some(Rc::clone(r));
+        }
+
+        let row = Rc::new(format(data.get(index)?));
+        let mut rows = self.rows.borrow_mut();
+        //The view only ever shows a few screens, anything older can be formatted again
+        if rows.len() >= self.capacity {
+            rows.clear();
+        }
+        rows.insert(index, Rc::clone(&row));
+
+        Some(row)
+    }
+
+    /// Rows of the range that exist, out of bounds indices are cut off
+    pub fn rows<T, F>(&self, data: &[T], range: Range<usize>, format: F) -> Vec<Rc<R>>
+    where
+        F: Fn(&T) -> R,
+    {
+        range.map_while(|i| self.row(data, i, &format)).collect()
+    }
+}
+
+impl<R> Default for VirtualList<R> {
+    fn default() -> Self {
+        VirtualList::new(500)
+    }
+}
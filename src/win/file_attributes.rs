@@ -1,3 +1,5 @@
+use std::fs::Metadata;
+
 #[allow(non_camel_case_types)]
 #[allow(dead_code)]
 #[derive(Debug, PartialEq)]
@@ -30,3 +32,16 @@ pub enum FileAttributes {
     FILE_ATTRIBUTE_VALID_FLAGS = 0x007FFFFF,
     FILE_ATTRIBUTE_VALID_SET_FLAGS = 0x0063FFFF,
 }
+
+/// The attribute bits of a file, outside of Windows there are none
+#[cfg(windows)]
+pub fn attributes_of(metadata: &Metadata) -> u32 {
+    use std::os::windows::fs::MetadataExt;
+
+    metadata.file_attributes()
+}
+
+#[cfg(not(windows))]
+pub fn attributes_of(_metadata: &Metadata) -> u32 {
+    0
+}
@@ -1,4 +1,4 @@
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[allow(non_camel_case_types)]
 #[allow(dead_code)]
 pub enum VirtualKeyCode {
@@ -195,3 +195,129 @@ pub enum VirtualKeyCode {
     VK_PA1 = 0xFD,
     VK_OEM_CLEAR = 0xFE,
 }
+
+impl VirtualKeyCode {
+    /// Names like `A`, `F5`, `Delete` or `PageUp`, case doesnt matter. Only keys that make sense in a shortcut are known.
+    pub fn from_name(name: &str) -> Option<VirtualKeyCode> {
+        use VirtualKeyCode::*;
+        let key = match name.trim().to_lowercase().as_str() {
+            "a" => VK_A,
+            "b" => VK_B,
+            "c" => VK_C,
+            "d" => VK_D,
+            "e" => VK_E,
+            "f" => VK_F,
+            "g" => VK_G,
+            "h" => VK_H,
+            "i" => VK_I,
+            "j" => VK_J,
+            "k" => VK_K,
+            "l" => VK_L,
+            "m" => VK_M,
+            "n" => VK_N,
+            "o" => VK_O,
+            "p" => VK_P,
+            "q" => VK_Q,
+            "r" => VK_R,
+            "s" => VK_S,
+            "t" => VK_T,
+            "u" => VK_U,
+            "v" => VK_V,
+            "w" => VK_W,
+            "x" => VK_X,
+            "y" => VK_Y,
+            "z" => VK_Z,
+            "0" => VK_0,
+            "1" => VK_1,
+            "2" => VK_2,
+            "3" => VK_3,
+            "4" => VK_4,
+            "5" => VK_5,
+            "6" => VK_6,
+            "7" => VK_7,
+            "8" => VK_8,
+            "9" => VK_9,
+            "f1" => VK_F1,
+            "f2" => VK_F2,
+            "f3" => VK_F3,
+            "f4" => VK_F4,
+            "f5" => VK_F5,
+            "f6" => VK_F6,
+            "f7" => VK_F7,
+            "f8" => VK_F8,
+            "f9" => VK_F9,
+            "f10" => VK_F10,
+            "f11" => VK_F11,
+            "f12" => VK_F12,
+            "f13" => VK_F13,
+            "f14" => VK_F14,
+            "f15" => VK_F15,
+            "f16" => VK_F16,
+            "f17" => VK_F17,
+            "f18" => VK_F18,
+            "f19" => VK_F19,
+            "f20" => VK_F20,
+            "f21" => VK_F21,
+            "f22" => VK_F22,
+            "f23" => VK_F23,
+            "f24" => VK_F24,
+            "enter" | "return" => VK_RETURN,
+            "esc" | "escape" => VK_ESCAPE,
+            "tab" => VK_TAB,
+            "space" => VK_SPACE,
+            "backspace" | "back" => VK_BACK,
+            "delete" | "del" => VK_DELETE,
+            "insert" | "ins" => VK_INSERT,
+            "home" => VK_HOME,
+            "end" => VK_END,
+            "pageup" | "pgup" => VK_PRIOR,
+            "pagedown" | "pgdn" => VK_NEXT,
+            "left" => VK_LEFT,
+            "right" => VK_RIGHT,
+            "up" => VK_UP,
+            "down" => VK_DOWN,
+            "plus" | "=" => VK_OEM_PLUS,
+            "minus" | "-" => VK_OEM_MINUS,
+            "comma" | "," => VK_OEM_COMMA,
+            "period" | "." => VK_OEM_PERIOD,
+            "apps" | "menu" => VK_APPS,
+            "browserback" => VK_BROWSER_BACK,
+            "browserforward" => VK_BROWSER_FORWARD,
+            "browserrefresh" => VK_BROWSER_REFRESH,
+            _ => return None,
+        };
+        Some(key)
+    }
+
+    /// The name `from_name` understands
+    pub fn name(&self) -> String {
+        use VirtualKeyCode::*;
+        match self {
+            VK_RETURN => "Enter".into(),
+            VK_ESCAPE => "Esc".into(),
+            VK_TAB => "Tab".into(),
+            VK_SPACE => "Space".into(),
+            VK_BACK => "Backspace".into(),
+            VK_DELETE => "Delete".into(),
+            VK_INSERT => "Insert".into(),
+            VK_HOME => "Home".into(),
+            VK_END => "End".into(),
+            VK_PRIOR => "PageUp".into(),
+            VK_NEXT => "PageDown".into(),
+            VK_LEFT => "Left".into(),
+            VK_RIGHT => "Right".into(),
+            VK_UP => "Up".into(),
+            VK_DOWN => "Down".into(),
+            VK_OEM_PLUS => "Plus".into(),
+            VK_OEM_MINUS => "Minus".into(),
+            VK_OEM_COMMA => "Comma".into(),
+            VK_OEM_PERIOD => "Period".into(),
+            VK_APPS => "Apps".into(),
+            VK_BROWSER_BACK => "BrowserBack".into(),
+            VK_BROWSER_FORWARD => "BrowserForward".into(),
+            VK_BROWSER_REFRESH => "BrowserRefresh".into(),
+            //`VK_F5` becomes `F5`
+            k => format!("{:?}", k).trim_start_matches("VK_").to_string(),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::{fs, path::Path};
+    use win_expl::{
+        app_state::{Action, AppState, Effect},
+        search_engine::sort::{SortColumn, SortKey},
+    };
+
+    fn setup(name: &str) -> TempDir {
+        let root = TempDir::new(&format!("app_state_{}", name));
+        fs::create_dir_all(root.join("sub").join("deeper")).unwrap();
+        fs::write(root.join("report.txt"), "1234").unwrap();
+        fs::write(root.join("notes.md"), "12").unwrap();
+        fs::write(root.join("sub").join("report old.txt"), "1").unwrap();
+        root
+    }
+
+    fn path(p: &Path) -> String {
+        p.to_string_lossy().to_string()
+    }
+
+    fn names(state: &AppState) -> Vec<String> {
+        state.results.iter().map(|r| r.name.clone()).collect()
+    }
+
+    #[test]
+    fn test_navigation_history() {
+        let root = setup("navigation");
+        let mut state = AppState::default();
+
+        assert!(state.dispatch(Action::Navigate(path(&root))).is_none());
+        assert_eq!(state.path, path(&root));
+        assert_eq!(names(&state), vec!["sub", "notes.md", "report.txt"]);
+        assert!(!state.can_go_back());
+
+        state.dispatch(Action::Navigate(path(&root.join("sub"))));
+        state.dispatch(Action::Navigate(path(&root.join("sub").join("deeper"))));
+        assert!(state.results.is_empty());
+        assert!(state.can_go_back());
+
+        state.dispatch(Action::Back);
+        assert_eq!(state.path, path(&root.join("sub")));
+        assert_eq!(names(&state), vec!["deeper", "report old.txt"]);
+
+        state.dispatch(Action::Up);
+        assert_eq!(state.path, path(&root));
+        state.dispatch(Action::Back);
+        assert_eq!(state.path, path(&root.join("sub")));
+        state.dispatch(Action::Back);
+        assert_eq!(state.path, path(&root));
+        assert!(!state.can_go_back());
+        assert!(state.dispatch(Action::Back).is_none());
+        assert_eq!(state.path, path(&root));
+    }
+
+    #[test]
+    fn test_navigate_to_missing_directory_changes_nothing() {
+        let root = setup("missing");
+        let mut state = AppState::default();
+        state.dispatch(Action::Navigate(path(&root)));
+        let generation = state.generation;
+
+        let effect = state.dispatch(Action::Navigate(path(&root.join("gone"))));
+        assert!(matches!(effect, Some(Effect::Error(_))));
+        let effect = state.dispatch(Action::Navigate(path(&root.join("report.txt"))));
+        assert!(matches!(effect, Some(Effect::Error(_))));
+
+        assert_eq!(state.path, path(&root));
+        assert_eq!(state.generation, generation);
+        assert!(!state.can_go_back());
+    }
+
+    #[test]
+    fn test_failing_back_changes_nothing() {
+        let root = setup("back");
+        let mut state = AppState::default();
+        state.dispatch(Action::Navigate(path(&root.join("sub").join("deeper"))));
+        state.dispatch(Action::Navigate(path(&root)));
+        state.dispatch(Action::Search(String::from("report")));
+        let generation = state.generation;
+
+        fs::remove_dir_all(root.join("sub")).unwrap();
+        let effect = state.dispatch(Action::Back);
+        assert!(matches!(effect, Some(Effect::Error(_))));
+        assert_eq!(state.path, path(&root));
+        assert_eq!(state.query, "report");
+        assert_eq!(state.generation, generation);
+        //The entry stays, so it can be tried again
+        assert!(state.can_go_back());
+
+        fs::create_dir_all(root.join("sub").join("deeper")).unwrap();
+        assert!(state.dispatch(Action::Back).is_none());
+        assert_eq!(state.path, path(&root.join("sub").join("deeper")));
+        assert!(!state.can_go_back());
+    }
+
+    #[test]
+    fn test_search_keeps_results_of_invalid_query() {
+        let root = setup("search");
+        let mut state = AppState::default();
+        state.dispatch(Action::Navigate(path(&root)));
+
+        state.dispatch(Action::Search("report depth:1".into()));
+        assert_eq!(names(&state), vec!["report old.txt", "report.txt"]);
+        assert!(state.status.error.is_none());
+        let generation = state.generation;
+
+        state.dispatch(Action::Search("report depth:1 size:".into()));
+        assert_eq!(names(&state), vec!["report old.txt", "report.txt"]);
+        assert!(state.status.error.is_some());
+        assert_eq!(state.generation, generation);
+
+        //A new directory starts without a query
+        state.dispatch(Action::Navigate(path(&root.join("sub"))));
+        assert!(state.query.is_empty());
+        assert!(state.status.error.is_none());
+        assert_eq!(names(&state), vec!["deeper", "report old.txt"]);
+    }
+
+    #[test]
+    fn test_sort_and_activate() {
+        let root = setup("sort");
+        let mut state = AppState::default();
+        state.dispatch(Action::Navigate(path(&root)));
+
+        state.dispatch(Action::Sort(SortKey::Size));
+        assert_eq!(names(&state), vec!["sub", "notes.md", "report.txt"]);
+        state.dispatch(Action::Sort(SortKey::Size));
+        assert_eq!(names(&state), vec!["sub", "report.txt", "notes.md"]);
+        assert_eq!(state.sort.primary(), Some(SortColumn { key: SortKey::Size, descending: true }));
+
+        //The listing of the next directory keeps the order
+        let effect = state.dispatch(Action::Activate(0));
+        assert!(effect.is_none());
+        assert_eq!(state.path, path(&root.join("sub")));
+
+        let effect = state.dispatch(Action::Activate(1));
+        match effect {
+            Some(Effect::Open(p)) => assert_eq!(p, path(&root.join("sub").join("report old.txt"))),
+            e => panic!("Expected the file to be opened, got {:?}", e),
+        }
+        assert!(state.dispatch(Action::Activate(10)).is_none());
+    }
+
+    #[test]
+    fn test_disk_usage_is_left_to_the_gui() {
+        let root = setup("usage");
+        let mut state = AppState::default();
+        state.dispatch(Action::Navigate(path(&root)));
+        let generation = state.generation;
+
+        match state.dispatch(Action::DiskUsage(true)) {
+            Some(Effect::ScanDiskUsage { path: p, .. }) => assert_eq!(p, state.dir()),
+            e => panic!("Expected a disk usage scan, got {:?}", e),
+        }
+        assert_eq!(state.generation, generation);
+        assert!(matches!(state.dispatch(Action::Refresh), Some(Effect::ScanDiskUsage { .. })));
+
+        state.dispatch(Action::ShowResults(Vec::new()));
+        assert!(state.results.is_empty());
+        assert_eq!(state.generation, generation + 1);
+
+        assert!(state.dispatch(Action::DiskUsage(false)).is_none());
+        assert_eq!(names(&state), vec!["sub", "notes.md", "report.txt"]);
+    }
+}
@@ -0,0 +1,65 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::{fs, rc::Rc};
+    use win_expl::{
+        assets::{self, AssetStore, ResourceType},
+        file_types::FileCategory,
+    };
+
+    fn setup(name: &str) -> TempDir {
+        let root = TempDir::new(&format!("assets_{}", name));
+        fs::create_dir_all(root.join("types")).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_embedded_assets_decode() {
+        let mut all = vec![ResourceType::ArrowLeft, ResourceType::ArrowUp, ResourceType::Refresh, ResourceType::Copy];
+        all.extend(FileCategory::ALL.into_iter().map(ResourceType::FileType));
+        for t in all {
+            let img = assets::decode(t.embedded()).unwrap();
+            assert!(img.width > 0 && img.height > 0, "{:?}", t);
+            assert_eq!(img.bgra.len(), (img.width * img.height * 4) as usize);
+        }
+        assert_eq!(ResourceType::FileType(FileCategory::Other).file_name(), "types/file.png");
+    }
+
+    #[test]
+    fn test_decoded_once() {
+        let store = AssetStore::new(None);
+        let a = store.image(ResourceType::Copy);
+        let b = store.image(ResourceType::Copy);
+        assert!(Rc::ptr_eq(&a, &b));
+        assert_eq!(store.cached(), 1);
+
+        store.clear();
+        assert_eq!(store.cached(), 0);
+        assert!(!Rc::ptr_eq(&a, &store.image(ResourceType::Copy)));
+    }
+
+    #[test]
+    fn test_theme_overrides() {
+        let theme = setup("theme");
+        image::RgbaImage::from_pixel(3, 2, image::Rgba([1, 2, 3, 255]))
+            .save(theme.join("types").join("code.png"))
+            .unwrap();
+        fs::write(theme.join("copy.png"), "not a png").unwrap();
+
+        let store = AssetStore::new(Some(theme.to_path_buf()));
+        let code = store.image(ResourceType::FileType(FileCategory::Code));
+        assert_eq!((code.width, code.height), (3, 2));
+        assert_eq!(&code.bgra[..4], &[3, 2, 1, 255]);
+
+        //Broken or missing overrides use the embedded asset
+        let embedded = assets::decode(ResourceType::Copy.embedded()).unwrap();
+        assert_eq!(*store.image(ResourceType::Copy), embedded);
+        let embedded = assets::decode(ResourceType::ArrowUp.embedded()).unwrap();
+        assert_eq!(*store.image(ResourceType::ArrowUp), embedded);
+
+        let missing = AssetStore::new(Some(theme.join("missing")));
+        assert_eq!(*missing.image(ResourceType::ArrowUp), embedded);
+    }
+}
@@ -0,0 +1,108 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::fs;
+    use win_expl::{
+        breadcrumb::{self, Breadcrumb, DirectorySource, LiveDirectorySource},
+        memory::index::Index,
+    };
+
+    struct FakeSource;
+
+    impl DirectorySource for FakeSource {
+        fn child_directories(&self, path: &str) -> Option<Vec<String>> {
+            match path {
+                "C:" => Some(vec!["Users".into(), "Program Files".into(), "ProgramData".into()]),
+                "C:\\Users" => Some(vec!["public".into(), "me".into()]),
+                _ => None,
+            }
+        }
+    }
+
+    fn init_folder_structure() -> TempDir {
+        let root = TempDir::new("breadcrumb");
+        fs::create_dir_all(root.join("alpha")).unwrap();
+        fs::create_dir_all(root.join("Alps")).unwrap();
+        fs::create_dir_all(root.join("beta")).unwrap();
+        fs::write(root.join("almost.txt"), "").unwrap();
+        root
+    }
+
+    #[test]
+    fn test_segments_split_windows_path() {
+        let crumbs = Breadcrumb::from_path("C:\\Users\\me\\Desktop");
+        let names: Vec<&str> = crumbs.segments().iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["C:", "Users", "me", "Desktop"]);
+        assert_eq!(crumbs.path_of(0), Some("C:"));
+        assert_eq!(crumbs.path_of(2), Some("C:\\Users\\me"));
+        assert_eq!(crumbs.path(), Some("C:\\Users\\me\\Desktop"));
+        assert_eq!(crumbs.path_of(4), None);
+    }
+
+    #[test]
+    fn test_segments_ignore_trailing_and_double_separators() {
+        let crumbs = Breadcrumb::from_path("C:\\\\repos\\");
+        assert_eq!(crumbs.segments().len(), 2);
+        assert_eq!(crumbs.path(), Some("C:\\repos"));
+    }
+
+    #[test]
+    fn test_segments_unix_root() {
+        let crumbs = Breadcrumb::from_path("/home/me");
+        assert_eq!(crumbs.path_of(0), Some("/"));
+        assert_eq!(crumbs.path_of(1), Some("/home"));
+        assert_eq!(crumbs.path(), Some("/home/me"));
+    }
+
+    #[test]
+    fn test_siblings_include_segment_itself() {
+        let crumbs = Breadcrumb::from_path("C:\\Users\\me");
+        let siblings = crumbs.siblings(2, &[&FakeSource]);
+        assert_eq!(siblings.len(), 2);
+        assert_eq!(siblings[0].name, "me");
+        assert_eq!(siblings[0].path, "C:\\Users\\me");
+        assert_eq!(siblings[1].path, "C:\\Users\\public");
+        assert!(crumbs.siblings(0, &[&FakeSource]).is_empty());
+    }
+
+    #[test]
+    fn test_autocomplete_prefix_is_case_insensitive() {
+        let result = breadcrumb::autocomplete("C:\\prog", &[&FakeSource], 10);
+        assert_eq!(result, vec!["C:\\Program Files", "C:\\ProgramData"]);
+
+        let result = breadcrumb::autocomplete("C:\\prog", &[&FakeSource], 1);
+        assert_eq!(result.len(), 1);
+
+        assert!(breadcrumb::autocomplete("C:", &[&FakeSource], 10).is_empty());
+        assert!(breadcrumb::autocomplete("D:\\x", &[&FakeSource], 10).is_empty());
+    }
+
+    #[test]
+    fn test_autocomplete_falls_back_to_next_source() {
+        let root = init_folder_structure();
+        let sources: [&dyn DirectorySource; 2] = [&FakeSource, &LiveDirectorySource];
+        let input = format!("{}{}al", root.display(), std::path::MAIN_SEPARATOR);
+        let result = breadcrumb::autocomplete(&input, &sources, 10);
+        assert_eq!(result.len(), 2, "Files must not be suggested");
+        assert!(result[0].ends_with("alpha"));
+        assert!(result[1].ends_with("Alps"));
+    }
+
+    #[test]
+    fn test_index_resolves_below_its_full_path_root() {
+        let root = init_folder_structure();
+        fs::create_dir_all(root.join("alpha").join("nested")).unwrap();
+        let path = root.to_string_lossy().to_string();
+        let index = Index::build(&path, 3);
+
+        let mut children = index.child_directories(&path).unwrap();
+        children.sort();
+        assert_eq!(children, vec!["Alps", "alpha", "beta"]);
+        let alpha = root.join("alpha").to_string_lossy().to_string();
+        assert_eq!(index.child_directories(&alpha), Some(vec!["nested".to_string()]));
+        //A sibling of the root that starts with the same name isnt part of it
+        assert_eq!(index.child_directories(&format!("{}2", path)), None);
+    }
+}
@@ -0,0 +1,220 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::{
+        fs,
+        path::Path,
+        time::{Duration, SystemTime},
+    };
+    use win_expl::{
+        bulk_rename::{self, CaseTransform, RenameRules, RenameStatus},
+        search_engine::result::SearchEngineResult,
+    };
+
+    fn setup(name: &str, files: &[&str]) -> (TempDir, Vec<SearchEngineResult>) {
+        let root = TempDir::new(&format!("bulk_rename_{}", name));
+        let results = files
+            .iter()
+            .map(|f| {
+                fs::write(root.join(f), f).unwrap();
+                SearchEngineResult::from_path(&root.join(f).to_string_lossy()).unwrap()
+            })
+            .collect();
+        (root, results)
+    }
+
+    fn names(root: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(root)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_counter_date_and_case() {
+        let (_, mut files) = setup("counter", &["DSC_0001.JPG", "DSC_0002.JPG"]);
+        //Mid year, so the offset cant change the year
+        files[0].modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_592_222_400);
+        files[1].modified = files[0].modified;
+
+        let rules = RenameRules {
+            replace: String::from("Holiday {date} {n}"),
+            date_pattern: String::from("[year]"),
+            counter_start: 9,
+            counter_padding: 2,
+            case: CaseTransform::Lower,
+            ..Default::default()
+        };
+        let plan = bulk_rename::preview(&files, &rules).unwrap();
+        assert_eq!(plan[0].new_name, "holiday 2020 09.JPG");
+        assert_eq!(plan[1].new_name, "holiday 2020 10.JPG");
+        assert!(plan.iter().all(|p| p.status == RenameStatus::Ready));
+
+        let rules = RenameRules {
+            case: CaseTransform::Title,
+            extension: Some(String::from("jpeg")),
+            ..Default::default()
+        };
+        let plan = bulk_rename::preview(&files, &rules).unwrap();
+        assert_eq!(plan[0].new_name, "Dsc_0001.jpeg");
+    }
+
+    #[test]
+    fn test_find_and_replace() {
+        let (_, files) = setup("replace", &["log-2023-01-05.txt", "log.old.txt", "notes"]);
+
+        let rules = RenameRules {
+            find: String::from(r"(\d{4})-(\d{2})-(\d{2})"),
+            replace: String::from("$3.$2.$1"),
+            regex: true,
+            ..Default::default()
+        };
+        let plan = bulk_rename::preview(&files, &rules).unwrap();
+        assert_eq!(plan[0].new_name, "log-05.01.2023.txt");
+        assert_eq!(plan[1].status, RenameStatus::Unchanged);
+
+        //A `$` in the old name isnt taken for a group
+        let (_, priced) = setup("dollar", &["price $1.txt"]);
+        let rules = RenameRules {
+            find: String::from("price"),
+            replace: String::from("{name} (cost)"),
+            regex: true,
+            ..Default::default()
+        };
+        let plan = bulk_rename::preview(&priced, &rules).unwrap();
+        assert_eq!(plan[0].new_name, "price $1 (cost) $1.txt");
+
+        //Without regex everything is literal, the extension isnt part of the name
+        let rules = RenameRules {
+            find: String::from("."),
+            replace: String::from("$1"),
+            extension: Some(String::new()),
+            ..Default::default()
+        };
+        let plan = bulk_rename::preview(&files, &rules).unwrap();
+        assert_eq!(plan[1].new_name, "log$1old");
+        assert_eq!(plan[2].status, RenameStatus::Unchanged);
+
+        let rules = RenameRules {
+            find: String::from("("),
+            regex: true,
+            ..Default::default()
+        };
+        assert!(bulk_rename::preview(&files, &rules).is_err());
+    }
+
+    #[test]
+    fn test_collisions_block_apply() {
+        let (root, files) = setup("collisions", &["a.txt", "b.txt", "c.txt"]);
+        fs::write(root.join("taken.txt"), "").unwrap();
+
+        let rules = RenameRules {
+            replace: String::from("same"),
+            ..Default::default()
+        };
+        let plan = bulk_rename::preview(&files, &rules).unwrap();
+        assert!(plan
+            .iter()
+            .all(|p| matches!(p.status, RenameStatus::Collision(_))));
+        assert!(bulk_rename::apply(&plan).is_err());
+
+        let rules = RenameRules {
+            find: String::from("a"),
+            replace: String::from("taken"),
+            ..Default::default()
+        };
+        let plan = bulk_rename::preview(&files[..1], &rules).unwrap();
+        assert!(matches!(plan[0].status, RenameStatus::Collision(_)));
+
+        //2.txt cant move to the existing 3.txt, so 1.txt cant take its name either
+        let (root, files) = setup("staying", &["1.txt", "2.txt"]);
+        fs::write(root.join("3.txt"), "").unwrap();
+        let rules = RenameRules {
+            replace: String::from("{n}"),
+            counter_start: 2,
+            counter_padding: 0,
+            ..Default::default()
+        };
+        let plan = bulk_rename::preview(&files, &rules).unwrap();
+        assert_eq!(plan[0].new_name, "2.txt");
+        assert!(plan
+            .iter()
+            .all(|p| matches!(p.status, RenameStatus::Collision(_))));
+        assert!(bulk_rename::apply(&plan).is_err());
+        assert_eq!(names(&root), vec!["1.txt", "2.txt", "3.txt"]);
+    }
+
+    #[test]
+    fn test_chains_and_cycles() {
+        let (root, files) = setup("cycles", &["3.txt", "1.txt", "2.txt", "5.txt"]);
+        let numbered = RenameRules {
+            replace: String::from("{n}"),
+            counter_padding: 0,
+            ..Default::default()
+        };
+
+        //3 -> 1 -> 2 -> 3 and 5 -> 4
+        let plan = bulk_rename::preview(&files, &numbered).unwrap();
+        let status: Vec<&RenameStatus> = plan.iter().map(|p| &p.status).collect();
+        assert_eq!(status[..3], [&RenameStatus::InCycle; 3]);
+        assert_eq!(status[3], &RenameStatus::Ready);
+
+        let report = bulk_rename::apply(&plan).unwrap();
+        assert_eq!(report.done(), 4);
+        assert_eq!(names(&root), vec!["1.txt", "2.txt", "3.txt", "4.txt"]);
+        assert_eq!(fs::read_to_string(root.join("1.txt")).unwrap(), "3.txt");
+        assert_eq!(fs::read_to_string(root.join("3.txt")).unwrap(), "2.txt");
+        assert_eq!(fs::read_to_string(root.join("4.txt")).unwrap(), "5.txt");
+
+        //1 -> 2 -> 3 -> 4 is a chain that has to start at the end
+        let files: Vec<SearchEngineResult> = ["1.txt", "2.txt", "3.txt"]
+            .iter()
+            .map(|f| SearchEngineResult::from_path(&root.join(f).to_string_lossy()).unwrap())
+            .collect();
+        fs::remove_file(root.join("4.txt")).unwrap();
+        let plan = bulk_rename::preview(
+            &files,
+            &RenameRules {
+                counter_start: 2,
+                ..numbered
+            },
+        )
+        .unwrap();
+        assert!(plan.iter().all(|p| p.status == RenameStatus::Ready));
+        bulk_rename::apply(&plan).unwrap();
+        assert_eq!(names(&root), vec!["2.txt", "3.txt", "4.txt"]);
+        assert_eq!(fs::read_to_string(root.join("4.txt")).unwrap(), "2.txt");
+    }
+
+    //Names that only differ in case are different files here, which makes a cycle fail halfway
+    #[cfg(not(windows))]
+    #[test]
+    fn test_failed_cycle_is_rolled_back() {
+        let (root, files) = setup("rollback", &["a.txt", "b.txt", "A.txt"]);
+        let plan = vec![
+            bulk_rename::PlannedRename {
+                source: files[0].full_path.clone(),
+                new_name: String::from("b.txt"),
+                target: files[1].full_path.clone(),
+                status: RenameStatus::InCycle,
+            },
+            bulk_rename::PlannedRename {
+                source: files[1].full_path.clone(),
+                new_name: String::from("A.txt"),
+                target: files[2].full_path.clone(),
+                status: RenameStatus::InCycle,
+            },
+        ];
+
+        let report = bulk_rename::apply(&plan).unwrap();
+        assert_eq!(report.done(), 0);
+        assert_eq!(report.errors().len(), 1);
+        assert_eq!(report.skipped(), 1);
+        assert_eq!(names(&root), vec!["A.txt", "a.txt", "b.txt"]);
+        assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "a.txt");
+    }
+}
@@ -0,0 +1,152 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::fs;
+    use win_expl::{
+        checksum::{self, SumsEntry, Verification},
+        hashing::{self, HashFunction},
+    };
+
+    const ABC_MD5: &str = "900150983CD24FB0D6963F7D28E17F72";
+
+    fn setup(name: &str) -> TempDir {
+        let root = TempDir::new(&format!("checksum_{}", name));
+        fs::write(root.join("abc.txt"), "abc").unwrap();
+        root
+    }
+
+    #[test]
+    fn test_parse_sums_formats() {
+        let gnu = "# comment\n900150983cd24fb0d6963f7d28e17f72  abc.txt\nd41d8cd98f00b204e9800998ecf8427e *my file.bin\n";
+        assert_eq!(
+            checksum::parse_sums(gnu, Some(HashFunction::MD5)),
+            vec![
+                SumsEntry {
+                    name: "abc.txt".into(),
+                    hash: "900150983cd24fb0d6963f7d28e17f72".into(),
+                    hash_function: Some(HashFunction::MD5),
+                },
+                SumsEntry {
+                    name: "my file.bin".into(),
+                    hash: "d41d8cd98f00b204e9800998ecf8427e".into(),
+                    hash_function: Some(HashFunction::MD5),
+                },
+            ]
+        );
+
+        let bsd = checksum::parse_sums("SHA1 (a (1).txt) = a9993e364706816aba3e25717850c26c9cd0d89d", None);
+        assert_eq!(bsd[0].name, "a (1).txt");
+        assert_eq!(bsd[0].hash_function, Some(HashFunction::SHA1));
+
+        let sfv = checksum::parse_sums("; generated\nsome file.txt 352441C2", Some(HashFunction::CRC32));
+        assert_eq!(sfv[0].name, "some file.txt");
+        assert_eq!(sfv[0].hash, "352441C2");
+
+        let bare = checksum::parse_sums("900150983cd24fb0d6963f7d28e17f72\n", None);
+        assert_eq!(bare[0].name, "");
+        assert_eq!(bare[0].hash_function, Some(HashFunction::MD5));
+    }
+
+    #[test]
+    fn test_function_from_name_and_length() {
+        assert_eq!(checksum::function_from_file_name("SHA256SUMS"), Some(HashFunction::SHA256));
+        assert_eq!(checksum::function_from_file_name("image.iso.md5"), Some(HashFunction::MD5));
+        assert_eq!(checksum::function_from_file_name("release.sfv"), Some(HashFunction::CRC32));
+        assert_eq!(checksum::function_from_file_name("notes.txt"), None);
+
+        assert_eq!(checksum::guess_function(ABC_MD5), Some(HashFunction::MD5));
+        assert_eq!(checksum::guess_function("352441C2"), Some(HashFunction::CRC32));
+        assert_eq!(checksum::guess_function("not a hash"), None);
+    }
+
+    #[test]
+    fn test_find_expected_prefers_direct_sidecar() {
+        let root = setup("sidecar");
+        fs::write(root.join("MD5SUMS"), "00000000000000000000000000000000  abc.txt\n").unwrap();
+        fs::write(root.join("abc.txt.md5"), format!("{}\n", ABC_MD5.to_lowercase())).unwrap();
+        //Shares the prefix but belongs to another file
+        fs::write(root.join("abc.txt2.sha1"), "a9993e364706816aba3e25717850c26c9cd0d89d\n").unwrap();
+
+        let path = root.join("abc.txt").to_string_lossy().to_string();
+        let expected = checksum::find_expected(&path).unwrap();
+        assert_eq!(expected.hash_function, HashFunction::MD5);
+        assert!(expected.source.as_ref().unwrap().ends_with("abc.txt.md5"));
+        assert_eq!(checksum::verify(&expected), Verification::Match);
+    }
+
+    #[test]
+    fn test_find_expected_in_sums_list() {
+        let root = setup("list");
+        fs::write(root.join("SHA1SUMS"), "a9993e364706816aba3e25717850c26c9cd0d89d *ABC.TXT\n").unwrap();
+
+        let path = root.join("abc.txt").to_string_lossy().to_string();
+        let expected = checksum::find_expected(&path).unwrap();
+        assert_eq!(expected.hash_function, HashFunction::SHA1);
+        assert_eq!(checksum::verify(&expected), Verification::Match);
+
+        fs::remove_file(root.join("SHA1SUMS")).unwrap();
+        assert_eq!(checksum::find_expected(&path), None);
+    }
+
+    #[test]
+    fn test_verify_pasted_hash_mismatch() {
+        let root = setup("pasted");
+        let path = root.join("abc.txt").to_string_lossy().to_string();
+
+        let expected = checksum::expected_from_text(&path, "  d41d8cd98f00b204e9800998ecf8427e  \n").unwrap();
+        assert_eq!(expected.source, None);
+        assert_eq!(
+            checksum::verify(&expected),
+            Verification::Mismatch {
+                expected: "D41D8CD98F00B204E9800998ECF8427E".into(),
+                actual: ABC_MD5.into(),
+            }
+        );
+        assert_eq!(checksum::expected_from_text(&path, "hello"), None);
+    }
+
+    #[test]
+    fn test_write_sums() {
+        let root = setup("write");
+        let dir = root.to_string_lossy().to_string();
+        let path = root.join("abc.txt").to_string_lossy().to_string();
+
+        let target = checksum::write_sums(&dir, HashFunction::MD5, &[(path.clone(), ABC_MD5.into())]).unwrap();
+        assert!(target.ends_with("MD5SUMS"));
+        assert_eq!(
+            fs::read_to_string(&target).unwrap(),
+            "900150983cd24fb0d6963f7d28e17f72 *abc.txt\n"
+        );
+        assert_eq!(checksum::verify(&checksum::find_expected(&path).unwrap()), Verification::Match);
+        //An existing sums file is left alone
+        assert!(checksum::write_sums(&dir, HashFunction::MD5, &[]).unwrap_err().ends_with("already exists"));
+        assert_eq!(
+            fs::read_to_string(&target).unwrap(),
+            "900150983cd24fb0d6963f7d28e17f72 *abc.txt\n"
+        );
+
+        assert_eq!(
+            checksum::format_sums(&[("abc.txt".into(), "352441C2".into())], HashFunction::CRC32),
+            "abc.txt 352441C2\n"
+        );
+    }
+
+    #[test]
+    fn test_written_sums_verify_for_every_function() {
+        for hf in HashFunction::ALL {
+            let root = setup(&format!("round_trip_{}", hf.name()));
+            let dir = root.to_string_lossy().to_string();
+            let path = root.join("abc.txt").to_string_lossy().to_string();
+            let hash = hashing::hash_file(&path, hf, |_| true).unwrap();
+
+            let target = checksum::write_sums(&dir, hf, &[(path.clone(), hash)]).unwrap();
+            let name = target.rsplit(['\\', '/']).next().unwrap();
+            assert_eq!(checksum::function_from_file_name(name), Some(hf), "{}", name);
+            let expected = checksum::find_expected(&path).unwrap();
+            assert_eq!(expected.hash_function, hf);
+            assert_eq!(checksum::verify(&expected), Verification::Match, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::fs;
+    use win_expl::{
+        cli::{self, Command, Invocation, OutputFormat},
+        export::ExportColumn,
+        hashing::HashFunction,
+        logging::Level,
+        memory::index::Index,
+    };
+
+    fn setup(name: &str) -> TempDir {
+        let root = TempDir::new(&format!("cli_{}", name));
+        fs::create_dir_all(root.join("sub.dir")).unwrap();
+        fs::write(root.join("report.txt"), "same").unwrap();
+        fs::write(root.join("sub.dir").join("report copy.txt"), "same").unwrap();
+        fs::write(root.join("sub.dir").join("other.rs"), "different").unwrap();
+        root
+    }
+
+    fn args(line: &[&str]) -> Result<Invocation, String> {
+        cli::parse_args(&line.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+    }
+
+    fn run(invocation: &Invocation) -> String {
+        let mut out = Vec::new();
+        cli::run(invocation, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let i = args(&["search", "C:\\", "report", "!r", "--format", "json", "--columns", "path,size"]).unwrap();
+        assert_eq!(i.format, OutputFormat::Json);
+        assert_eq!(
+            i.command,
+            Command::Search {
+                root: "C:\\".into(),
+                query: "report !r".into(),
+                columns: vec![ExportColumn::Path, ExportColumn::Size],
+            }
+        );
+
+        let i = args(&["hash", "a", "b", "--algo", "crc32", "-f", "nul"]).unwrap();
+        assert_eq!(i.format, OutputFormat::Nul);
+        assert_eq!(i.command, Command::Hash { files: vec!["a".into(), "b".into()], algo: HashFunction::CRC32 });
+
+        assert_eq!(args(&["index", "stats", "--index", "x.json"]).unwrap().index_file, "x.json");
+        assert_eq!(args(&["index", "stats"]).unwrap().log_level, None);
+        assert_eq!(args(&["index", "stats", "--log-level", "Debug"]).unwrap().log_level, Some(Level::Debug));
+        assert!(args(&["index", "stats", "--log-level", "loud"]).is_err());
+        assert_eq!(args(&[]).unwrap().command, Command::Help);
+        assert_eq!(args(&["dupes", "x", "--help"]).unwrap().command, Command::Help);
+        assert!(args(&["index", "drop"]).is_err());
+        assert!(args(&["hash"]).is_err());
+        assert!(args(&["search", "x", "--format", "xml"]).is_err());
+        assert!(args(&["search", "x", "--depth"]).is_err());
+        assert!(args(&["search", "x", "--verbose"]).is_err());
+    }
+
+    #[test]
+    fn test_search() {
+        let root = setup("search");
+        let root_arg = root.to_string_lossy().to_string();
+
+        let table = run(&args(&["search", &root_arg, "report", "depth:1", "--columns", "name,size"]).unwrap());
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines, vec!["NAME             SIZE", "report copy.txt  4", "report.txt       4"]);
+
+        let nul = run(&args(&["search", &root_arg, "other", "depth:1", "-f", "nul"]).unwrap());
+        assert_eq!(nul, format!("{}\0", root.join("sub.dir").join("other.rs").to_string_lossy()));
+
+        let json = run(&args(&["search", &root_arg, "type:code", "depth:1", "-f", "json", "--columns", "name"]).unwrap());
+        assert_eq!(json, "{\"name\":\"other.rs\"}\n");
+
+        let mut out = Vec::new();
+        assert!(cli::run(&args(&["search", &root_arg, "ext:"]).unwrap(), &mut out).is_err());
+    }
+
+    #[test]
+    fn test_index() {
+        let root = setup("index");
+        //Outside of the root, it would be indexed as well
+        let index_dir = TempDir::new("cli_index_file");
+        let index_file = index_dir.join("index.json").to_string_lossy().to_string();
+        let root_arg = root.to_string_lossy().to_string();
+
+        let built = run(&args(&["index", "build", &root_arg, "--index", &index_file, "-f", "json"]).unwrap());
+        let stats: serde_json::Value = serde_json::from_str(&built).unwrap();
+        assert_eq!(stats["root"], root_arg.as_str());
+        assert_eq!((stats["directories"].as_u64(), stats["files"].as_u64()), (Some(2), Some(3)));
+        assert_eq!(stats["depth"], 1);
+        assert_eq!(Index::load(&index_file).unwrap().root_path(), Some(root_arg.as_str()));
+
+        fs::write(root.join("new.txt"), "").unwrap();
+        let refreshed = run(&args(&["index", "refresh", "--index", &index_file, "-f", "json"]).unwrap());
+        let stats: serde_json::Value = serde_json::from_str(&refreshed).unwrap();
+        assert_eq!(stats["files"], 4);
+
+        let table = run(&args(&["index", "stats", "--index", &index_file, "--depth", "0"]).unwrap());
+        assert!(table.lines().next().unwrap().starts_with("ROOT"));
+        assert!(table.lines().nth(1).unwrap().starts_with(&root_arg));
+
+        //Files below the root come from the index
+        let dupes = run(&args(&["dupes", &root_arg, "--use-index", "--index", &index_file, "-f", "nul"]).unwrap());
+        let mut files: Vec<&str> = dupes.split_terminator('\0').collect();
+        files.sort();
+        assert_eq!(files.len(), 2);
+        assert!(files[0].ends_with("report.txt"));
+        assert!(files[1].ends_with("report copy.txt"));
+    }
+
+    #[test]
+    fn test_hash_and_dupes() {
+        let root = setup("hash");
+        let file = root.join("report.txt").to_string_lossy().to_string();
+
+        let table = run(&args(&["hash", &file, "--algo", "crc32"]).unwrap());
+        assert!(table.ends_with(&format!("  {}\n", file)));
+        let json: serde_json::Value = serde_json::from_str(&run(&args(&["hash", &file, "-f", "json"]).unwrap())).unwrap();
+        assert_eq!(json["algorithm"], "SHA256");
+        assert_eq!(json["path"], file.as_str());
+
+        //The other files are still hashed
+        let mut out = Vec::new();
+        let missing = root.join("missing").to_string_lossy().to_string();
+        let err = cli::run(&args(&["hash", &missing, &file]).unwrap(), &mut out).unwrap_err();
+        assert!(err.contains("missing"));
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 1);
+
+        let root_arg = root.to_string_lossy().to_string();
+        let dupes = run(&args(&["dupes", &root_arg]).unwrap());
+        assert!(dupes.starts_with("4 bytes, 4 wasted, "));
+        assert!(dupes.ends_with("1 sets, 4 bytes wasted\n"));
+        let json = run(&args(&["dupes", &root_arg, "-f", "json"]).unwrap());
+        let group: serde_json::Value = serde_json::from_str(json.lines().next().unwrap()).unwrap();
+        assert_eq!(group["files"].as_array().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,147 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::fs;
+    use win_expl::{
+        context_actions::{self, ActionJob, AppliesTo, ContextAction},
+        search_engine::result::SearchEngineResult,
+    };
+
+    /// Creates the files and folders (ending with `/`) and hands back their results
+    fn setup(name: &str, entries: &[&str]) -> (TempDir, Vec<SearchEngineResult>) {
+        let root = TempDir::new(&format!("context_actions_{}", name));
+        let results = entries
+            .iter()
+            .map(|e| {
+                let path = root.join(e.trim_end_matches('/'));
+                match e.ends_with('/') {
+                    true => fs::create_dir(&path).unwrap(),
+                    false => fs::write(&path, e).unwrap(),
+                }
+                SearchEngineResult::from_path(&path.to_string_lossy()).unwrap()
+            })
+            .collect();
+        (root, results)
+    }
+
+    fn action(args: &[&str]) -> ContextAction {
+        ContextAction {
+            label: String::from("Test"),
+            applies_to: AppliesTo::All,
+            extensions: Vec::new(),
+            program: String::from("tool"),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            working_dir: None,
+            capture_output: false,
+        }
+    }
+
+    #[test]
+    fn test_applies_to() {
+        let (_, results) = setup("applies_to", &["a.JPG", "b.tar.gz", "c.txt", "folder/"]);
+        let mut a = action(&[]);
+        assert!(results.iter().all(|r| a.applies_to(r)));
+
+        a.applies_to = AppliesTo::Directories;
+        let matches: Vec<bool> = results.iter().map(|r| a.applies_to(r)).collect();
+        assert_eq!(matches, vec![false, false, false, true]);
+
+        a.applies_to = AppliesTo::Files;
+        a.extensions = vec![String::from("jpg"), String::from("*.tar.gz")];
+        let matches: Vec<bool> = results.iter().map(|r| a.applies_to(r)).collect();
+        assert_eq!(matches, vec![true, true, false, false]);
+
+        //Directories dont have an extension
+        a.applies_to = AppliesTo::All;
+        assert!(!a.applies_to(&results[3]));
+    }
+
+    #[test]
+    fn test_prepare_placeholders() {
+        let (root, results) = setup("prepare", &["a.txt", "b.txt", "folder/"]);
+        let root = root.to_string_lossy().to_string();
+        let files = &results[..2];
+
+        //Per file placeholders start the program for every item
+        let mut a = action(&["--open", "{name}"]);
+        a.working_dir = Some(String::from("{dir}"));
+        let commands = a.prepare(files);
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[1].args, vec!["--open", "b.txt"]);
+        assert_eq!(commands[1].working_dir.as_deref(), Some(root.as_str()));
+
+        //The selection on its own is split into one argument per path
+        let a = action(&["-x", "{selection}"]);
+        let commands = a.prepare(files);
+        assert_eq!(commands.len(), 1);
+        assert_eq!(
+            commands[0].args,
+            vec!["-x", results[0].full_path.as_str(), results[1].full_path.as_str()]
+        );
+
+        //Inside of another argument it is joined
+        let a = action(&["--files={selection}"]);
+        let commands = a.prepare(files);
+        assert_eq!(
+            commands[0].args,
+            vec![format!("--files={} {}", results[0].full_path, results[1].full_path)]
+        );
+
+        //Items the action doesnt apply to are left out
+        let mut a = action(&["{path}"]);
+        a.applies_to = AppliesTo::Directories;
+        let commands = a.prepare(&results);
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].args, vec![results[2].full_path.clone()]);
+        assert!(a.prepare(files).is_empty());
+    }
+
+    #[test]
+    fn test_load() {
+        let (root, _) = setup("load", &[]);
+        let path = root.join("context_actions.json");
+        let path = path.to_string_lossy();
+        assert_eq!(context_actions::load(&path), Ok(Vec::new()));
+
+        fs::write(
+            root.join("context_actions.json"),
+            r#"[
+                { "label": "Open in editor", "program": "code", "args": ["{path}"] },
+                { "label": "Git status", "applies_to": "directories", "program": "git", "args": ["status"],
+                  "working_dir": "{path}", "capture_output": true }
+            ]"#,
+        )
+        .unwrap();
+        let actions = context_actions::load(&path).unwrap();
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].applies_to, AppliesTo::All);
+        assert!(!actions[0].capture_output);
+        assert_eq!(actions[1].applies_to, AppliesTo::Directories);
+        assert_eq!(actions[1].working_dir.as_deref(), Some("{path}"));
+
+        fs::write(root.join("context_actions.json"), r#"[{ "label": "No program" }]"#).unwrap();
+        assert!(context_actions::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_run_captures_output() {
+        let (root, results) = setup("run", &["a.txt"]);
+        let mut a = action(&["--version"]);
+        a.program = String::from("cargo");
+        a.working_dir = Some(root.to_string_lossy().to_string());
+        a.capture_output = true;
+
+        let outputs = ActionJob::spawn(&a, &results, || {}).wait();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].status, Some(0));
+        assert!(outputs[0].stdout.starts_with("cargo"));
+        assert!(context_actions::summary(&outputs).starts_with("> cargo --version\ncargo"));
+
+        a.program = String::from("win_expl_missing_program");
+        let output = &ActionJob::spawn(&a, &results, || {}).wait()[0];
+        assert_eq!(output.status, None);
+        assert!(output.stderr.starts_with("Failed to start"));
+    }
+}
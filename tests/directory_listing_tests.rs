@@ -0,0 +1,67 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::{fs, path::Path};
+    use win_expl::search_engine::{
+        directory_listing::{DirectoryListing, ListingError, ListingOptions},
+        sort::{SortColumn, SortKey, SortOrder},
+    };
+
+    fn init_folder_structure() -> TempDir {
+        let root = TempDir::new("directory_listing");
+        fs::create_dir_all(root.join("b-folder")).unwrap();
+        fs::create_dir_all(root.join("A-folder")).unwrap();
+        fs::write(root.join("c.txt"), "12345").unwrap();
+        fs::write(root.join("a.txt"), "1").unwrap();
+        fs::write(root.join("B.txt"), "123").unwrap();
+        root
+    }
+
+    fn names(root: &Path, options: &ListingOptions) -> Vec<String> {
+        DirectoryListing::list(&root.to_string_lossy(), options)
+            .expect("Failed to list")
+            .entries
+            .into_iter()
+            .map(|e| e.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_folders_first_sorted_by_name() {
+        let root = init_folder_structure();
+        let result = names(&root, &ListingOptions::default());
+        assert_eq!(result, vec!["A-folder", "b-folder", "a.txt", "B.txt", "c.txt"]);
+    }
+
+    #[test]
+    fn test_descending_keeps_folders_first() {
+        let root = init_folder_structure();
+        let options = ListingOptions {
+            sort: SortOrder {
+                folders_first: true,
+                columns: vec![SortColumn {
+                    key: SortKey::Size,
+                    descending: true,
+                }],
+            },
+            ..Default::default()
+        };
+        let result = names(&root, &options);
+        assert!(result[0].ends_with("folder") && result[1].ends_with("folder"));
+        assert_eq!(&result[2..], &["c.txt", "B.txt", "a.txt"]);
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        let root = init_folder_structure();
+        let missing = root.join("missing").to_string_lossy().to_string();
+        let err = DirectoryListing::list(&missing, &ListingOptions::default()).unwrap_err();
+        assert_eq!(err, ListingError::NotFound(missing));
+
+        let file = root.join("a.txt").to_string_lossy().to_string();
+        let err = DirectoryListing::list(&file, &ListingOptions::default()).unwrap_err();
+        assert_eq!(err, ListingError::NotADirectory(file));
+    }
+}
@@ -0,0 +1,104 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::{fs, sync::atomic::Ordering};
+    use win_expl::{
+        disk_usage::{self, ScanState, UsageCache},
+        memory::index::Index,
+        search_engine::parameter_parser::SearchEngineParameter,
+    };
+
+    fn setup(name: &str) -> TempDir {
+        let root = TempDir::new(&format!("disk_usage_{}", name));
+        fs::create_dir_all(root.join("big").join("nested")).unwrap();
+        fs::create_dir_all(root.join("small")).unwrap();
+        fs::create_dir_all(root.join("bin")).unwrap();
+        fs::write(root.join("big").join("a.bin"), vec![0u8; 3000]).unwrap();
+        fs::write(root.join("big").join("nested").join("b.bin"), vec![0u8; 5000]).unwrap();
+        fs::write(root.join("small").join("c.txt"), vec![0u8; 100]).unwrap();
+        fs::write(root.join("bin").join("tool.exe"), vec![0u8; 10_000]).unwrap();
+        fs::write(root.join("top.txt"), vec![0u8; 900]).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_recursive_sizes_largest_first() {
+        let root = setup("sizes");
+        let skip = SearchEngineParameter::default();
+        let state = ScanState::default();
+        let tree = disk_usage::analyze(&root.to_string_lossy(), &skip, &state).unwrap();
+        assert!(state.take_failures().is_empty());
+
+        //bin is skipped by default, same as in the search
+        assert_eq!(tree.size, 9000);
+        assert_eq!(tree.file_count, 4);
+        let names: Vec<&str> = tree.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["big", "top.txt", "small"]);
+        assert_eq!(tree.children[0].size, 8000);
+        assert_eq!(tree.children[0].file_count, 2);
+        assert_eq!(tree.children[1].percent_of(tree.size), 10.0);
+
+        let skip = SearchEngineParameter {
+            search_bin_dirs: true,
+            ..Default::default()
+        };
+        let tree = disk_usage::analyze(&root.to_string_lossy(), &skip, &ScanState::default()).unwrap();
+        assert_eq!(tree.size, 19_000);
+        assert_eq!(tree.children[0].name, "bin");
+    }
+
+    #[test]
+    fn test_cache_drills_down() {
+        let root = setup("cache");
+        let root_path = root.to_string_lossy().to_string();
+        let tree = disk_usage::analyze(&root_path, &SearchEngineParameter::default(), &ScanState::default()).unwrap();
+
+        let mut cache = UsageCache::default();
+        cache.insert(tree);
+        let nested = root.join("BIG").join("nested").to_string_lossy().to_string();
+        let node = cache.get(&nested).unwrap();
+        assert_eq!(node.size, 5000);
+        assert_eq!(node.children[0].name, "b.bin");
+
+        assert!(cache.get(&format!("{}2", root_path)).is_none());
+        assert!(cache.get(&root.join("top.txt").to_string_lossy()).is_none());
+
+        cache.clear();
+        assert!(cache.get(&root_path).is_none());
+    }
+
+    #[test]
+    fn test_sizes_from_index() {
+        let root = setup("index");
+        let index = Index::build(&root.to_string_lossy(), 8);
+        let skip = SearchEngineParameter::default();
+
+        let (node, path) = disk_usage::index_subtree(&index, &root.join("BIG").to_string_lossy()).unwrap();
+        assert_eq!(path, root.join("big").to_string_lossy());
+        let state = ScanState::default();
+        let tree = disk_usage::from_index(&node, path, &skip, &state).unwrap();
+        assert_eq!(tree.size, 8000);
+        assert_eq!(tree.children[0].path, root.join("big").join("nested").to_string_lossy());
+        assert!(state.take_failures().is_empty());
+
+        let (node, path) = disk_usage::index_subtree(&index, &root.to_string_lossy()).unwrap();
+        let state = ScanState::default();
+        state.cancelled.store(true, Ordering::Relaxed);
+        assert!(disk_usage::from_index(&node, path, &skip, &state).is_err());
+        assert!(disk_usage::index_subtree(&index, &root.join("missing").to_string_lossy()).is_none());
+    }
+
+    #[test]
+    fn test_cancelled_scan_fails() {
+        let root = setup("cancel");
+        let skip = SearchEngineParameter::default();
+        let state = ScanState::default();
+        state.cancelled.store(true, Ordering::Relaxed);
+        assert!(disk_usage::analyze(&root.to_string_lossy(), &skip, &state).is_err());
+
+        let missing = root.join("missing").to_string_lossy().to_string();
+        assert!(disk_usage::analyze(&missing, &skip, &ScanState::default()).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::{collections::HashMap, fs, path::PathBuf, time::Duration};
+    use win_expl::{
+        dual_pane::{compare, Comparison, Difference, Side},
+        search_engine::result::SearchEngineResult,
+    };
+
+    fn setup(name: &str) -> TempDir {
+        let root = TempDir::new(&format!("dual_pane_{}", name));
+        for side in ["left", "right"] {
+            fs::create_dir_all(root.join(side).join("docs")).unwrap();
+            fs::write(root.join(side).join("same.txt"), "1234").unwrap();
+            fs::write(root.join(side).join("touched.txt"), "1234").unwrap();
+        }
+        fs::write(root.join("left").join("sized.txt"), "12").unwrap();
+        fs::write(root.join("right").join("sized.txt"), "1234").unwrap();
+        fs::write(root.join("left").join("only left.txt"), "").unwrap();
+        fs::create_dir_all(root.join("right").join("only right")).unwrap();
+        root
+    }
+
+    fn list(dir: PathBuf) -> Vec<SearchEngineResult> {
+        let mut results: Vec<SearchEngineResult> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| SearchEngineResult::read(&e.unwrap()).unwrap())
+            .collect();
+        results.sort_by(|a, b| a.name.cmp(&b.name));
+        results
+    }
+
+    fn names(results: &[SearchEngineResult], rows: &HashMap<usize, Difference>) -> Vec<(String, Difference)> {
+        let mut names: Vec<(String, Difference)> =
+            rows.iter().map(|(r, d)| (results[*r].name.clone(), *d)).collect();
+        names.sort_by(|a, b| a.0.cmp(&b.0));
+        names
+    }
+
+    #[test]
+    fn test_compare_marks_both_sides() {
+        let root = setup("compare");
+        let left = list(root.join("left"));
+        let mut right = list(root.join("right"));
+        let touched = right.iter_mut().find(|r| r.name == "touched.txt").unwrap();
+        touched.modified += Duration::from_secs(60);
+
+        let comparison = compare(&left, &right);
+        assert_eq!(
+            names(&left, &comparison.left),
+            vec![
+                ("only left.txt".to_string(), Difference::Missing),
+                ("sized.txt".to_string(), Difference::Size),
+                ("touched.txt".to_string(), Difference::Modified),
+            ]
+        );
+        assert_eq!(
+            names(&right, &comparison.right),
+            vec![
+                ("only right".to_string(), Difference::Missing),
+                ("sized.txt".to_string(), Difference::Size),
+                ("touched.txt".to_string(), Difference::Modified),
+            ]
+        );
+        assert_eq!(comparison.summary(), "1 only left, 1 only right, 2 differ");
+
+        let row = right.iter().position(|r| r.name == "sized.txt").unwrap();
+        assert_eq!(comparison.difference(Side::Right, row), Some(Difference::Size));
+        assert_eq!(comparison.difference(Side::Left, 100), None);
+        assert_eq!(comparison.rows(Side::Right).len(), 3);
+    }
+
+    #[test]
+    fn test_small_time_differences_are_ignored() {
+        let root = setup("tolerance");
+        let left = list(root.join("left"));
+        let mut right: Vec<SearchEngineResult> = left.clone();
+        for r in right.iter_mut() {
+            r.modified += Duration::from_secs(1);
+        }
+
+        let comparison = compare(&left, &right);
+        assert_eq!(comparison, Comparison::default());
+        assert!(comparison.is_empty());
+        assert_eq!(comparison.summary(), "Both sides are the same");
+        assert_eq!(Side::Left.other(), Side::Right);
+    }
+
+    #[test]
+    fn test_file_and_folder_of_the_same_name_differ() {
+        let root = TempDir::new("dual_pane_type");
+        fs::create_dir_all(root.join("left")).unwrap();
+        fs::create_dir_all(root.join("right").join("thing")).unwrap();
+        fs::write(root.join("left").join("thing"), "").unwrap();
+
+        let comparison = compare(&list(root.join("left")), &list(root.join("right")));
+        assert_eq!(comparison.difference(Side::Left, 0), Some(Difference::Type));
+        assert_eq!(comparison.difference(Side::Right, 0), Some(Difference::Type));
+        assert_eq!(comparison.summary(), "0 only left, 0 only right, 1 differ");
+    }
+}
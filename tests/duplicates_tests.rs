@@ -0,0 +1,159 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::{
+        fs,
+        path::Path,
+    };
+    use win_expl::duplicates::{
+        self, DuplicateEvent, DuplicateGroup, DuplicateJob, DuplicateOptions, DuplicateSets,
+        FileSource, Stage, BLOCK_SIZE,
+    };
+
+    fn setup(name: &str) -> TempDir {
+        let root = TempDir::new(&format!("duplicates_{}", name));
+        fs::create_dir_all(root.join("sub")).unwrap();
+        root
+    }
+
+    fn path(root: &Path, name: &str) -> String {
+        root.join(name).to_string_lossy().to_string()
+    }
+
+    fn find(root: &Path) -> Vec<DuplicateGroup> {
+        let files = duplicates::walk(&root.to_string_lossy(), || true).unwrap();
+        duplicates::find_duplicates(files, &DuplicateOptions::default(), |_, _, _| true).unwrap()
+    }
+
+    #[test]
+    fn test_groups_identical_files() {
+        let root = setup("identical");
+        fs::write(root.join("a.txt"), "same content").unwrap();
+        fs::write(root.join("sub").join("b.txt"), "same content").unwrap();
+        fs::write(root.join("c.txt"), "same content").unwrap();
+        //Same size, different content
+        fs::write(root.join("d.txt"), "other conten").unwrap();
+        fs::write(root.join("empty1.txt"), "").unwrap();
+        fs::write(root.join("empty2.txt"), "").unwrap();
+
+        let groups = find(&root);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].size, 12);
+        assert_eq!(groups[0].wasted(), 24);
+        let mut expected = vec![
+            path(&root, "a.txt"),
+            path(&root, "c.txt"),
+            path(&root.join("sub"), "b.txt"),
+        ];
+        expected.sort();
+        assert_eq!(groups[0].files, expected);
+        assert_eq!(duplicates::total_wasted(&groups), 24);
+    }
+
+    #[test]
+    fn test_full_hash_tells_apart_equal_ends() {
+        let root = setup("big");
+        let mut content = vec![1u8; 4 * BLOCK_SIZE as usize];
+        fs::write(root.join("a.bin"), &content).unwrap();
+        fs::write(root.join("b.bin"), &content).unwrap();
+        //Only the middle differs, so the partial hash cant see it
+        content[2 * BLOCK_SIZE as usize] = 2;
+        fs::write(root.join("c.bin"), &content).unwrap();
+
+        let groups = find(&root);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].files, vec![path(&root, "a.bin"), path(&root, "b.bin")]);
+        assert_eq!(groups[0].wasted(), 4 * BLOCK_SIZE);
+    }
+
+    #[test]
+    fn test_cancel_while_hashing_whole_files() {
+        let root = setup("cancel_full");
+        let content = vec![1u8; 4 * BLOCK_SIZE as usize];
+        fs::write(root.join("a.bin"), &content).unwrap();
+        fs::write(root.join("b.bin"), &content).unwrap();
+        let files = duplicates::walk(&root.to_string_lossy(), || true).unwrap();
+
+        let mut asked = Vec::new();
+        let options = DuplicateOptions::default();
+        let result = duplicates::find_duplicates(files, &options, |stage, done, _| {
+            if stage != Stage::FullHash {
+                return true;
+            }
+            //The second question about the same file comes from inside the hashing
+            let again = asked.contains(&done);
+            asked.push(done);
+            !again
+        });
+        assert!(result.is_err());
+        assert_eq!(asked, vec![0, 0]);
+    }
+
+    #[test]
+    fn test_hardlinks_are_no_duplicates() {
+        let root = setup("hardlinks");
+        fs::write(root.join("a.txt"), "linked").unwrap();
+        fs::hard_link(root.join("a.txt"), root.join("b.txt")).unwrap();
+        assert!(find(&root).is_empty());
+
+        fs::write(root.join("c.txt"), "linked").unwrap();
+        let groups = find(&root);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].files.len(), 2);
+        assert!(groups[0].files.contains(&path(&root, "c.txt")));
+    }
+
+    #[test]
+    fn test_cancel_and_csv() {
+        let root = setup("csv");
+        fs::write(root.join("a \"1\".txt"), "abc").unwrap();
+        fs::write(root.join("b.txt"), "abc").unwrap();
+        let files = duplicates::walk(&root.to_string_lossy(), || true).unwrap();
+        assert!(duplicates::walk(&root.to_string_lossy(), || false).is_err());
+
+        let cancelled = duplicates::find_duplicates(files.clone(), &DuplicateOptions::default(), |_, _, _| false);
+        assert!(cancelled.is_err());
+
+        let groups = duplicates::find_duplicates(files, &DuplicateOptions::default(), |_, _, _| true).unwrap();
+        let csv = duplicates::to_csv(&groups);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "group,size,wasted,hash,path");
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with(&format!("1,3,3,{},", groups[0].hash)));
+        assert!(lines[1].ends_with("a \"\"1\"\".txt\""));
+    }
+
+    #[test]
+    fn test_sets_find_the_group_of_a_path() {
+        let groups = vec![
+            DuplicateGroup { size: 1, hash: "a".into(), files: vec!["x".into(), "y".into()] },
+            DuplicateGroup { size: 2, hash: "b".into(), files: vec!["z".into(), "w".into()] },
+        ];
+        let mut sets = DuplicateSets::new(groups);
+        assert_eq!(sets.find("w").map(|(i, g)| (i, g.size)), Some((1, 2)));
+        assert!(sets.find("v").is_none());
+
+        sets.clear();
+        assert!(sets.is_empty());
+        assert!(sets.find("x").is_none());
+    }
+
+    #[test]
+    fn test_job_reports_groups() {
+        let root = setup("job");
+        fs::write(root.join("a.txt"), "job").unwrap();
+        fs::write(root.join("sub").join("a.txt"), "job").unwrap();
+
+        let job = DuplicateJob::spawn(
+            FileSource::Tree(root.to_string_lossy().to_string()),
+            DuplicateOptions::default(),
+            || {},
+        );
+        match job.wait().pop() {
+            Some(DuplicateEvent::Finished(Ok(groups))) => assert_eq!(groups.len(), 1),
+            e => panic!("unexpected event {:?}", e),
+        }
+    }
+}
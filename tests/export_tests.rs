@@ -0,0 +1,90 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::{
+        fs,
+        path::Path,
+        time::{Duration, SystemTime},
+    };
+    use win_expl::{
+        export::{self, ExportColumn, ExportFormat, ExportOptions},
+        search_engine::result::SearchEngineResult,
+    };
+
+    fn setup(name: &str) -> (TempDir, Vec<SearchEngineResult>) {
+        let root = TempDir::new(&format!("export_{}", name));
+        fs::create_dir_all(root.join("folder")).unwrap();
+        fs::write(root.join("notes.txt"), "hello").unwrap();
+        fs::write(root.join("a, \"quoted\".rs"), "").unwrap();
+
+        let results = ["folder", "notes.txt", "a, \"quoted\".rs"]
+            .iter()
+            .map(|n| {
+                let mut r = SearchEngineResult::from_path(&root.join(n).to_string_lossy()).unwrap();
+                r.modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+                r
+            })
+            .collect();
+        (root, results)
+    }
+
+    fn export(results: &[SearchEngineResult], format: ExportFormat, columns: Vec<ExportColumn>) -> String {
+        let mut out = Vec::new();
+        export::write(results, &ExportOptions { format, columns }, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_csv() {
+        let (_root, results) = setup("csv");
+        let columns = vec![ExportColumn::Name, ExportColumn::Extension, ExportColumn::Size, ExportColumn::Modified, ExportColumn::Type];
+        let csv = export(&results, ExportFormat::Csv, columns);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "name,extension,size,modified,type");
+        assert_eq!(lines[2], "notes.txt,txt,5,2023-11-14T22:13:20Z,Document");
+        assert_eq!(lines[3], "\"a, \"\"quoted\"\".rs\",rs,0,2023-11-14T22:13:20Z,Code");
+        assert!(lines[1].starts_with("folder,,"));
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let (root, results) = setup("json");
+        let json = export(&results, ExportFormat::JsonLines, vec![ExportColumn::Path, ExportColumn::Size]);
+        let rows: Vec<serde_json::Value> = json.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1]["size"], 5);
+        assert_eq!(rows[1]["path"], root.join("notes.txt").to_string_lossy().as_ref());
+        assert_eq!(rows[1].as_object().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_path_lists_and_file() {
+        let (root, results) = setup("lists");
+        let paths: Vec<String> = results.iter().map(|r| r.full_path.clone()).collect();
+
+        let lines = export(&results, ExportFormat::PathList, Vec::new());
+        assert_eq!(lines, paths.iter().map(|p| format!("{}\n", p)).collect::<String>());
+        let nul = export(&results, ExportFormat::NulPathList, Vec::new());
+        assert_eq!(nul, paths.iter().map(|p| format!("{}\0", p)).collect::<String>());
+
+        let target = root.join("out.csv");
+        export::write_file(&results[1..2], &ExportOptions::default(), &target).unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap().lines().count(), 2);
+        assert!(export::write_file(&results, &ExportOptions::default(), &root.join("missing").join("x.csv")).is_err());
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(ExportFormat::from_name("JSONL"), Some(ExportFormat::JsonLines));
+        assert_eq!(ExportFormat::from_name("nul"), Some(ExportFormat::NulPathList));
+        assert_eq!(ExportFormat::from_name("xml"), None);
+        assert_eq!(ExportFormat::from_path(Path::new("out.CSV")), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::from_path(Path::new("out")), None);
+        assert_eq!(ExportColumn::parse_list("path, size,"), Ok(vec![ExportColumn::Path, ExportColumn::Size]));
+        assert!(ExportColumn::parse_list("path,owner").is_err());
+        assert_eq!(export::iso_8601(SystemTime::UNIX_EPOCH), "1970-01-01T00:00:00Z");
+    }
+}
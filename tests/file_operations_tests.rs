@@ -0,0 +1,330 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::{
+        collections::HashMap,
+        fs,
+        path::Path,
+    };
+    use win_expl::file_operations::{
+        self, ConflictPolicy, DeleteMode, FileEvent, FileJob, Operation, OperationKind, Outcome,
+    };
+
+    fn setup(name: &str) -> TempDir {
+        let root = TempDir::new(&format!("file_operations_{}", name));
+        fs::create_dir_all(root.join("src").join("folder").join("inner")).unwrap();
+        fs::create_dir_all(root.join("dst")).unwrap();
+        fs::write(root.join("src").join("a.txt"), "aaa").unwrap();
+        fs::write(root.join("src").join("folder").join("b.txt"), "bbbb").unwrap();
+        fs::write(
+            root.join("src").join("folder").join("inner").join("c.txt"),
+            "cc",
+        )
+        .unwrap();
+        root
+    }
+
+    fn path(p: &Path) -> String {
+        p.to_string_lossy().to_string()
+    }
+
+    fn never_asked(_: &file_operations::Conflict) -> ConflictPolicy {
+        panic!("nothing should be asked")
+    }
+
+    #[test]
+    fn test_copy_files_and_folders() {
+        let root = setup("copy");
+        let sources = vec![
+            path(&root.join("src").join("a.txt")),
+            path(&root.join("src").join("folder")),
+        ];
+        let mut progress = Vec::new();
+        let report = file_operations::copy(
+            &sources,
+            &path(&root.join("dst")),
+            ConflictPolicy::Skip,
+            &mut never_asked,
+            &mut |p| {
+                progress.push(p.done);
+                true
+            },
+        );
+
+        assert_eq!(report.done(), 2);
+        assert_eq!(report.bytes, 9);
+        assert_eq!(progress.last(), Some(&9));
+        assert_eq!(
+            fs::read_to_string(root.join("dst").join("a.txt")).unwrap(),
+            "aaa"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("dst").join("folder").join("inner").join("c.txt"))
+                .unwrap(),
+            "cc"
+        );
+        assert!(root.join("src").join("a.txt").exists());
+        assert_eq!(report.to_string(), "Copied 2");
+    }
+
+    #[test]
+    fn test_conflict_policies() {
+        let root = setup("conflicts");
+        let source = vec![path(&root.join("src").join("a.txt"))];
+        let dst = path(&root.join("dst"));
+        fs::write(root.join("dst").join("a.txt"), "old").unwrap();
+        assert_eq!(file_operations::conflicts(&source, &dst).len(), 1);
+
+        let report = file_operations::copy(
+            &source,
+            &dst,
+            ConflictPolicy::Skip,
+            &mut never_asked,
+            &mut |_| true,
+        );
+        assert_eq!(report.skipped(), 1);
+        assert_eq!(report.to_string(), "Copied 0, 1 skipped");
+        assert_eq!(
+            fs::read_to_string(root.join("dst").join("a.txt")).unwrap(),
+            "old"
+        );
+
+        let report = file_operations::copy(
+            &source,
+            &dst,
+            ConflictPolicy::KeepBoth,
+            &mut never_asked,
+            &mut |_| true,
+        );
+        assert_eq!(
+            report.items[0].target,
+            Some(path(&root.join("dst").join("a (2).txt")))
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("dst").join("a (2).txt")).unwrap(),
+            "aaa"
+        );
+
+        let mut asked = 0;
+        let report = file_operations::copy(
+            &source,
+            &dst,
+            ConflictPolicy::Ask,
+            &mut |_| {
+                asked += 1;
+                ConflictPolicy::Overwrite
+            },
+            &mut |_| true,
+        );
+        assert_eq!(asked, 1);
+        assert_eq!(report.done(), 1);
+        assert_eq!(
+            fs::read_to_string(root.join("dst").join("a.txt")).unwrap(),
+            "aaa"
+        );
+
+        //Overwriting a file with itself would destroy it
+        let report = file_operations::copy(
+            &source,
+            &path(&root.join("src")),
+            ConflictPolicy::Overwrite,
+            &mut never_asked,
+            &mut |_| true,
+        );
+        assert_eq!(report.skipped(), 1);
+        assert_eq!(
+            fs::read_to_string(root.join("src").join("a.txt")).unwrap(),
+            "aaa"
+        );
+    }
+
+    #[test]
+    fn test_move() {
+        let root = setup("move");
+        let sources = vec![path(&root.join("src").join("folder"))];
+        let mut last = None;
+        let report = file_operations::move_to(
+            &sources,
+            &path(&root.join("dst")),
+            ConflictPolicy::Skip,
+            &mut never_asked,
+            &mut |p| {
+                last = Some((p.done, p.total));
+                true
+            },
+        );
+        assert_eq!(report.kind, OperationKind::Move);
+        assert_eq!(report.done(), 1);
+        //Renamed at once, but the progress still ends at the size of the folder
+        assert_eq!(last, Some((6, 6)));
+        assert!(!root.join("src").join("folder").exists());
+        assert!(root.join("dst").join("folder").join("b.txt").exists());
+
+        //A folder cant end up inside itself
+        let sources = vec![path(&root.join("dst"))];
+        let report = file_operations::move_to(
+            &sources,
+            &path(&root.join("dst").join("folder")),
+            ConflictPolicy::Skip,
+            &mut never_asked,
+            &mut |_| true,
+        );
+        assert_eq!(report.errors().len(), 1);
+
+        //Also when the target is spelled differently
+        let report = file_operations::copy(
+            &sources,
+            &path(&root.join("src").join("..").join("dst").join("folder")),
+            ConflictPolicy::Skip,
+            &mut never_asked,
+            &mut |_| true,
+        );
+        assert!(report.errors()[0].ends_with("A folder cant be put into itself"));
+    }
+
+    #[test]
+    fn test_overwriting_a_folder_merges() {
+        let root = setup("merge");
+        let existing = root.join("dst").join("folder");
+        fs::create_dir_all(existing.join("inner")).unwrap();
+        fs::write(existing.join("b.txt"), "old").unwrap();
+        fs::write(existing.join("keep.txt"), "keep").unwrap();
+        fs::write(existing.join("inner").join("d.txt"), "dd").unwrap();
+
+        let sources = vec![path(&root.join("src").join("folder"))];
+        let report = file_operations::move_to(
+            &sources,
+            &path(&root.join("dst")),
+            ConflictPolicy::Overwrite,
+            &mut never_asked,
+            &mut |_| true,
+        );
+        assert_eq!(report.done(), 1);
+        assert!(report.items[0].replaced);
+        assert!(!root.join("src").join("folder").exists());
+        assert_eq!(fs::read_to_string(existing.join("b.txt")).unwrap(), "bbbb");
+        assert_eq!(fs::read_to_string(existing.join("keep.txt")).unwrap(), "keep");
+        assert!(existing.join("inner").join("c.txt").exists());
+        assert!(existing.join("inner").join("d.txt").exists());
+        assert_eq!(fs::read_dir(&existing).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn test_rename_and_new_folder() {
+        let root = setup("rename");
+        let file = path(&root.join("src").join("a.txt"));
+
+        let report =
+            file_operations::rename(&file, "bad?.txt", ConflictPolicy::Skip, &mut never_asked);
+        assert!(matches!(report.items[0].outcome, Outcome::Failed(_)));
+        assert!(file_operations::invalid_name_reason("name.").is_some());
+        assert!(file_operations::invalid_name_reason("..").is_some());
+        assert!(file_operations::invalid_name_reason("fine name.txt").is_none());
+
+        let report =
+            file_operations::rename(&file, "A.txt", ConflictPolicy::Skip, &mut never_asked);
+        assert_eq!(report.done(), 1);
+        assert!(root.join("src").join("A.txt").exists());
+
+        let file = path(&root.join("src").join("A.txt"));
+        let report =
+            file_operations::rename(&file, "folder", ConflictPolicy::Skip, &mut never_asked);
+        assert_eq!(report.skipped(), 1);
+
+        let report = file_operations::new_folder(&path(&root.join("src")), "folder");
+        assert_eq!(
+            report.items[0].target,
+            Some(path(&root.join("src").join("folder (2)")))
+        );
+        assert!(root.join("src").join("folder (2)").is_dir());
+    }
+
+    #[test]
+    fn test_delete_permanently() {
+        let root = setup("delete");
+        let paths = vec![
+            path(&root.join("src").join("a.txt")),
+            path(&root.join("src").join("folder")),
+            path(&root.join("src").join("missing")),
+        ];
+        let report = file_operations::delete(&paths, DeleteMode::Permanent);
+        assert_eq!(report.done(), 2);
+        assert_eq!(report.errors().len(), 1);
+        assert_eq!(report.to_string(), "Deleted 2, 1 failed");
+        assert!(!root.join("src").join("folder").exists());
+    }
+
+    #[test]
+    fn test_cancel_removes_partial_copy() {
+        let root = setup("cancel");
+        let sources = vec![
+            path(&root.join("src").join("folder")),
+            path(&root.join("src").join("a.txt")),
+        ];
+        let report = file_operations::copy(
+            &sources,
+            &path(&root.join("dst")),
+            ConflictPolicy::Skip,
+            &mut never_asked,
+            &mut |_| false,
+        );
+
+        assert!(report.cancelled);
+        assert_eq!(report.done(), 0);
+        assert!(!root.join("dst").join("folder").exists());
+        assert!(!root.join("dst").join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_cancelled_overwrite_keeps_the_target() {
+        let root = setup("overwrite");
+        fs::create_dir_all(root.join("dst").join("folder")).unwrap();
+        fs::write(root.join("dst").join("folder").join("old.txt"), "old").unwrap();
+        let sources = vec![path(&root.join("src").join("folder"))];
+
+        let report = file_operations::copy(
+            &sources,
+            &path(&root.join("dst")),
+            ConflictPolicy::Overwrite,
+            &mut never_asked,
+            &mut |_| false,
+        );
+        assert!(report.cancelled);
+        assert!(root.join("dst").join("folder").join("old.txt").exists());
+        assert_eq!(fs::read_dir(root.join("dst")).unwrap().count(), 1);
+
+        let report = file_operations::move_to(
+            &sources,
+            &path(&root.join("dst")),
+            ConflictPolicy::Overwrite,
+            &mut never_asked,
+            &mut |_| true,
+        );
+        assert_eq!(report.done(), 1);
+        //Merged, so the old file is still there
+        assert!(root.join("dst").join("folder").join("old.txt").exists());
+        assert!(root.join("dst").join("folder").join("inner").join("c.txt").exists());
+        assert_eq!(fs::read_dir(root.join("dst")).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_job_uses_decisions() {
+        let root = setup("job");
+        fs::write(root.join("dst").join("a.txt"), "old").unwrap();
+        let a = path(&root.join("src").join("a.txt"));
+        let operation = Operation::Copy {
+            sources: vec![a.clone(), path(&root.join("src").join("folder"))],
+            target_dir: path(&root.join("dst")),
+        };
+        let decisions = HashMap::from([(a, ConflictPolicy::KeepBoth)]);
+
+        let job = FileJob::spawn(operation, ConflictPolicy::Ask, decisions, || {});
+        match job.wait().pop() {
+            Some(FileEvent::Finished(report)) => assert_eq!(report.done(), 2),
+            e => panic!("unexpected event {:?}", e),
+        }
+        assert!(root.join("dst").join("a (2).txt").exists());
+    }
+}
@@ -0,0 +1,111 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::{
+        fs,
+        path::Path,
+    };
+    use win_expl::{
+        file_types::FileCategory,
+        search_engine::{
+            parameter_parser::SearchEngineParameter,
+            result::SearchEngineResult,
+            sort::{self, SortColumn, SortKey, SortOrder},
+            SearchEngine,
+        },
+    };
+
+    fn setup(name: &str) -> TempDir {
+        let root = TempDir::new(&format!("file_types_{}", name));
+        fs::create_dir_all(root.join("folder")).unwrap();
+        fs::write(root.join("photo.JPG"), "not really a jpg").unwrap();
+        fs::write(root.join("song.mp3"), "").unwrap();
+        fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("notes.txt"), "").unwrap();
+        fs::write(root.join("setup.exe"), "MZ").unwrap();
+        fs::write(root.join("unknown.xyz"), "").unwrap();
+        //No extension, recognized by their content
+        fs::write(root.join("picture"), b"\x89PNG\r\n\x1a\n").unwrap();
+        fs::write(root.join("plain"), "hello").unwrap();
+        root
+    }
+
+    fn category(root: &Path, name: &str) -> FileCategory {
+        SearchEngineResult::from_path(&root.join(name).to_string_lossy())
+            .unwrap()
+            .category
+    }
+
+    #[test]
+    fn test_classify() {
+        let root = setup("classify");
+        assert_eq!(category(&root, "folder"), FileCategory::Directory);
+        assert_eq!(category(&root, "photo.JPG"), FileCategory::Image);
+        assert_eq!(category(&root, "song.mp3"), FileCategory::Audio);
+        assert_eq!(category(&root, "main.rs"), FileCategory::Code);
+        assert_eq!(category(&root, "notes.txt"), FileCategory::Document);
+        assert_eq!(category(&root, "setup.exe"), FileCategory::Executable);
+        assert_eq!(category(&root, "unknown.xyz"), FileCategory::Other);
+        assert_eq!(category(&root, "picture"), FileCategory::Image);
+        assert_eq!(category(&root, "plain"), FileCategory::Other);
+        assert_eq!(FileCategory::Other.label(), "File");
+    }
+
+    #[test]
+    fn test_magic_and_names() {
+        assert_eq!(FileCategory::from_magic(b"RIFF\0\0\0\0WAVEfmt "), Some(FileCategory::Audio));
+        assert_eq!(FileCategory::from_magic(b"RIFF\0\0\0\0AVI LIST"), Some(FileCategory::Video));
+        assert_eq!(FileCategory::from_magic(b"\0\0\0\x20ftypisom"), Some(FileCategory::Video));
+        assert_eq!(FileCategory::from_magic(b"\0\0\0\x20ftypM4A "), Some(FileCategory::Audio));
+        assert_eq!(FileCategory::from_magic(b"PK\x03\x04"), Some(FileCategory::Archive));
+        assert_eq!(FileCategory::from_magic(b"%PDF-1.7"), Some(FileCategory::Document));
+        assert_eq!(FileCategory::from_magic(b"\x7FELF"), Some(FileCategory::Executable));
+        assert_eq!(FileCategory::from_magic(b"#!/bin/sh"), Some(FileCategory::Code));
+        assert_eq!(FileCategory::from_magic(b"RIFF"), None);
+        assert_eq!(FileCategory::from_magic(b""), None);
+
+        assert_eq!(FileCategory::from_name("Images"), Some(FileCategory::Image));
+        assert_eq!(FileCategory::from_name("folder"), Some(FileCategory::Directory));
+        assert_eq!(FileCategory::from_name("code"), Some(FileCategory::Code));
+        assert_eq!(FileCategory::from_name("file"), Some(FileCategory::Other));
+        assert_eq!(FileCategory::from_name("pictures"), None);
+
+        for (i, c) in FileCategory::ALL.iter().enumerate() {
+            assert_eq!(c.icon_index(), i);
+        }
+    }
+
+    #[test]
+    fn test_type_filter_and_sort() {
+        let root = setup("filter");
+        let p = SearchEngineParameter::parse_search_term("type:image").unwrap();
+        assert_eq!(p.category, Some(FileCategory::Image));
+        let mut names: Vec<String> = SearchEngine::search(&p, &root.to_string_lossy(), 0)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["photo.JPG", "picture"]);
+
+        let results = fs::read_dir(&root)
+            .unwrap()
+            .map(|e| SearchEngineResult::from(&e.unwrap()))
+            .collect();
+        let order = SortOrder {
+            folders_first: false,
+            columns: vec![
+                SortColumn { key: SortKey::Type, descending: false },
+                SortColumn { key: SortKey::Name, descending: false },
+            ],
+        };
+        let categories: Vec<FileCategory> = sort::sort_results(results, &order).iter().map(|r| r.category).collect();
+        let mut expected = categories.clone();
+        expected.sort();
+        assert_eq!(categories, expected);
+        assert_eq!(categories.first(), Some(&FileCategory::Directory));
+        assert_eq!(categories.last(), Some(&FileCategory::Other));
+    }
+}
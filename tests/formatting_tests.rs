@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use time::UtcOffset;
+    use win_expl::formatting::{self, DateStyle, Formatter, SizeUnits};
+
+    #[test]
+    fn test_size_binary_units() {
+        assert_eq!(formatting::format_size(0, SizeUnits::Binary), "0 B");
+        assert_eq!(formatting::format_size(1023, SizeUnits::Binary), "1023 B");
+        assert_eq!(formatting::format_size(1536, SizeUnits::Binary), "1.5 KiB");
+        assert_eq!(formatting::format_size(150 * 1024 * 1024, SizeUnits::Binary), "150 MiB");
+        assert_eq!(formatting::format_size(5_000_000_000, SizeUnits::Binary), "4.7 GiB");
+        assert_eq!(formatting::format_size(1024 * 1024 - 1, SizeUnits::Binary), "1.0 MiB");
+    }
+
+    #[test]
+    fn test_size_si_units() {
+        assert_eq!(formatting::format_size(999, SizeUnits::Si), "999 B");
+        assert_eq!(formatting::format_size(1000, SizeUnits::Si), "1.0 kB");
+        assert_eq!(formatting::format_size(5_000_000_000, SizeUnits::Si), "5.0 GB");
+        assert_eq!(formatting::format_size(u64::MAX, SizeUnits::Si), "18447 PB");
+    }
+
+    #[test]
+    fn test_relative_dates() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let ago = |secs| now - Duration::from_secs(secs);
+        assert_eq!(formatting::format_relative(ago(5), now), "just now");
+        assert_eq!(formatting::format_relative(ago(60), now), "1 minute ago");
+        assert_eq!(formatting::format_relative(ago(3 * 60 * 60 + 5), now), "3 hours ago");
+        assert_eq!(formatting::format_relative(ago(2 * 24 * 60 * 60), now), "2 days ago");
+        assert_eq!(formatting::format_relative(ago(400 * 24 * 60 * 60), now), "1 year ago");
+        assert_eq!(
+            formatting::format_relative(now + Duration::from_secs(120), now),
+            "in 2 minutes"
+        );
+    }
+
+    #[test]
+    fn test_date_pattern() {
+        let t = UNIX_EPOCH + Duration::from_secs(24 * 60 * 60 + 90);
+        let f = Formatter::with_offset(SizeUnits::Binary, &DateStyle::default(), UtcOffset::UTC).unwrap();
+        assert_eq!(f.date_at(t, SystemTime::now()), "1970-01-02 00:01");
+
+        let offset = UtcOffset::from_hms(2, 0, 0).unwrap();
+        let style = DateStyle::Pattern("[day].[month].[year] [hour]h".into());
+        let f = Formatter::with_offset(SizeUnits::Si, &style, offset).unwrap();
+        assert_eq!(f.date_at(t, SystemTime::now()), "02.01.1970 02h");
+
+        let f = Formatter::with_offset(SizeUnits::Si, &DateStyle::Relative, UtcOffset::UTC).unwrap();
+        assert_eq!(f.date_at(t, t + Duration::from_secs(7200)), "2 hours ago");
+
+        assert!(Formatter::new(SizeUnits::Si, &DateStyle::Pattern("[nonsense]".into())).is_err());
+    }
+}
@@ -0,0 +1,71 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::fs;
+    use win_expl::hashing::{self, HashError, HashEvent, HashFunction, HashJob};
+
+    fn hash_abc(hf: HashFunction) -> String {
+        hashing::to_hex(&hashing::hash_reader("abc".as_bytes(), hf, |_| true).unwrap())
+    }
+
+    #[test]
+    fn test_known_digests() {
+        assert_eq!(hash_abc(HashFunction::MD5), "900150983CD24FB0D6963F7D28E17F72");
+        assert_eq!(hash_abc(HashFunction::SHA1), "A9993E364706816ABA3E25717850C26C9CD0D89D");
+        assert_eq!(
+            hash_abc(HashFunction::SHA256),
+            "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD"
+        );
+        assert_eq!(
+            hash_abc(HashFunction::BLAKE3),
+            "6437B3AC38465133FFB63B75273A8DB548C558465D79DB03FD359C6CD5BD9D85"
+        );
+        assert_eq!(hash_abc(HashFunction::CRC32), "352441C2");
+        assert_eq!(hash_abc(HashFunction::XXH3).len(), 16);
+    }
+
+    #[test]
+    fn test_names_round_trip() {
+        for hf in HashFunction::ALL {
+            assert_eq!(HashFunction::from_name(&hf.name().to_lowercase()), Some(hf));
+        }
+        assert_eq!(HashFunction::from_name("sha3"), None);
+    }
+
+    #[test]
+    fn test_progress_can_cancel() {
+        let data = vec![0u8; 3 * 1024 * 1024];
+        let mut calls = 0;
+        let res = hashing::hash_reader(data.as_slice(), HashFunction::SHA1, |_| {
+            calls += 1;
+            false
+        });
+        assert!(res.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_job_hashes_multiple_files() {
+        let root = TempDir::new("hashing_job");
+        let file = root.join("abc.txt");
+        fs::write(&file, "abc").unwrap();
+        let file = file.to_string_lossy().to_string();
+        let dir = root.to_string_lossy().to_string();
+
+        let job = HashJob::spawn(vec![file.clone(), dir.clone()], HashFunction::MD5, || {});
+        let events = job.wait();
+
+        let mut results = events.iter().filter_map(|e| match e {
+            HashEvent::File(p, r) => Some((p.clone(), r)),
+            _ => None,
+        });
+        let (path, first) = results.next().unwrap();
+        assert_eq!(path, file);
+        assert_eq!(first, &Ok("900150983CD24FB0D6963F7D28E17F72".to_string()));
+        let (_, second) = results.next().unwrap();
+        assert_eq!(second, &Err(HashError::IsDirectory(dir)));
+        assert!(matches!(events.last(), Some(HashEvent::Finished)));
+    }
+}
@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+    use win_expl::job::Job;
+
+    #[test]
+    fn test_events_arrive_in_order_and_notify() {
+        let notified = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&notified);
+        let job = Job::start(
+            |reporter| {
+                for i in 0..3 {
+                    reporter.send(i);
+                }
+            },
+            move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+            },
+        );
+
+        assert_eq!(job.wait(), vec![0, 1, 2]);
+        assert_eq!(notified.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_cancel_stops_the_work() {
+        let job = Job::start(
+            |reporter| {
+                let mut steps = 0;
+                while !reporter.is_cancelled() {
+                    steps += 1;
+                    thread::sleep(Duration::from_millis(1));
+                }
+                reporter.send(steps);
+            },
+            || {},
+        );
+        assert!(job.poll().is_empty());
+
+        job.cancel();
+        assert_eq!(job.wait().len(), 1);
+    }
+
+    #[test]
+    fn test_dropping_cancels() {
+        let flag = Arc::new(AtomicUsize::new(0));
+        let worker_flag = Arc::clone(&flag);
+        let job = Job::<()>::start(
+            move |reporter| {
+                let cancelled = reporter.cancel_flag();
+                while !cancelled.load(Ordering::Relaxed) {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                worker_flag.store(1, Ordering::Relaxed);
+            },
+            || {},
+        );
+        drop(job);
+
+        for _ in 0..1000 {
+            if flag.load(Ordering::Relaxed) == 1 {
+                return;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        panic!("the job was not cancelled");
+    }
+}
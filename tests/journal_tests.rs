@@ -0,0 +1,240 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::{fs, path::PathBuf};
+    use win_expl::{
+        file_operations::{self, ConflictPolicy},
+        journal::{Action, Journal, JournalEntry},
+        ring_buffer::RingBuffer,
+    };
+
+    fn setup(name: &str) -> TempDir {
+        let root = TempDir::new(&format!("journal_{}", name));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("dst")).unwrap();
+        fs::write(root.join("src").join("a.txt"), "aaa").unwrap();
+        root
+    }
+
+    fn path(p: PathBuf) -> String {
+        p.to_string_lossy().to_string()
+    }
+
+    fn no_conflicts(_: &file_operations::Conflict) -> ConflictPolicy {
+        ConflictPolicy::Skip
+    }
+
+    #[test]
+    fn test_undo_and_redo_move() {
+        let root = setup("move");
+        let sources = vec![path(root.join("src").join("a.txt"))];
+        let report = file_operations::move_to(
+            &sources,
+            &path(root.join("dst")),
+            ConflictPolicy::Skip,
+            &mut no_conflicts,
+            &mut |_| true,
+        );
+        let mut journal = Journal::default();
+        journal.record(JournalEntry::from_report(&report).unwrap());
+        assert_eq!(journal.undo_description(), Some("Moved 1"));
+
+        assert_eq!(journal.undo(), Ok(String::from("Moved 1")));
+        assert!(root.join("src").join("a.txt").exists());
+        assert!(!root.join("dst").join("a.txt").exists());
+        assert_eq!(journal.undo_description(), None);
+        assert!(journal.undo().is_err());
+
+        assert_eq!(journal.redo(), Ok(String::from("Moved 1")));
+        assert!(root.join("dst").join("a.txt").exists());
+        assert_eq!(journal.redo_description(), None);
+    }
+
+    #[test]
+    fn test_refuses_changed_files() {
+        let root = setup("changed");
+        let sources = vec![path(root.join("src").join("a.txt"))];
+        let report = file_operations::copy(
+            &sources,
+            &path(root.join("dst")),
+            ConflictPolicy::Skip,
+            &mut no_conflicts,
+            &mut |_| true,
+        );
+        let mut journal = Journal::default();
+        journal.record(JournalEntry::from_report(&report).unwrap());
+
+        fs::write(root.join("dst").join("a.txt"), "edited since").unwrap();
+        assert!(journal.undo().is_err());
+        assert!(root.join("dst").join("a.txt").exists());
+        //The entry stays, so it can be tried again
+        assert_eq!(journal.undo_description(), Some("Copied 1"));
+
+        fs::remove_file(root.join("dst").join("a.txt")).unwrap();
+        assert!(journal.undo().is_err());
+    }
+
+    #[test]
+    fn test_rename_and_new_folder() {
+        let root = setup("rename");
+        let mut journal = Journal::default();
+        let report = file_operations::rename(
+            &path(root.join("src").join("a.txt")),
+            "b.txt",
+            ConflictPolicy::Skip,
+            &mut no_conflicts,
+        );
+        journal.record(JournalEntry::from_report(&report).unwrap());
+        let report = file_operations::new_folder(&path(root.join("src")), "new");
+        journal.record(JournalEntry::from_report(&report).unwrap());
+
+        //Folders that arent empty anymore are left alone
+        fs::write(root.join("src").join("new").join("x.txt"), "x").unwrap();
+        assert!(journal.undo().is_err());
+        fs::remove_file(root.join("src").join("new").join("x.txt")).unwrap();
+
+        journal.undo().unwrap();
+        assert!(!root.join("src").join("new").exists());
+        journal.undo().unwrap();
+        assert!(root.join("src").join("a.txt").exists());
+        assert!(!root.join("src").join("b.txt").exists());
+
+        //A new operation drops what was undone
+        let report = file_operations::new_folder(&path(root.join("src")), "other");
+        journal.record(JournalEntry::from_report(&report).unwrap());
+        assert_eq!(journal.redo_description(), None);
+    }
+
+    #[test]
+    fn test_permanent_delete_isnt_recorded() {
+        let root = setup("delete");
+        let report = file_operations::delete(
+            &[path(root.join("src").join("a.txt"))],
+            file_operations::DeleteMode::Permanent,
+        );
+        assert!(JournalEntry::from_report(&report).is_none());
+    }
+
+    #[test]
+    fn test_overwriting_copy_isnt_recorded() {
+        let root = setup("overwrite");
+        fs::write(root.join("dst").join("a.txt"), "old").unwrap();
+        fs::write(root.join("src").join("b.txt"), "bbb").unwrap();
+        let sources = vec![path(root.join("src").join("a.txt")), path(root.join("src").join("b.txt"))];
+        let report = file_operations::copy(
+            &sources,
+            &path(root.join("dst")),
+            ConflictPolicy::Overwrite,
+            &mut no_conflicts,
+            &mut |_| true,
+        );
+        assert_eq!(report.done(), 2);
+        assert!(report.items[0].replaced);
+
+        let mut journal = Journal::default();
+        journal.record(JournalEntry::from_report(&report).unwrap());
+        journal.undo().unwrap();
+        assert_eq!(fs::read_to_string(root.join("dst").join("a.txt")).unwrap(), "aaa");
+        assert!(!root.join("dst").join("b.txt").exists());
+
+        let sources = vec![path(root.join("src").join("a.txt"))];
+        let report = file_operations::copy(
+            &sources,
+            &path(root.join("dst")),
+            ConflictPolicy::Overwrite,
+            &mut no_conflicts,
+            &mut |_| true,
+        );
+        assert!(JournalEntry::from_report(&report).is_none());
+    }
+
+    #[test]
+    fn test_persists_bounded() {
+        let root = setup("persist");
+        let file = path(root.join("journal.json"));
+        let mut journal = Journal::default();
+        for i in 0..60 {
+            let report = file_operations::new_folder(&path(root.join("src")), &format!("f{}", i));
+            journal.record(JournalEntry::from_report(&report).unwrap());
+        }
+        journal.save(&file).unwrap();
+
+        let mut loaded = Journal::load(&file);
+        let mut count = 0;
+        while loaded.undo().is_ok() {
+            count += 1;
+        }
+        assert_eq!(count, 49);
+        assert!(root.join("src").join("f10").exists());
+        assert!(!root.join("src").join("f11").exists());
+
+        assert!(Journal::load(root.join("missing.json"))
+            .undo_description()
+            .is_none());
+    }
+
+    #[test]
+    fn test_failed_undo_keeps_the_rest() {
+        let root = setup("partial");
+        fs::write(root.join("dst").join("a.txt"), "aaa").unwrap();
+        fs::create_dir(root.join("new")).unwrap();
+        //Passes the checks, but the folder to move back into is gone
+        let moved = Action::Moved {
+            from: path(root.join("gone").join("a.txt")),
+            to: path(root.join("dst").join("a.txt")),
+            stamp: None,
+        };
+        let created = Action::CreatedFolder { path: path(root.join("new")) };
+        let mut journal = Journal::default();
+        journal.record(JournalEntry {
+            description: String::from("Both"),
+            actions: vec![moved, created],
+        });
+
+        assert!(journal.undo().is_err());
+        assert!(!root.join("new").exists());
+        assert!(journal.redo().is_ok());
+        assert!(root.join("new").exists());
+
+        //The redone folder is on top, below it the move that failed
+        assert!(journal.undo().is_ok());
+        assert!(!root.join("new").exists());
+        assert!(journal.undo().is_err());
+        assert!(root.join("dst").join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_broken_buffers_load_empty() {
+        let root = setup("broken");
+        let file = root.join("journal.json");
+        let broken = r#"{"size":0,"items":[]}"#;
+        fs::write(&file, format!(r#"{{"undo":{0},"redo":{0}}}"#, broken)).unwrap();
+
+        let mut loaded = Journal::load(&file);
+        assert!(loaded.undo_description().is_none());
+        assert!(loaded.undo().is_err());
+        assert!(loaded.redo().is_err());
+    }
+
+    #[test]
+    fn test_ring_buffer_peek() {
+        let mut buffer = RingBuffer::new(3);
+        assert_eq!(buffer.peek(), None);
+        buffer.push(1);
+        buffer.push(2);
+        buffer.push(3);
+        assert_eq!(buffer.peek(), Some(&3));
+        assert_eq!(buffer.pop(), Some(3));
+        assert_eq!(buffer.pop(), Some(2));
+        assert_eq!(buffer.pop(), None);
+        buffer.push(4);
+        buffer.push(5);
+        buffer.push(6);
+        assert_eq!(buffer.to_vec(), vec![5, 6]);
+        buffer.clear();
+        assert!(buffer.to_vec().is_empty());
+        assert!(buffer.all_read());
+    }
+}
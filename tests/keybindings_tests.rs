@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use win_expl::keybindings::{KeyAction, KeyChord, Keymap, VirtualKeyCode};
+
+    fn overrides(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(a, c)| (a.to_string(), c.to_string())).collect()
+    }
+
+    #[test]
+    fn test_parse_chords() {
+        let chord = KeyChord::parse("Ctrl+Shift+C").unwrap();
+        assert_eq!(chord, KeyChord::new(VirtualKeyCode::VK_C).ctrl().shift());
+        assert_eq!(KeyChord::parse(" alt + left ").unwrap(), KeyChord::new(VirtualKeyCode::VK_LEFT).alt());
+        assert_eq!(KeyChord::parse("shift+ctrl+f12").unwrap(), KeyChord::new(VirtualKeyCode::VK_F12).ctrl().shift());
+        assert_eq!(KeyChord::parse("Del").unwrap(), KeyChord::new(VirtualKeyCode::VK_DELETE));
+        assert_eq!(KeyChord::parse("Control+7").unwrap(), KeyChord::new(VirtualKeyCode::VK_7).ctrl());
+        assert_eq!(KeyChord::parse("Ctrl+PgDn").unwrap(), KeyChord::new(VirtualKeyCode::VK_NEXT).ctrl());
+
+        assert!(KeyChord::parse("").is_err());
+        assert!(KeyChord::parse("Ctrl+").is_err());
+        assert!(KeyChord::parse("Ctrl+Ctrl+C").is_err());
+        assert!(KeyChord::parse("Hyper+C").is_err());
+        assert!(KeyChord::parse("Ctrl+F25").is_err());
+        assert!(KeyChord::parse("Ctrl+Shift").is_err());
+    }
+
+    #[test]
+    fn test_chords_display_as_they_parse() {
+        for text in ["Ctrl+Shift+C", "Alt+Left", "F5", "Shift+Delete", "Ctrl+PageUp", "Ctrl+Alt+0", "Ctrl+Plus"] {
+            let chord = KeyChord::parse(text).unwrap();
+            assert_eq!(chord.to_string(), text);
+            assert_eq!(KeyChord::parse(&chord.to_string()).unwrap(), chord);
+        }
+    }
+
+    #[test]
+    fn test_defaults() {
+        let map = Keymap::default();
+        assert_eq!(map.action(&KeyChord::parse("Alt+Left").unwrap()), Some(KeyAction::Back));
+        assert_eq!(map.action(&KeyChord::parse("Ctrl+L").unwrap()), Some(KeyAction::FocusPathBar));
+        assert_eq!(map.action(&KeyChord::parse("Delete").unwrap()), Some(KeyAction::Trash));
+        assert_eq!(map.action(&KeyChord::parse("Shift+Delete").unwrap()), Some(KeyAction::Delete));
+        assert_eq!(map.action(&KeyChord::parse("Ctrl+Shift+C").unwrap()), None);
+        assert_eq!(
+            map.action_for_key(VirtualKeyCode::VK_F5 as u32, false, false, false),
+            Some(KeyAction::Refresh)
+        );
+        assert_eq!(map.action_for_key(VirtualKeyCode::VK_F5 as u32, true, false, false), None);
+
+        //Every action has its own chord
+        for a in KeyAction::ALL {
+            let chord = map.chord(a).unwrap();
+            assert_eq!(map.action(&chord), Some(a));
+            assert_eq!(KeyAction::from_key(a.key()), Some(a));
+        }
+    }
+
+    #[test]
+    fn test_overrides_and_conflicts() {
+        let (map, problems) = Keymap::with_overrides(&overrides(&[("refresh", "Ctrl+R"), ("copy_path", "")]));
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(map.chord(KeyAction::Refresh), KeyChord::parse("Ctrl+R").ok());
+        assert_eq!(map.action(&KeyChord::parse("F5").unwrap()), None);
+        assert_eq!(map.chord(KeyAction::CopyPath), None);
+
+        //Taking the chord of a default binding unbinds it and says so
+        let (map, problems) = Keymap::with_overrides(&overrides(&[("refresh", "F2")]));
+        assert_eq!(map.action(&KeyChord::parse("F2").unwrap()), Some(KeyAction::Refresh));
+        assert_eq!(map.chord(KeyAction::Rename), None);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("rename"), "{}", problems[0]);
+
+        //Unless that action got a new shortcut as well
+        let (map, problems) = Keymap::with_overrides(&overrides(&[("refresh", "F2"), ("rename", "F5")]));
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(map.chord(KeyAction::Refresh), KeyChord::parse("F2").ok());
+        assert_eq!(map.chord(KeyAction::Rename), KeyChord::parse("F5").ok());
+
+        //Two overrides on one chord, the earlier action wins
+        let (map, problems) = Keymap::with_overrides(&overrides(&[("undo", "Ctrl+U"), ("back", "ctrl+u")]));
+        assert_eq!(map.action(&KeyChord::parse("Ctrl+U").unwrap()), Some(KeyAction::Back));
+        assert_eq!(map.chord(KeyAction::Undo), KeyChord::parse("Ctrl+Z").ok());
+        assert_eq!(problems.len(), 1);
+
+        let (map, problems) = Keymap::with_overrides(&overrides(&[("teleport", "F9"), ("rename", "Ctrl+Nope")]));
+        assert_eq!(map, Keymap::default());
+        assert_eq!(problems.len(), 2);
+    }
+}
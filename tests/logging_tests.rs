@@ -0,0 +1,87 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::fs;
+    use time::OffsetDateTime;
+    use win_expl::logging::{self, Level, RotatingFile, LOG_FILE};
+
+    fn setup(name: &str) -> TempDir {
+        let root = TempDir::new(&format!("logging_{}", name));
+        root
+    }
+
+    #[test]
+    fn test_levels() {
+        assert_eq!(Level::from_name(" Warning"), Some(Level::Warn));
+        assert_eq!(Level::from_name("off"), Some(Level::Off));
+        assert_eq!(Level::from_name("loud"), None);
+        assert!(Level::Error < Level::Info && Level::Info < Level::Trace);
+        assert_eq!(serde_json::from_str::<Level>("\"debug\"").unwrap(), Level::Debug);
+    }
+
+    #[test]
+    fn test_format_line() {
+        let time = OffsetDateTime::from_unix_timestamp(1_699_193_000).unwrap();
+        let line = logging::format_line(
+            time,
+            Level::Warn,
+            "win_expl::cli",
+            &["search{query=\"a b\"}".into()],
+            "Failed\nto read",
+            &[("dir", "C:\\Users".into()), ("error", "not found".into()), ("empty", String::new())],
+        );
+        assert_eq!(
+            line,
+            "2023-11-05T14:03:20.000Z WARN  win_expl::cli: search{query=\"a b\"}: Failed\\nto read \
+             dir=C:\\Users error=\"not found\" empty=\"\""
+        );
+
+        let line = logging::format_line(time, Level::Info, "win_expl", &[], "Starting", &[]);
+        assert_eq!(line, "2023-11-05T14:03:20.000Z INFO  win_expl: Starting");
+    }
+
+    #[test]
+    fn test_rotation() {
+        let root = setup("rotation");
+        let mut log = RotatingFile::open(root.join(LOG_FILE), 20, 2).unwrap();
+        for i in 0..4 {
+            log.write_line(&format!("line {} of the log", i)).unwrap();
+        }
+
+        let read = |n: usize| fs::read_to_string(log.rotated_path(n)).unwrap();
+        assert_eq!(read(0), "line 3 of the log\n");
+        assert_eq!(read(1), "line 2 of the log\n");
+        assert_eq!(read(2), "line 1 of the log\n");
+        //Line 0 was rotated out
+        assert!(!log.rotated_path(3).exists());
+
+        //Appends to the existing log
+        drop(log);
+        let mut log = RotatingFile::open(root.join(LOG_FILE), 100, 2).unwrap();
+        log.write_line("line 4").unwrap();
+        assert_eq!(fs::read_to_string(root.join(LOG_FILE)).unwrap(), "line 3 of the log\nline 4\n");
+    }
+
+    #[test]
+    fn test_init_writes_records_with_spans() {
+        let root = setup("init");
+        logging::init(&root, Level::Debug).unwrap();
+        {
+            let _span = win_expl::span!("search", query = "a b");
+            win_expl::info!("Searched {}", "C:"; results = 3);
+            win_expl::trace!("Not written");
+        }
+        win_expl::warn!("After the span");
+        logging::set_level(Level::Off);
+        win_expl::error!("Not written either");
+
+        let log = fs::read_to_string(root.join(LOG_FILE)).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", log);
+        assert!(lines[0].ends_with("INFO  logging_tests::tests: search{query=\"a b\"}: Searched C: results=3"));
+        assert!(lines[1].contains("DEBUG logging_tests::tests: search{query=\"a b\"}: Done elapsed_ms="));
+        assert!(lines[2].ends_with("WARN  logging_tests::tests: After the span"));
+    }
+}
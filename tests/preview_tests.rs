@@ -0,0 +1,204 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::{
+        fs,
+        io::Write,
+        path::Path,
+        sync::Arc,
+        time::SystemTime,
+    };
+    use win_expl::preview::{
+        self, PreviewCache, PreviewContent, PreviewJob, Previewer, Previewers, TextEncoding, HEX_PREVIEW_BYTES,
+        TEXT_PREVIEW_BYTES,
+    };
+
+    fn setup(name: &str) -> TempDir {
+        let root = TempDir::new(&format!("preview_{}", name));
+        root
+    }
+
+    #[test]
+    fn test_encodings() {
+        assert_eq!(preview::detect_encoding("héllo".as_bytes()), Some(TextEncoding::Utf8));
+        //Cut off in the middle of the é
+        assert_eq!(preview::detect_encoding(&"hé".as_bytes()[..2]), Some(TextEncoding::Utf8));
+        assert_eq!(preview::detect_encoding(b"\xEF\xBB\xBFhi"), Some(TextEncoding::Utf8Bom));
+        assert_eq!(preview::detect_encoding(&[0x93, b'a', 0x94]), Some(TextEncoding::Windows1252));
+        assert_eq!(preview::detect_encoding(&[0x01, 0x02, 0x00, 0xFF]), None);
+
+        let utf16: Vec<u8> = [0xFF, 0xFE]
+            .into_iter()
+            .chain("hé".encode_utf16().flat_map(|u| u.to_le_bytes()))
+            .collect();
+        assert_eq!(preview::detect_encoding(&utf16), Some(TextEncoding::Utf16Le));
+        assert_eq!(preview::decode_text(&utf16, TextEncoding::Utf16Le), "hé");
+        assert_eq!(preview::decode_text(&[0x93, b'a', 0x94, 0xE9], TextEncoding::Windows1252), "“a”é");
+        assert_eq!(preview::decode_text(b"\xEF\xBB\xBFhi", TextEncoding::Utf8Bom), "hi");
+        //Encodings forced on bytes without a BOM dont panic
+        assert_eq!(preview::decode_text(b"h", TextEncoding::Utf8Bom), "");
+        assert_eq!(preview::decode_text(b"h", TextEncoding::Utf16Be), "");
+    }
+
+    #[test]
+    fn test_text_and_hex() {
+        let root = setup("text_and_hex");
+        let previewers = Previewers::default();
+
+        fs::write(root.join("short.rs"), "fn main() {}\n").unwrap();
+        let p = previewers.preview(&root.join("short.rs")).unwrap();
+        assert_eq!(p.previewer, "Text");
+        assert_eq!(p.size, 13);
+        assert_eq!(
+            p.content,
+            PreviewContent::Text {
+                text: String::from("fn main() {}\n"),
+                encoding: TextEncoding::Utf8,
+                truncated: false,
+            }
+        );
+
+        fs::write(root.join("long.txt"), "a".repeat(TEXT_PREVIEW_BYTES + 10)).unwrap();
+        match previewers.preview(&root.join("long.txt")).unwrap().content {
+            PreviewContent::Text { text, truncated, .. } => {
+                assert_eq!(text.len(), TEXT_PREVIEW_BYTES);
+                assert!(truncated);
+            }
+            c => panic!("Expected text, got {:?}", c),
+        }
+
+        let binary: Vec<u8> = (0..=255u8).cycle().take(HEX_PREVIEW_BYTES * 2).collect();
+        fs::write(root.join("data.bin"), &binary).unwrap();
+        match previewers.preview(&root.join("data.bin")).unwrap().content {
+            PreviewContent::Hex { dump, truncated } => {
+                assert!(truncated);
+                assert_eq!(dump.lines().count(), HEX_PREVIEW_BYTES / 16);
+                assert_eq!(
+                    dump.lines().nth(2).unwrap(),
+                    "00000020  20 21 22 23 24 25 26 27 28 29 2a 2b 2c 2d 2e 2f  | !\"#$%&'()*+,-./|"
+                );
+            }
+            c => panic!("Expected hex, got {:?}", c),
+        }
+
+        assert!(previewers.preview(&root).is_err());
+    }
+
+    #[test]
+    fn test_image_thumbnail() {
+        let root = setup("image");
+        let mut img = image::RgbaImage::new(512, 256);
+        img.put_pixel(0, 0, image::Rgba([10, 20, 30, 255]));
+        img.save(root.join("wide.png")).unwrap();
+
+        let p = Previewers::default().preview(&root.join("wide.png")).unwrap();
+        assert_eq!(p.previewer, "Image");
+        match p.content {
+            PreviewContent::Image { width, height, thumbnail } => {
+                assert_eq!((width, height), (512, 256));
+                assert_eq!((thumbnail.width, thumbnail.height), (256, 128));
+                assert_eq!(thumbnail.bgra.len(), 256 * 128 * 4);
+
+                let bmp = thumbnail.to_bmp();
+                assert_eq!(&bmp[..2], b"BM");
+                assert_eq!(bmp.len(), 54 + thumbnail.bgra.len());
+                assert_eq!(i32::from_le_bytes(bmp[22..26].try_into().unwrap()), -128);
+            }
+            c => panic!("Expected an image, got {:?}", c),
+        }
+
+        //Broken images fall back to the hex dump
+        fs::write(root.join("broken.png"), b"\x89PNG\r\n\x1a\n\0\0\0\0broken").unwrap();
+        let p = Previewers::default().preview(&root.join("broken.png")).unwrap();
+        assert_eq!(p.previewer, "Hex");
+    }
+
+    #[test]
+    fn test_archives() {
+        let root = setup("archives");
+
+        let mut zip = zip::ZipWriter::new(fs::File::create(root.join("a.zip")).unwrap());
+        let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.add_directory("docs/", options).unwrap();
+        zip.start_file("docs/readme.txt", options).unwrap();
+        zip.write_all(b"hello").unwrap();
+        zip.finish().unwrap();
+
+        let mut tar = tar::Builder::new(fs::File::create(root.join("b.tar")).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(3);
+        header.set_cksum();
+        tar.append_data(&mut header, "x/y.txt", &b"abc"[..]).unwrap();
+        tar.finish().unwrap();
+        drop(tar);
+
+        let previewers = Previewers::default();
+        let entries = |path: &Path| match previewers.preview(path).unwrap().content {
+            PreviewContent::Archive { entries, total } => {
+                assert_eq!(entries.len(), total);
+                entries
+                    .into_iter()
+                    .map(|e| (e.name, e.size, e.is_dir))
+                    .collect::<Vec<_>>()
+            }
+            c => panic!("Expected an archive, got {:?}", c),
+        };
+        assert_eq!(
+            entries(&root.join("a.zip")),
+            vec![(String::from("docs/"), 0, true), (String::from("docs/readme.txt"), 5, false)]
+        );
+        assert_eq!(entries(&root.join("b.tar")), vec![(String::from("x/y.txt"), 3, false)]);
+    }
+
+    struct Shouting;
+
+    impl Previewer for Shouting {
+        fn name(&self) -> &'static str {
+            "Shouting"
+        }
+
+        fn accepts(&self, path: &Path, _head: &[u8]) -> bool {
+            path.extension().is_some_and(|e| e == "txt")
+        }
+
+        fn preview(&self, path: &Path) -> Result<PreviewContent, String> {
+            Ok(PreviewContent::Text {
+                text: fs::read_to_string(path).map_err(|e| e.to_string())?.to_uppercase(),
+                encoding: TextEncoding::Utf8,
+                truncated: false,
+            })
+        }
+    }
+
+    #[test]
+    fn test_register_cache_and_job() {
+        let root = setup("register");
+        fs::write(root.join("a.txt"), "quiet").unwrap();
+        fs::write(root.join("b.md"), "quiet").unwrap();
+
+        let mut previewers = Previewers::default();
+        previewers.register(Box::new(Shouting));
+        let previewers = Arc::new(previewers);
+
+        let a = PreviewJob::spawn(Arc::clone(&previewers), root.join("a.txt"), || {})
+            .wait()
+            .unwrap();
+        assert_eq!(a.previewer, "Shouting");
+        assert_eq!(previewers.preview(&root.join("b.md")).unwrap().previewer, "Text");
+
+        let mut cache = PreviewCache::default();
+        let a = Arc::new(a);
+        cache.insert(Arc::clone(&a));
+        assert_eq!(cache.get(&root.join("a.txt")), Some(a.clone()));
+        assert!(cache.get(&root.join("b.md")).is_none());
+
+        //Outdated once the file changed
+        let mut old = (*a).clone();
+        old.modified = SystemTime::UNIX_EPOCH;
+        cache.insert(Arc::new(old));
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(&root.join("a.txt")).is_none());
+    }
+}
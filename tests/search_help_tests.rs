@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use win_expl::search_engine::{
+        parameter_parser::SearchEngineParameter,
+        search_help::{FilterKind, SearchFilter, SearchHelp},
+    };
+
+    #[test]
+    fn test_keys_and_flags_parse() {
+        let p = SearchEngineParameter::parse_search_term("report ext:pdf depth:2!h").unwrap();
+        assert_eq!(p.term.as_deref(), Some("report"));
+        assert_eq!(p.extension.as_deref(), Some("pdf"));
+        assert_eq!(p.depth, 2);
+        assert!(p.search_hidden_dirs);
+        assert!(!p.search_bin_dirs);
+
+        //Unknown keys stay part of the term, broken values are skipped
+        let p = SearchEngineParameter::parse_search_term("a size:3!dx!q").unwrap();
+        assert_eq!(p.term.as_deref(), Some("a size:3"));
+        assert_eq!(p.depth, 0);
+    }
+
+    #[test]
+    fn test_validate() {
+        let help = SearchHelp::default();
+        assert_eq!(help.validate("report.pdf!d2!h"), Ok(()));
+        assert_eq!(help.validate("report ext:pdf!"), Ok(()));
+        assert_eq!(help.validate(""), Ok(()));
+
+        assert!(help.validate("report!q").unwrap_err().contains("!q"));
+        assert!(help.validate("report size:3").unwrap_err().contains("size:3"));
+        assert!(help.validate("report!dx").unwrap_err().contains("number"));
+        assert!(help.validate("report ext:").is_err());
+        assert!(help.validate("report.").is_err());
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let help = SearchHelp::default();
+        let flags: Vec<String> = help.suggestions("report!", 20).into_iter().map(|s| s.text).collect();
+        assert_eq!(flags.len(), 7);
+        assert!(flags.contains(&String::from("report!h")));
+
+        //Flags that are already there arent suggested again
+        let flags: Vec<String> = help.suggestions("report!h!", 20).into_iter().map(|s| s.text).collect();
+        assert!(!flags.contains(&String::from("report!h!h")));
+        assert!(flags.contains(&String::from("report!h!d")));
+        assert!(help.suggestions("report!h", 20).is_empty());
+
+        let d = help.suggestions("report!d", 20);
+        assert_eq!(d.len(), 1);
+        assert!(d[0].label.starts_with("!d<depth>"));
+
+        let keys: Vec<String> = help.suggestions("report EXT:pd", 20).into_iter().map(|s| s.text).collect();
+        assert_eq!(keys, vec![String::from("report ext:pd")]);
+        assert_eq!(help.suggestions("report :", 20).len(), 3);
+        assert!(help.suggestions("report", 20).is_empty());
+        assert_eq!(help.suggestions("report!", 3).len(), 3);
+    }
+
+    #[test]
+    fn test_register() {
+        let mut help = SearchHelp::default();
+        let count = help.filters().len();
+        help.register(SearchFilter {
+            name: "min",
+            kind: FilterKind::Key,
+            syntax: "min:<depth>",
+            description: "Test",
+            example: "a min:1",
+            takes_value: true,
+            apply: |p, v| {
+                p.depth = v.parse().map_err(|_| String::from("Not a number"))?;
+                Ok(())
+            },
+        });
+        assert_eq!(help.filters().len(), count + 1);
+        assert_eq!(help.validate("a min:4"), Ok(()));
+        let p = SearchEngineParameter::parse_with("a min:4", &help).unwrap();
+        assert_eq!(p.depth, 4);
+        assert_eq!(p.term.as_deref(), Some("a"));
+
+        //Same token replaces the old filter
+        help.register(SearchFilter {
+            description: "Replaced",
+            ..help.filters().last().unwrap().clone()
+        });
+        assert_eq!(help.filters().len(), count + 1);
+        assert_eq!(help.filters().last().unwrap().description, "Replaced");
+    }
+}
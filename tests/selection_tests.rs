@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod tests {
+    use win_expl::selection::Selection;
+
+    #[test]
+    fn test_ranges_and_rows_for() {
+        let mut selection = Selection::default();
+        selection.reset(10);
+        selection.set(2, true);
+        selection.set_range(5..=7, true);
+        selection.set(6, false);
+        //Past the end is ignored
+        selection.set_range(8..=20, true);
+        assert_eq!(selection.rows(), vec![2, 5, 7, 8, 9]);
+        assert_eq!(selection.len(), 5);
+
+        assert_eq!(selection.rows_for(7), vec![2, 5, 7, 8, 9]);
+        assert_eq!(selection.rows_for(3), vec![3]);
+
+        selection.reset(3);
+        assert!(selection.is_empty());
+        assert_eq!(selection.row_count(), 3);
+    }
+
+    #[test]
+    fn test_select_all_and_invert() {
+        let mut selection = Selection::default();
+        selection.reset(5);
+        selection.set(1, true);
+        selection.set(3, true);
+        selection.invert();
+        assert_eq!(selection.rows(), vec![0, 2, 4]);
+
+        selection.select_all();
+        assert_eq!(selection.len(), 5);
+        selection.invert();
+        assert!(selection.is_empty());
+
+        selection.reset(0);
+        selection.select_all();
+        selection.set(0, true);
+        assert!(selection.is_empty());
+    }
+}
@@ -0,0 +1,109 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::fs;
+    use win_expl::search_engine::{
+        result::SearchEngineResult,
+        sort::{self, SortColumn, SortKey, SortOrder},
+    };
+
+    fn init_folder_structure() -> TempDir {
+        let root = TempDir::new("sort");
+        fs::create_dir_all(root.join("zeta")).unwrap();
+        fs::create_dir_all(root.join("Alpha")).unwrap();
+        fs::write(root.join("file10.txt"), "1234567890").unwrap();
+        fs::write(root.join("file2.txt"), "12").unwrap();
+        fs::write(root.join("File1.log"), "1").unwrap();
+        fs::write(root.join("file02.txt"), "12").unwrap();
+        fs::write(root.join("b.md"), "123").unwrap();
+        root
+    }
+
+    fn load() -> Vec<SearchEngineResult> {
+        let root = init_folder_structure();
+        fs::read_dir(&root)
+            .unwrap()
+            .map(|e| SearchEngineResult::from(&e.unwrap()))
+            .collect()
+    }
+
+    fn names(results: &[SearchEngineResult]) -> Vec<&str> {
+        results.iter().map(|r| r.name.as_str()).collect()
+    }
+
+    fn order(folders_first: bool, columns: &[(SortKey, bool)]) -> SortOrder {
+        SortOrder {
+            folders_first,
+            columns: columns
+                .iter()
+                .map(|(key, descending)| SortColumn {
+                    key: *key,
+                    descending: *descending,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_natural_name_order_folders_first() {
+        let result = sort::sort_results(load(), &SortOrder::default());
+        assert_eq!(
+            names(&result),
+            vec!["Alpha", "zeta", "b.md", "File1.log", "file02.txt", "file2.txt", "file10.txt"]
+        );
+    }
+
+    #[test]
+    fn test_descending_name_without_folders_first() {
+        let result = sort::sort_results(load(), &order(false, &[(SortKey::Name, true)]));
+        assert_eq!(
+            names(&result),
+            vec!["zeta", "file10.txt", "file2.txt", "file02.txt", "File1.log", "b.md", "Alpha"]
+        );
+    }
+
+    #[test]
+    fn test_secondary_key_breaks_ties() {
+        let result = sort::sort_results(
+            load(),
+            &order(true, &[(SortKey::Size, true), (SortKey::Name, false)]),
+        );
+        assert_eq!(&names(&result)[2..], &["file10.txt", "b.md", "file02.txt", "file2.txt", "File1.log"]);
+    }
+
+    #[test]
+    fn test_extension_and_type_sort() {
+        let result = sort::sort_results(
+            load(),
+            &order(false, &[(SortKey::Extension, false), (SortKey::Name, false)]),
+        );
+        assert_eq!(
+            names(&result),
+            vec!["Alpha", "zeta", "File1.log", "b.md", "file02.txt", "file2.txt", "file10.txt"]
+        );
+
+        let result = sort::sort_results(load(), &order(false, &[(SortKey::Type, false)]));
+        assert!(result[0].file_type.is_dir() && result[1].file_type.is_dir());
+    }
+
+    #[test]
+    fn test_toggle_keeps_previous_keys() {
+        let mut o = SortOrder::default();
+        o.toggle(SortKey::Name);
+        assert_eq!(o.primary(), Some(SortColumn { key: SortKey::Name, descending: true }));
+
+        o.toggle(SortKey::Size);
+        o.toggle(SortKey::Modified);
+        o.toggle(SortKey::Size);
+        let keys: Vec<SortKey> = o.columns.iter().map(|c| c.key).collect();
+        assert_eq!(keys, vec![SortKey::Size, SortKey::Modified, SortKey::Name]);
+        assert!(!o.columns[0].descending);
+
+        o.toggle(SortKey::Path);
+        assert_eq!(o.columns.len(), 3);
+        assert_eq!(SortKey::from_column(2), Some(SortKey::Type));
+        assert_eq!(SortKey::from_column(9), None);
+    }
+}
@@ -0,0 +1,127 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TempDir;
+    use std::{fs, path::Path};
+    use win_expl::{
+        app_state::{Action, AppState},
+        search_engine::sort::SortKey,
+        tabs::Tabs,
+    };
+
+    fn setup(name: &str) -> TempDir {
+        let root = TempDir::new(&format!("tabs_{}", name));
+        fs::create_dir_all(root.join("a")).unwrap();
+        fs::create_dir_all(root.join("b")).unwrap();
+        fs::write(root.join("a").join("report.txt"), "1234").unwrap();
+        fs::write(root.join("a").join("notes.md"), "12").unwrap();
+        root
+    }
+
+    fn path(p: &Path) -> String {
+        p.to_string_lossy().to_string()
+    }
+
+    fn tab(p: &Path) -> AppState {
+        let mut state = AppState::default();
+        state.dispatch(Action::Navigate(path(p)));
+        state
+    }
+
+    #[test]
+    fn test_open_close_and_switch() {
+        let root = setup("switch");
+        let mut tabs = Tabs::default();
+        tabs.active_mut().dispatch(Action::Navigate(path(&root)));
+
+        //New tabs open next to the active one and stay in the background
+        assert_eq!(tabs.open(tab(&root.join("b"))), 1);
+        assert_eq!(tabs.open(tab(&root.join("a"))), 1);
+        let title = root.file_name().unwrap().to_string_lossy().to_string();
+        assert_eq!(tabs.titles(), vec![title.as_str(), "a", "b"]);
+        assert_eq!(tabs.active_index(), 0);
+
+        assert!(tabs.switch(2));
+        assert!(!tabs.switch(2));
+        assert!(!tabs.switch(3));
+        assert_eq!(tabs.active().path, path(&root.join("b")));
+        assert_eq!(tabs.neighbour(1), 0);
+        assert_eq!(tabs.neighbour(-1), 1);
+
+        //The tabs dont share their state
+        tabs.active_mut().dispatch(Action::Navigate(path(&root.join("a"))));
+        assert!(tabs.active().can_go_back());
+        tabs.switch(1);
+        assert!(!tabs.active().can_go_back());
+
+        assert!(tabs.close(0));
+        assert_eq!(tabs.active_index(), 0);
+        assert_eq!(tabs.active().path, path(&root.join("a")));
+        assert!(tabs.close(0));
+        assert_eq!(tabs.count(), 1);
+        assert!(!tabs.close(0));
+        assert_eq!(tabs.titles(), vec!["a"]);
+    }
+
+    #[test]
+    fn test_closing_the_last_active_tab_activates_the_one_before() {
+        let root = setup("close");
+        let mut tabs = Tabs::default();
+        tabs.active_mut().dispatch(Action::Navigate(path(&root)));
+        tabs.open(tab(&root.join("a")));
+        tabs.switch(1);
+
+        assert!(tabs.close(1));
+        assert_eq!(tabs.active_index(), 0);
+        assert_eq!(tabs.active().path, path(&root));
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let root = setup("session");
+        //The data directory doesnt exist on the first start
+        let file = root.join("data").join("session.json");
+        let mut tabs = Tabs::default();
+        tabs.active_mut().dispatch(Action::Navigate(path(&root)));
+        tabs.active_mut().dispatch(Action::Navigate(path(&root.join("a"))));
+        tabs.active_mut().dispatch(Action::Sort(SortKey::Size));
+        tabs.active_mut().dispatch(Action::Search("report depth:1".into()));
+        let index = tabs.open(tab(&root.join("b")));
+        tabs.switch(index);
+        tabs.save(&file).unwrap();
+
+        let mut loaded = Tabs::load(&file).unwrap();
+        assert_eq!(loaded.count(), 2);
+        assert_eq!(loaded.active_index(), 1);
+        assert_eq!(loaded.titles(), vec!["a", "b"]);
+        //Nothing is listed until the tab is shown
+        assert!(!loaded.active().is_loaded());
+
+        loaded.switch(0);
+        let first = loaded.active_mut();
+        assert_eq!(first.query, "report depth:1");
+        assert_eq!(first.sort.primary().map(|c| c.key), Some(SortKey::Size));
+        first.dispatch(Action::Refresh);
+        assert!(first.is_loaded());
+        assert_eq!(first.results.len(), 1);
+        first.dispatch(Action::Back);
+        assert_eq!(first.path, path(&root));
+    }
+
+    #[test]
+    fn test_load_skips_tabs_without_path() {
+        let root = setup("empty");
+        let file = root.join("session.json");
+        let mut tabs = Tabs::default();
+        let index = tabs.open(tab(&root.join("a")));
+        tabs.switch(index);
+        tabs.save(&file).unwrap();
+
+        let loaded = Tabs::load(&file).unwrap();
+        assert_eq!(loaded.titles(), vec!["a"]);
+        assert_eq!(loaded.active_index(), 0);
+
+        assert!(Tabs::load(root.join("missing.json")).is_err());
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use win_expl::virtual_list::VirtualList;
+
+    #[test]
+    fn test_rows_are_formatted_once() {
+        let data: Vec<u32> = (0..100).collect();
+        let calls = Cell::new(0);
+        let format = |v: &u32| {
+            calls.set(calls.get() + 1);
+            format!("row {}", v)
+        };
+
+        let list: VirtualList<String> = VirtualList::new(50);
+        list.reset(data.len());
+        let rows = list.rows(&data, 10..20, format);
+        assert_eq!(rows.len(), 10);
+        assert_eq!(*rows[0], "row 10");
+        assert_eq!(calls.get(), 10);
+
+        list.rows(&data, 15..20, format);
+        assert_eq!(calls.get(), 10, "Cached rows must not be formatted again");
+        assert_eq!(list.cached_rows(), 10);
+    }
+
+    #[test]
+    fn test_out_of_range_and_reset() {
+        let data: Vec<u32> = (0..5).collect();
+        let list: VirtualList<u32> = VirtualList::new(50);
+        list.reset(data.len());
+        assert_eq!(list.rows(&data, 3..10, |v| *v).len(), 2);
+        assert!(list.row(&data, 5, |v| *v).is_none());
+
+        list.reset(2);
+        assert_eq!(list.cached_rows(), 0);
+        assert!(list.row(&data, 3, |v| *v).is_none(), "Rows past the new length are gone");
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let data: Vec<u32> = (0..1000).collect();
+        let list: VirtualList<u32> = VirtualList::new(100);
+        list.reset(data.len());
+        list.rows(&data, 0..1000, |v| *v);
+        assert!(list.cached_rows() <= 100);
+        assert_eq!(*list.row(&data, 999, |v| *v).unwrap(), 999);
+    }
+}